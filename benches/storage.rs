@@ -0,0 +1,110 @@
+//! Compares the indexed input storage with hashed collections, at a high event rate.
+//!
+//! Run with `cargo bench --bench storage`.
+
+use fishsticks::storage::{InputMap, InputSet};
+use fishsticks::{Button, BUTTONS};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Button events to process, such as from a controller polled at 8 kHz for two minutes.
+const EVENTS: usize = 1_000_000;
+
+/// Events between two updates, which clear the edge flags.
+const EVENTS_PER_UPDATE: usize = 8;
+
+/// The operations done for each event, mirroring how digital inputs track a button.
+trait Storage: Default {
+    fn event(&mut self, button: Button, pressed: bool, now: Instant);
+    fn update(&mut self);
+}
+
+#[derive(Default)]
+struct Hashed {
+    activated: HashMap<Button, Instant>,
+    just_activated: HashSet<Button>,
+    just_deactivated: HashSet<Button>,
+}
+
+impl Storage for Hashed {
+    fn event(&mut self, button: Button, pressed: bool, now: Instant) {
+        if pressed {
+            if let Entry::Vacant(entry) = self.activated.entry(button) {
+                entry.insert(now);
+                self.just_activated.insert(button);
+                self.just_deactivated.remove(&button);
+            }
+        } else if self.activated.remove(&button).is_some() {
+            self.just_activated.remove(&button);
+            self.just_deactivated.insert(button);
+        }
+    }
+
+    fn update(&mut self) {
+        self.just_activated.clear();
+        self.just_deactivated.clear();
+    }
+}
+
+#[derive(Default)]
+struct Indexed {
+    activated: InputMap<Button, Instant>,
+    just_activated: InputSet<Button>,
+    just_deactivated: InputSet<Button>,
+}
+
+impl Storage for Indexed {
+    fn event(&mut self, button: Button, pressed: bool, now: Instant) {
+        if pressed {
+            if !self.activated.contains_key(button) {
+                self.activated.insert(button, now);
+                self.just_activated.insert(button);
+                self.just_deactivated.remove(button);
+            }
+        } else if self.activated.remove(button).is_some() {
+            self.just_activated.remove(button);
+            self.just_deactivated.insert(button);
+        }
+    }
+
+    fn update(&mut self) {
+        self.just_activated.clear();
+        self.just_deactivated.clear();
+    }
+}
+
+fn run<S: Storage>() -> Duration {
+    let mut storage = S::default();
+    let now = Instant::now();
+    let start = Instant::now();
+    for event in 0..EVENTS {
+        // Cycles through the buttons, pressing them on one pass and releasing them on the next.
+        let button = BUTTONS[event % BUTTONS.len()];
+        let pressed = (event / BUTTONS.len()).is_multiple_of(2);
+        storage.event(black_box(button), pressed, now);
+        if event.is_multiple_of(EVENTS_PER_UPDATE) {
+            storage.update();
+        }
+    }
+    black_box(&storage);
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    let per_event = elapsed.as_nanos() as f64 / EVENTS as f64;
+    println!(
+        "{:<8} {:>10.2?} total, {:>6.2} ns per event",
+        name, elapsed, per_event
+    );
+}
+
+fn main() {
+    // Warms up caches and the allocator before measuring.
+    run::<Hashed>();
+    run::<Indexed>();
+
+    report("hashed", run::<Hashed>());
+    report("indexed", run::<Indexed>());
+}
@@ -0,0 +1,174 @@
+//! Deadzones, ranges and threshold crossings of analog inputs.
+
+/// The minimum value of an analog input.
+pub const ANALOG_MIN: f32 = -1.0;
+/// The maximum value of an analog input.
+pub const ANALOG_MAX: f32 = 1.0;
+
+/// Scales a value so that `min` reads as [`ANALOG_MIN`] and `max` reads as [`ANALOG_MAX`],
+/// and inverts it if `inverted` is set.
+///
+/// `min` has to be negative and `max` positive. Values beyond them are clamped.
+pub fn scale_to_range(value: f32, min: f32, max: f32, inverted: bool) -> f32 {
+    let scaled = if value < 0.0 {
+        value / -min
+    } else {
+        value / max
+    };
+    let scaled = scaled.clamp(ANALOG_MIN, ANALOG_MAX);
+
+    if inverted {
+        -scaled
+    } else {
+        scaled
+    }
+}
+
+/// Checks if a value is outside a deadzone. Values on its edge are outside.
+pub fn is_outside_deadzone(value: f32, deadzone: f32) -> bool {
+    value.abs() >= deadzone.abs()
+}
+
+/// Remaps a value outside a deadzone to the full range, so that it starts at `0.0` on the edge
+/// of the deadzone. Returns `0.0` inside it.
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    let deadzone = deadzone.abs();
+    if !is_outside_deadzone(value, deadzone) || deadzone >= ANALOG_MAX {
+        return 0.0;
+    }
+
+    let remapped = (value.abs() - deadzone) / (ANALOG_MAX - deadzone);
+    if value < 0.0 {
+        -remapped
+    } else {
+        remapped
+    }
+}
+
+/// Converts a value to a digital one, which is either [`ANALOG_MIN`] or [`ANALOG_MAX`] when a
+/// nonzero value is outside the deadzone, and `0.0` otherwise.
+pub fn to_digital(value: f32, deadzone: f32) -> f32 {
+    if !is_outside_deadzone(value, deadzone) {
+        0.0
+    } else if value < 0.0 {
+        ANALOG_MIN
+    } else if value > 0.0 {
+        ANALOG_MAX
+    } else {
+        0.0
+    }
+}
+
+/// A change of whether an input is activated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// The input was activated.
+    Activated,
+    /// The input was deactivated.
+    Deactivated,
+}
+
+/// Tracks whether an analog input is outside a deadzone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Threshold {
+    active: bool,
+}
+
+impl Threshold {
+    /// Creates a threshold that starts activated or not.
+    pub const fn new(active: bool) -> Self {
+        Self { active }
+    }
+
+    /// Checks if the input is outside the deadzone.
+    pub const fn is_active(self) -> bool {
+        self.active
+    }
+
+    /// Records a new value of the input, and returns whether it was activated or deactivated.
+    ///
+    /// Active inputs are only deactivated once they fall `hysteresis` below the deadzone.
+    /// An input that passes through the whole deadzone between two values, such as a stick
+    /// flicked from one side to the other, is activated again even though it never
+    /// deactivated.
+    pub fn update(
+        &mut self,
+        value: f32,
+        old_value: Option<f32>,
+        deadzone: f32,
+        hysteresis: f32,
+    ) -> Option<Edge> {
+        let was_active = self.active;
+        let threshold = if was_active {
+            deadzone - hysteresis
+        } else {
+            deadzone
+        };
+
+        if value.abs() < threshold {
+            self.active = false;
+            was_active.then_some(Edge::Deactivated)
+        } else {
+            self.active = true;
+            let flipped = old_value.is_some_and(|old_value| value.signum() != old_value.signum());
+            (!was_active || flipped).then_some(Edge::Activated)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzones_remap_to_the_full_range() {
+        assert_eq!(apply_deadzone(0.25, 0.5), 0.0);
+        assert_eq!(apply_deadzone(0.5, 0.5), 0.0);
+        assert_eq!(apply_deadzone(0.75, 0.5), 0.5);
+        assert_eq!(apply_deadzone(-0.75, 0.5), -0.5);
+        assert_eq!(apply_deadzone(1.0, 0.5), 1.0);
+        // A negative deadzone is as wide as a positive one.
+        assert_eq!(apply_deadzone(0.75, -0.5), 0.5);
+        // Nothing is outside a deadzone that covers the whole range.
+        assert_eq!(apply_deadzone(1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn thresholds_report_edges() {
+        let mut threshold = Threshold::default();
+        assert_eq!(threshold.update(0.1, None, 0.2, 0.0), None);
+        assert_eq!(
+            threshold.update(0.5, Some(0.1), 0.2, 0.0),
+            Some(Edge::Activated)
+        );
+        assert_eq!(threshold.update(0.8, Some(0.5), 0.2, 0.0), None);
+        assert_eq!(
+            threshold.update(0.1, Some(0.8), 0.2, 0.0),
+            Some(Edge::Deactivated)
+        );
+        assert!(!threshold.is_active());
+    }
+
+    #[test]
+    fn thresholds_apply_hysteresis() {
+        let mut threshold = Threshold::new(true);
+        assert_eq!(threshold.update(0.15, Some(0.5), 0.2, 0.1), None);
+        assert!(threshold.is_active());
+        assert_eq!(
+            threshold.update(0.05, Some(0.15), 0.2, 0.1),
+            Some(Edge::Deactivated)
+        );
+        // Inactive inputs are only activated outside the deadzone itself.
+        assert_eq!(threshold.update(0.15, Some(0.05), 0.2, 0.1), None);
+    }
+
+    #[test]
+    fn flicks_through_the_deadzone_activate_again() {
+        let mut threshold = Threshold::new(true);
+        assert_eq!(
+            threshold.update(-0.9, Some(0.9), 0.2, 0.0),
+            Some(Edge::Activated)
+        );
+        assert!(threshold.is_active());
+    }
+}
@@ -0,0 +1,262 @@
+//! Edge detection of digital inputs.
+
+/// The number of inputs a [`DigitalState`] holds.
+///
+/// Enough for the buttons of a gamepad, but not for the virtual buttons of fishsticks.
+pub const MAX_INPUTS: usize = 64;
+
+/// The state of a single digital input, along with its change since the last update.
+///
+/// Its methods hold the edge detection, so that inputs kept in any kind of storage behave
+/// alike. [`DigitalState`] keeps up to [`MAX_INPUTS`] of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputState {
+    /// Whether the input is activated.
+    pub activated: bool,
+    /// Whether the input has been activated since the last update.
+    pub just_activated: bool,
+    /// Whether the input has been deactivated since the last update.
+    pub just_deactivated: bool,
+    /// Whether the input was released during the update it was activated in, while latching,
+    /// and is deactivated on the next update.
+    pub latched: bool,
+}
+
+impl InputState {
+    /// Creates the state of an input that is not activated.
+    pub const fn new() -> Self {
+        Self {
+            activated: false,
+            just_activated: false,
+            just_deactivated: false,
+            latched: false,
+        }
+    }
+
+    /// Activates the input, and returns whether it was deactivated before.
+    pub fn activate(&mut self) -> bool {
+        // Pressed again before its latched release, so it simply stays activated.
+        self.latched = false;
+
+        let activated = !self.activated;
+        if activated {
+            self.activated = true;
+            self.just_activated = true;
+            self.just_deactivated = false;
+        }
+        activated
+    }
+
+    /// Deactivates the input, and returns whether it was activated before.
+    ///
+    /// If `latching` is set and the input has just been activated, it stays activated until
+    /// the next update instead.
+    pub fn deactivate(&mut self, latching: bool) -> bool {
+        if latching && self.just_activated {
+            self.latched = true;
+            return false;
+        }
+
+        let deactivated = self.activated;
+        if deactivated {
+            self.activated = false;
+            self.just_activated = false;
+            self.just_deactivated = true;
+        }
+        deactivated
+    }
+
+    /// Starts a new update, forgetting whether the input was just activated or deactivated.
+    /// Returns whether a latched input was deactivated.
+    pub fn update(&mut self) -> bool {
+        self.just_activated = false;
+        self.just_deactivated = false;
+
+        let latched = core::mem::take(&mut self.latched);
+        if latched {
+            self.activated = false;
+            self.just_deactivated = true;
+        }
+        latched
+    }
+}
+
+/// The state of up to [`MAX_INPUTS`] digital inputs, identified by index, along with their
+/// changes since the last update.
+///
+/// # Panics
+///
+/// Every method that takes an index panics if it is [`MAX_INPUTS`] or more.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DigitalState {
+    activated: u64,
+    just_activated: u64,
+    just_deactivated: u64,
+    // Inputs released during the update they were activated in, while latching.
+    latched: u64,
+    latching: bool,
+}
+
+fn bit(index: usize) -> u64 {
+    assert!(index < MAX_INPUTS, "Input index {index} out of range");
+    1 << index
+}
+
+fn assign(bits: &mut u64, bit: u64, set: bool) {
+    if set {
+        *bits |= bit;
+    } else {
+        *bits &= !bit;
+    }
+}
+
+impl DigitalState {
+    /// Creates a state where no input is activated.
+    pub const fn new() -> Self {
+        Self {
+            activated: 0,
+            just_activated: 0,
+            just_deactivated: 0,
+            latched: 0,
+            latching: false,
+        }
+    }
+
+    /// Checks if an input is activated.
+    pub fn activated(&self, index: usize) -> bool {
+        self.activated & bit(index) != 0
+    }
+
+    /// Checks if an input has just been activated.
+    pub fn just_activated(&self, index: usize) -> bool {
+        self.just_activated & bit(index) != 0
+    }
+
+    /// Checks if an input has just been deactivated.
+    pub fn just_deactivated(&self, index: usize) -> bool {
+        self.just_deactivated & bit(index) != 0
+    }
+
+    /// Checks if any input is activated.
+    pub fn any_activated(&self) -> bool {
+        self.activated != 0
+    }
+
+    /// Keeps inputs that are released within the update they were activated in activated until
+    /// the next update, so that every press is seen at least once. Disabled by default.
+    pub fn set_latching(&mut self, enabled: bool) {
+        self.latching = enabled;
+    }
+
+    /// Gets the state of an input.
+    pub fn get(&self, index: usize) -> InputState {
+        let bit = bit(index);
+        InputState {
+            activated: self.activated & bit != 0,
+            just_activated: self.just_activated & bit != 0,
+            just_deactivated: self.just_deactivated & bit != 0,
+            latched: self.latched & bit != 0,
+        }
+    }
+
+    fn set(&mut self, index: usize, state: InputState) {
+        let bit = bit(index);
+        assign(&mut self.activated, bit, state.activated);
+        assign(&mut self.just_activated, bit, state.just_activated);
+        assign(&mut self.just_deactivated, bit, state.just_deactivated);
+        assign(&mut self.latched, bit, state.latched);
+    }
+
+    /// Activates an input. Does nothing if it is activated already.
+    pub fn activate(&mut self, index: usize) {
+        let mut state = self.get(index);
+        state.activate();
+        self.set(index, state);
+    }
+
+    /// Deactivates an input. Does nothing if it is not activated.
+    pub fn deactivate(&mut self, index: usize) {
+        let mut state = self.get(index);
+        state.deactivate(self.latching);
+        self.set(index, state);
+    }
+
+    /// Starts a new update, forgetting which inputs were just activated or deactivated.
+    ///
+    /// Call this before applying the changes of the update.
+    pub fn update(&mut self) {
+        for index in 0..MAX_INPUTS {
+            let mut state = self.get(index);
+            state.update();
+            self.set(index, state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_last_one_update() {
+        let mut state = DigitalState::new();
+        state.activate(3);
+        assert!(state.activated(3) && state.just_activated(3));
+
+        state.update();
+        assert!(state.activated(3) && !state.just_activated(3));
+
+        state.deactivate(3);
+        assert!(!state.activated(3) && state.just_deactivated(3));
+        state.update();
+        assert!(!state.just_deactivated(3));
+    }
+
+    #[test]
+    fn taps_are_latched_until_the_next_update() {
+        let mut state = DigitalState::new();
+        state.set_latching(true);
+        state.activate(0);
+        state.deactivate(0);
+        assert!(state.activated(0) && state.just_activated(0));
+
+        state.update();
+        assert!(!state.activated(0) && state.just_deactivated(0));
+    }
+
+    #[test]
+    fn pressing_again_cancels_a_latched_release() {
+        let mut state = DigitalState::new();
+        state.set_latching(true);
+        state.activate(0);
+        state.deactivate(0);
+        state.activate(0);
+
+        state.update();
+        assert!(state.activated(0) && !state.just_deactivated(0));
+    }
+
+    #[test]
+    fn taps_without_latching_are_only_released() {
+        let mut state = DigitalState::new();
+        state.activate(63);
+        state.deactivate(63);
+        assert!(!state.activated(63) && !state.just_activated(63));
+        assert!(state.just_deactivated(63));
+    }
+
+    #[test]
+    fn input_states_report_their_changes() {
+        let mut state = InputState::new();
+        assert!(state.activate() && !state.activate());
+        assert!(!state.deactivate(true) && state.latched);
+        assert!(state.update() && !state.activated);
+        assert!(!state.deactivate(false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn indices_beyond_the_maximum_panic() {
+        DigitalState::new().activate(MAX_INPUTS);
+    }
+}
@@ -0,0 +1,17 @@
+//! The input semantics of [fishsticks](https://docs.rs/fishsticks), without a backend.
+//!
+//! This crate is `no_std` and does not allocate, so that embedded projects, such as handhelds
+//! that read their buttons and sticks through their own HAL, can make inputs behave like
+//! fishsticks does. It holds the deadzone, range and threshold logic of analog inputs, and
+//! the edge detection of digital inputs.
+//!
+//! The inputs of fishsticks are built on top of it. Its digital inputs track far more inputs
+//! than [`digital::MAX_INPUTS`], so they keep their state in sets of their own and change it
+//! through [`digital::InputState`].
+//!
+//! Response curves need `powf`, which `core` lacks, so they are left to the caller.
+
+#![no_std]
+
+pub mod analog;
+pub mod digital;
@@ -0,0 +1,181 @@
+//! Interactive conformance runner.
+//!
+//! Walks through every button and axis, asks for each to be actuated on a physical controller,
+//! and checks what the backend reports. A JSON report is printed to stdout, or written to the
+//! path given as the first argument.
+//!
+//! Run with `cargo run --example conformance [report.json]`.
+
+use fishsticks::{BackendKind, GamepadContext, GamepadId, AXES, BUTTONS};
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+use std::{env, fs, thread};
+
+/// How long to wait for each input before skipping it.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// How often to poll the backend.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// How far an axis has to travel to count as fully actuated.
+const FULL_TRAVEL: f32 = 0.9;
+
+fn main() -> fishsticks::error::Result<()> {
+    let report_path = env::args().nth(1);
+    let mut context = GamepadContext::init()?;
+
+    eprintln!("Connect a gamepad...");
+    let id = loop {
+        context.update()?;
+        if let Some((id, _)) = context.gamepads().next() {
+            break id;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let mut buttons = Vec::new();
+    for button in BUTTONS {
+        eprintln!(
+            "Press and release {:?} (skipped after {:?})",
+            button, TIMEOUT
+        );
+        let result = check_button(&mut context, id, button)?;
+        eprintln!("  {}", result);
+        buttons.push((format!("{:?}", button), result));
+    }
+
+    let mut axes = Vec::new();
+    for axis in AXES {
+        eprintln!(
+            "Move {:?} through its full range, then let go (skipped after {:?})",
+            axis, TIMEOUT
+        );
+        let (result, min, max) = check_axis(&mut context, id, axis)?;
+        eprintln!("  {} (min {:.2}, max {:.2})", result, min, max);
+        axes.push((format!("{:?}", axis), result, min, max));
+    }
+
+    let report = report(context.backend(), &buttons, &axes);
+    match report_path {
+        Some(path) => fs::write(path, report).map_err(|e| e.to_string())?,
+        None => println!("{}", report),
+    }
+
+    Ok(())
+}
+
+/// Waits for a button to be pressed and released, noting any other buttons pressed meanwhile.
+fn check_button(
+    context: &mut GamepadContext,
+    id: GamepadId,
+    button: fishsticks::Button,
+) -> fishsticks::error::Result<String> {
+    let start = Instant::now();
+    let mut pressed = false;
+    let mut unexpected = Vec::new();
+
+    while start.elapsed() < TIMEOUT {
+        context.update()?;
+        let gamepad = match context.gamepad(id) {
+            Some(gamepad) => gamepad,
+            None => return Ok("disconnected".to_string()),
+        };
+
+        for other in BUTTONS {
+            if other != button && gamepad.digital_inputs.just_activated(other) {
+                unexpected.push(format!("{:?}", other));
+            }
+        }
+
+        if gamepad.digital_inputs.just_activated(button) {
+            pressed = true;
+        }
+        if pressed && gamepad.digital_inputs.just_deactivated(button) {
+            return Ok(if unexpected.is_empty() {
+                "pass".to_string()
+            } else {
+                format!("pass, also reported {}", unexpected.join(" "))
+            });
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    Ok(if pressed {
+        "no release".to_string()
+    } else if unexpected.is_empty() {
+        "skipped".to_string()
+    } else {
+        format!("fail, reported {}", unexpected.join(" "))
+    })
+}
+
+/// Records the range of an axis until it returns to rest after reaching full travel.
+fn check_axis(
+    context: &mut GamepadContext,
+    id: GamepadId,
+    axis: fishsticks::Axis,
+) -> fishsticks::error::Result<(String, f32, f32)> {
+    let start = Instant::now();
+    let mut min = 0.0f32;
+    let mut max = 0.0f32;
+
+    while start.elapsed() < TIMEOUT {
+        context.update()?;
+        let value = match context.gamepad(id) {
+            Some(gamepad) => gamepad.analog_inputs.value(axis),
+            None => return Ok(("disconnected".to_string(), min, max)),
+        };
+
+        min = min.min(value);
+        max = max.max(value);
+
+        let reached_full_travel = min <= -FULL_TRAVEL || max >= FULL_TRAVEL;
+        if reached_full_travel && value == 0.0 {
+            return Ok(("pass".to_string(), min, max));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    let result = if min == 0.0 && max == 0.0 {
+        "skipped"
+    } else {
+        "partial travel"
+    };
+    Ok((result.to_string(), min, max))
+}
+
+/// Formats the results as JSON.
+fn report(
+    backend: BackendKind,
+    buttons: &[(String, String)],
+    axes: &[(String, String, f32, f32)],
+) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "{{");
+    let _ = writeln!(report, "  \"backend\": \"{}\",", backend);
+
+    let _ = writeln!(report, "  \"buttons\": [");
+    for (i, (name, result)) in buttons.iter().enumerate() {
+        let separator = if i + 1 < buttons.len() { "," } else { "" };
+        let _ = writeln!(
+            report,
+            "    {{ \"input\": \"{}\", \"result\": \"{}\" }}{}",
+            name, result, separator
+        );
+    }
+    let _ = writeln!(report, "  ],");
+
+    let _ = writeln!(report, "  \"axes\": [");
+    for (i, (name, result, min, max)) in axes.iter().enumerate() {
+        let separator = if i + 1 < axes.len() { "," } else { "" };
+        let _ = writeln!(
+            report,
+            "    {{ \"input\": \"{}\", \"result\": \"{}\", \"min\": {}, \"max\": {} }}{}",
+            name, result, min, max, separator
+        );
+    }
+    let _ = writeln!(report, "  ]");
+    let _ = write!(report, "}}");
+
+    report
+}
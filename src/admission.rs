@@ -0,0 +1,305 @@
+//! Limits on which and how many gamepads a context reports, such as for games with a fixed
+//! number of players, or kiosks that must ignore devices that are not meant to be played with.
+//!
+//! Set them with [`GamepadContext::set_max_gamepads`](crate::GamepadContext::set_max_gamepads),
+//! [`GamepadContext::set_join_policy`](crate::GamepadContext::set_join_policy) and
+//! [`GamepadContext::set_device_filter`](crate::GamepadContext::set_device_filter).
+
+use crate::event::GamepadEvent;
+use crate::{Gamepad, GamepadId, Guid};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// What happens to gamepads that connect while the maximum number of gamepads is reached.
+///
+/// Gamepads that are left out are still read, but are not reported by the context until they
+/// join, so they cannot change the state of the game.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JoinPolicy {
+    /// The gamepad is ignored until it reconnects, even if other gamepads leave.
+    Ignore,
+    /// The gamepad waits until another gamepad disconnects, and then joins in the order it
+    /// connected in.
+    #[default]
+    Queue,
+    /// The gamepad replaces the gamepad that has been idle the longest, if it has been idle for
+    /// at least the given duration. The replaced gamepad waits as with [`Self::Queue`].
+    /// Otherwise, the new gamepad waits itself.
+    ReplaceIdle(Duration),
+}
+
+/// Identifies devices for a [`DeviceFilter`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DeviceMatch {
+    /// Matches the device with this GUID.
+    Guid(Guid),
+    /// Matches devices with this USB vendor ID and, if given, product ID.
+    VendorProduct {
+        /// The vendor ID.
+        vendor: u16,
+        /// The product ID, or `None` to match every product of the vendor.
+        product: Option<u16>,
+    },
+    /// Matches devices whose name matches this pattern, ignoring case. `*` matches any number of
+    /// characters, such as in `"*RGB*"`.
+    Name(String),
+}
+
+impl DeviceMatch {
+    fn matches(&self, guid: Guid, name: Option<&str>) -> bool {
+        match self {
+            Self::Guid(other) => guid == *other,
+            Self::VendorProduct { vendor, product } => {
+                guid.vendor_id() == Some(*vendor)
+                    && product.is_none_or(|product| guid.product_id() == Some(product))
+            }
+            Self::Name(pattern) => name
+                .is_some_and(|name| matches_pattern(&pattern.to_lowercase(), &name.to_lowercase())),
+        }
+    }
+}
+
+/// Checks if `text` matches `pattern`, where `*` matches any number of characters.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<_> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*`, so the whole text has to match.
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Lists the devices that a context reports or ignores, by their identity.
+///
+/// Devices that match a denied entry are ignored. If any entry is allowed, only devices that
+/// match one of them are reported. The default filter reports every device.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeviceFilter {
+    allowed: Vec<DeviceMatch>,
+    denied: Vec<DeviceMatch>,
+}
+
+impl DeviceFilter {
+    /// Creates a filter that reports every device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only reports devices that match this or other allowed entries.
+    pub fn allow(mut self, device: DeviceMatch) -> Self {
+        self.allowed.push(device);
+        self
+    }
+
+    /// Ignores devices that match this entry, even if they are allowed.
+    pub fn deny(mut self, device: DeviceMatch) -> Self {
+        self.denied.push(device);
+        self
+    }
+
+    /// Checks if a device with this GUID and name is reported.
+    pub fn allows(&self, guid: Guid, name: Option<&str>) -> bool {
+        let matches = |device: &DeviceMatch| device.matches(guid, name);
+        !self.denied.iter().any(matches)
+            && (self.allowed.is_empty() || self.allowed.iter().any(matches))
+    }
+}
+
+/// Keeps the gamepads that are left out of a context.
+#[derive(Default)]
+pub(crate) struct Admission {
+    max: Option<usize>,
+    policy: JoinPolicy,
+    // The gamepads reported by the context.
+    joined: HashSet<GamepadId>,
+    // Gamepads that are left out, in the order they were left out in.
+    waiting: Vec<GamepadId>,
+    ignored: HashSet<GamepadId>,
+    filter: DeviceFilter,
+    // Gamepads that the filter denies, which never join nor wait.
+    filtered: HashSet<GamepadId>,
+    // Whether the filter changed since the last update.
+    refilter: bool,
+    // The left out gamepads, between updates.
+    parked: HashMap<GamepadId, Gamepad>,
+    events: Vec<GamepadEvent>,
+}
+
+impl Admission {
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    pub(crate) fn set_max(&mut self, max: Option<usize>) {
+        self.max = max;
+    }
+
+    pub(crate) fn policy(&self) -> JoinPolicy {
+        self.policy
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: JoinPolicy) {
+        self.policy = policy;
+    }
+
+    pub(crate) fn filter(&self) -> &DeviceFilter {
+        &self.filter
+    }
+
+    pub(crate) fn set_filter(&mut self, filter: DeviceFilter) {
+        self.filter = filter;
+        self.refilter = true;
+    }
+
+    /// Iterates over the gamepads that wait to join, in the order they will join in.
+    pub(crate) fn waiting(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.waiting
+            .iter()
+            .copied()
+            .filter(|id| !self.ignored.contains(id))
+    }
+
+    /// Iterates over the gamepads that are left out, for them to be updated.
+    pub(crate) fn parked_mut(&mut self) -> impl Iterator<Item = &mut Gamepad> {
+        self.parked.values_mut()
+    }
+
+    /// Puts the left out gamepads back, so that the backend can update them.
+    pub(crate) fn unpark(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        gamepads.extend(self.parked.drain());
+    }
+
+    /// Takes the events about gamepads that were left out or joined.
+    pub(crate) fn take_events(&mut self) -> impl Iterator<Item = GamepadEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Leaves out the gamepads past the maximum, and lets waiting gamepads join.
+    /// Call right after the backend updated the gamepads.
+    pub(crate) fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        self.joined.retain(|id| gamepads.contains_key(id));
+        self.waiting.retain(|id| gamepads.contains_key(id));
+        self.ignored.retain(|id| gamepads.contains_key(id));
+        self.filtered.retain(|id| gamepads.contains_key(id));
+        for &id in self.waiting.iter().chain(&self.filtered) {
+            if let Some(gamepad) = gamepads.remove(&id) {
+                self.parked.insert(id, gamepad);
+            }
+        }
+        self.apply_filter(gamepads);
+
+        let mut connected: Vec<_> = gamepads
+            .keys()
+            .copied()
+            .filter(|id| !self.joined.contains(id))
+            .collect();
+        connected.sort();
+        for id in connected {
+            if self.has_room() {
+                self.joined.insert(id);
+                continue;
+            }
+
+            match self.policy {
+                JoinPolicy::Ignore => {
+                    self.ignored.insert(id);
+                    self.leave_out(id, gamepads);
+                    self.events.push(GamepadEvent::Ignored(id));
+                }
+                JoinPolicy::Queue => {
+                    self.leave_out(id, gamepads);
+                    self.events.push(GamepadEvent::Queued(id));
+                }
+                JoinPolicy::ReplaceIdle(min_idle) => {
+                    let idle = self
+                        .joined
+                        .iter()
+                        .filter_map(|&id| Some((id, gamepads.get(&id)?.idle_for())))
+                        .filter(|&(_, idle)| idle >= min_idle)
+                        .max_by_key(|&(id, idle)| (idle, std::cmp::Reverse(id)));
+                    match idle {
+                        Some((old, _)) => {
+                            self.joined.remove(&old);
+                            self.leave_out(old, gamepads);
+                            self.joined.insert(id);
+                            self.events.push(GamepadEvent::Replaced { old, new: id });
+                        }
+                        None => {
+                            self.leave_out(id, gamepads);
+                            self.events.push(GamepadEvent::Queued(id));
+                        }
+                    }
+                }
+            }
+        }
+
+        while self.has_room() {
+            let Some(index) = self
+                .waiting
+                .iter()
+                .position(|id| !self.ignored.contains(id))
+            else {
+                break;
+            };
+            let id = self.waiting.remove(index);
+            if let Some(gamepad) = self.parked.remove(&id) {
+                gamepads.insert(id, gamepad);
+                self.joined.insert(id);
+            }
+        }
+    }
+
+    /// Leaves out the gamepads that the filter denies, as if they had never connected.
+    /// After the filter changed, gamepads that it allows again connect as new ones.
+    fn apply_filter(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        let refilter = std::mem::take(&mut self.refilter);
+        if refilter {
+            for id in self.filtered.drain() {
+                if let Some(gamepad) = self.parked.remove(&id) {
+                    gamepads.insert(id, gamepad);
+                }
+            }
+        }
+
+        // Only new gamepads are checked, unless the filter changed.
+        let denied: Vec<_> = gamepads
+            .iter()
+            .filter(|(id, _)| refilter || !self.joined.contains(id))
+            .chain(self.parked.iter().filter(|_| refilter))
+            .filter(|(_, gamepad)| !self.filter.allows(gamepad.guid(), gamepad.name()))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in denied {
+            self.joined.remove(&id);
+            self.waiting.retain(|&other| other != id);
+            self.ignored.remove(&id);
+            if let Some(gamepad) = gamepads.remove(&id) {
+                self.parked.insert(id, gamepad);
+            }
+            self.filtered.insert(id);
+        }
+    }
+
+    fn has_room(&self) -> bool {
+        self.max.is_none_or(|max| self.joined.len() < max)
+    }
+
+    fn leave_out(&mut self, id: GamepadId, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        if let Some(gamepad) = gamepads.remove(&id) {
+            self.parked.insert(id, gamepad);
+            self.waiting.push(id);
+        }
+    }
+}
@@ -1,12 +1,15 @@
 //! Generic analog input support.
 
-use std::collections::{HashMap, HashSet};
-use std::hash::Hash;
+use crate::storage::{InputKind, InputMap, InputSet};
+use crate::timestamp::Timestamp;
+use fishsticks_core::analog::{self as core, Edge, Threshold};
+use std::time::{Duration, Instant};
 
-/// The minimum value of an analog input.
-pub const ANALOG_MIN: f32 = -1.0;
-/// The maximum value of an analog input.
-pub const ANALOG_MAX: f32 = 1.0;
+pub use fishsticks_core::analog::{ANALOG_MAX, ANALOG_MIN};
+/// The analog deadzone that analog inputs start with.
+pub const DEFAULT_ANALOG_DEADZONE: f32 = 0.1;
+/// The digital deadzone that analog inputs start with.
+pub const DEFAULT_DIGITAL_DEADZONE: f32 = 0.5;
 
 /// Wrapper around `f32` for analog inputs.
 #[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -35,6 +38,47 @@ impl From<f32> for AnalogInputValue {
     }
 }
 
+/// A fixed grid that analog values are rounded to, so that every platform and backend reads
+/// bit-identical values, such as for lockstep or rollback netcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quantization {
+    /// 127 steps from `0.0` to `1.0`, so that values fit in an `i8`.
+    I8,
+    /// 32767 steps from `0.0` to `1.0`, so that values fit in an `i16`.
+    I16,
+    /// A number of steps from `0.0` to `1.0`.
+    Steps(u16),
+}
+
+impl Quantization {
+    /// Gets the number of steps from `0.0` to `1.0`.
+    pub fn steps(self) -> u16 {
+        match self {
+            Self::I8 => i8::MAX as u16,
+            Self::I16 => i16::MAX as u16,
+            Self::Steps(steps) => steps.max(1),
+        }
+    }
+
+    /// Rounds a value to the nearest step.
+    pub fn quantize(self, value: f32) -> f32 {
+        let steps = f32::from(self.steps());
+        (value * steps).round() / steps
+    }
+
+    /// Rounds a value to the nearest step, and gets the number of that step, such as to send
+    /// it over the network.
+    pub fn to_step(self, value: f32) -> i32 {
+        (value * f32::from(self.steps())).round() as i32
+    }
+
+    /// Gets the value of a step, as returned by [`Self::to_step`].
+    pub fn from_step(self, step: i32) -> f32 {
+        (step as f32 / f32::from(self.steps())).clamp(ANALOG_MIN, ANALOG_MAX)
+    }
+}
+
 /// Wrapper around `f32` for deadzones.
 #[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub(crate) struct Deadzone(f32);
@@ -53,39 +97,76 @@ impl From<AnalogInputValue> for Deadzone {
 
 /// Container for analog inputs.
 #[derive(Debug)]
-pub struct AnalogInput<T> {
-    inputs: HashMap<T, AnalogInputValue>,
+pub struct AnalogInput<T: InputKind> {
+    inputs: InputMap<T, AnalogInputValue>,
+    // Values set by an emulation layer, which replace the configured backend values.
+    overrides: InputMap<T, AnalogInputValue>,
+    // Low-pass filtered backend values, for inputs with smoothing.
+    smoothed: InputMap<T, AnalogInputValue>,
+    last_changed: InputMap<T, Timestamp>,
+    // When the backend last reported a change of each input.
+    event_times: InputMap<T, Timestamp>,
+    tick: u64,
 
-    just_activated: HashSet<T>,
-    just_deactivated: HashSet<T>,
+    // Values at the start of the current update, and how long the previous update took.
+    previous_values: InputMap<T, f32>,
+    updated_at: Option<Instant>,
+    update_interval: Duration,
+
+    // Inputs outside the analog deadzone, which may lag behind values with hysteresis.
+    active: InputSet<T>,
+    just_activated: InputSet<T>,
+    just_deactivated: InputSet<T>,
     deadzone: Deadzone,
+    deadzones: InputMap<T, Deadzone>,
+    configs: InputMap<T, AxisConfig>,
+    // Backend inputs that report to another input instead of themselves.
+    remaps: InputMap<T, T>,
 
-    just_activated_digital: HashSet<T>,
-    just_deactivated_digital: HashSet<T>,
+    active_digital: InputSet<T>,
+    just_activated_digital: InputSet<T>,
+    just_deactivated_digital: InputSet<T>,
     digital_deadzone: Deadzone,
+
+    // Whether every input reads as `0.0`, while still tracking backend values.
+    suppressed: bool,
+    quantization: Option<Quantization>,
 }
 
 impl<T> AnalogInput<T>
 where
-    T: Hash + Eq,
+    T: InputKind,
 {
     /// Gets the value of an analog input.
     ///
     /// Returns `0.0` if the input is within the analog deadzone, or if it has not been read yet.
     pub fn value(&self, input: T) -> f32 {
-        match self.inputs.get(&input) {
-            Some(&value) if Deadzone::from(value) >= self.deadzone => {
-                let deadzone = self.deadzone.get();
-                let remapped_value = (value.get().abs() - deadzone) / (ANALOG_MAX - deadzone);
-                value.get().signum() * remapped_value
-            }
+        let Some(value) = self.configured_value(input) else {
+            return 0.0;
+        };
+        let value = core::apply_deadzone(value.get(), self.deadzone_of(input).get());
+        let value = match self.configs.get(input).map_or(1.0, |config| config.curve) {
+            1.0 => value,
+            curve => value.signum() * value.abs().powf(curve),
+        };
+        self.quantized(value)
+    }
+
+    /// Gets the value of an analog input as the backend reported it, before the config,
+    /// smoothing, deadzones and response curve, such as for calibration screens or games that
+    /// process inputs themselves.
+    ///
+    /// Remaps and suppression still apply. Returns `0.0` if the input has not been read yet.
+    pub fn raw_value(&self, input: T) -> f32 {
+        match self.inputs.get(input) {
+            Some(value) if !self.suppressed => self.quantized(value.get()),
             _ => 0.0,
         }
     }
 
     /// Checks if an analog input just left the analog deadzone.
     pub fn just_activated(&self, input: T) -> Option<f32> {
-        if self.just_activated.contains(&input) {
+        if self.just_activated.contains(input) {
             Some(self.value(input))
         } else {
             None
@@ -94,7 +175,7 @@ where
 
     /// Checks if an analog input just entered the analog deadzone.
     pub fn just_deactivated(&self, input: T) -> bool {
-        self.just_deactivated.contains(&input)
+        self.just_deactivated.contains(input)
     }
 
     /// Converts an analog input to a digital value.
@@ -102,23 +183,18 @@ where
     /// Returns either `ANALOG_MIN` or `ANALOG_MAX` when a nonzero input is outside
     /// the digital deadzone, and `0.0` otherwise.
     pub fn digital_value(&self, input: T) -> f32 {
-        match self.inputs.get(&input) {
-            Some(&value) if Deadzone::from(value) >= self.digital_deadzone => {
-                if value.get() < 0.0 {
-                    ANALOG_MIN
-                } else if value.get() > 0.0 {
-                    ANALOG_MAX
-                } else {
-                    0.0
-                }
-            }
-            _ => 0.0,
-        }
+        self.digital_value_of(self.configured_value(input))
+    }
+
+    fn digital_value_of(&self, value: Option<AnalogInputValue>) -> f32 {
+        value.map_or(0.0, |value| {
+            core::to_digital(value.get(), self.digital_deadzone.get())
+        })
     }
 
     /// Checks if an analog input just left the digital deadzone.
     pub fn just_activated_digital(&self, input: T) -> Option<f32> {
-        if self.just_activated_digital.contains(&input) {
+        if self.just_activated_digital.contains(input) {
             Some(self.digital_value(input))
         } else {
             None
@@ -127,66 +203,357 @@ where
 
     /// Checks if an analog input just entered the digital deadzone.
     pub fn just_deactivated_digital(&self, input: T) -> bool {
-        self.just_deactivated_digital.contains(&input)
+        self.just_deactivated_digital.contains(input)
+    }
+
+    /// Gets when the value of an analog input last changed, before deadzones.
+    ///
+    /// Returns `None` if the input has not been read yet.
+    pub fn last_changed(&self, input: T) -> Option<Timestamp> {
+        self.last_changed.get(input).copied()
+    }
+
+    /// Gets when the backend last reported a change of an analog input.
+    ///
+    /// Like [`DigitalInput::last_event_time`](crate::DigitalInput::last_event_time), this is the
+    /// time of the operating system event where the backend provides it.
+    pub fn last_event_time(&self, input: T) -> Option<Timestamp> {
+        self.event_times.get(input).copied()
+    }
+
+    /// Gets the number of updates since the gamepad connected.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Gets how much the value of an analog input changed during the last update.
+    pub fn delta(&self, input: T) -> f32 {
+        let previous = self.previous_values.get(input).copied().unwrap_or(0.0);
+        self.value(input) - previous
+    }
+
+    /// Gets how fast the value of an analog input changes, in units per second.
+    ///
+    /// This is [`Self::delta`] divided by the time between the last two updates.
+    /// Returns `0.0` until the second update.
+    pub fn velocity(&self, input: T) -> f32 {
+        if self.update_interval.is_zero() {
+            0.0
+        } else {
+            self.delta(input) / self.update_interval.as_secs_f32()
+        }
+    }
+
+    /// Sets the analog deadzone of a single analog input, overriding the shared deadzone.
+    ///
+    /// Useful for controllers where only one stick drifts.
+    pub fn set_deadzone_for(&mut self, input: T, deadzone: f32) {
+        let deadzone = Deadzone::from(AnalogInputValue::from(deadzone));
+        self.deadzones.insert(input, deadzone);
+    }
+
+    /// Makes an analog input use the shared analog deadzone again.
+    pub fn reset_deadzone_for(&mut self, input: T) {
+        self.deadzones.remove(input);
+    }
+
+    /// Gets the analog deadzone shared by all analog inputs.
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone.get()
+    }
+
+    /// Gets the digital deadzone shared by all analog inputs.
+    pub fn digital_deadzone(&self) -> f32 {
+        self.digital_deadzone.get()
+    }
+
+    /// Gets the analog deadzone that applies to an analog input, which is either its own
+    /// or the shared one.
+    pub fn deadzone_for(&self, input: T) -> f32 {
+        self.deadzone_of(input).get()
+    }
+
+    /// Gets the analog deadzone of an analog input, if it overrides the shared deadzone.
+    pub fn deadzone_override(&self, input: T) -> Option<f32> {
+        self.deadzones.get(input).map(Deadzone::get)
+    }
+
+    /// Rounds every value to a fixed grid, both before the deadzones and after the response
+    /// curve, or stops rounding them with `None`.
+    pub fn set_quantization(&mut self, quantization: Option<Quantization>) {
+        if self.quantization == quantization {
+            return;
+        }
+        let old_values: Vec<_> = (0..T::COUNT)
+            .map(|index| self.configured_value(T::from_index(index)))
+            .collect();
+        self.quantization = quantization;
+
+        for (index, old_value) in old_values.into_iter().enumerate() {
+            let input = T::from_index(index);
+            if old_value.is_some() && old_value != self.configured_value(input) {
+                self.track_edges(input, old_value);
+            }
+        }
+    }
+
+    /// Gets the grid that values are rounded to.
+    pub fn quantization(&self) -> Option<Quantization> {
+        self.quantization
+    }
+
+    /// Gets the configuration of an analog input.
+    pub fn config(&self, input: T) -> AxisConfig {
+        self.configs.get(input).copied().unwrap_or_default()
+    }
+
+    /// Gets mutable access to the configuration of an analog input.
+    pub fn config_mut(&mut self, input: T) -> &mut AxisConfig {
+        self.configs.get_or_insert_with(input, Default::default)
+    }
+
+    /// Resets the configuration of an analog input to the default.
+    pub fn reset_config(&mut self, input: T) {
+        self.configs.remove(input);
+    }
+
+    fn configured_value(&self, input: T) -> Option<AnalogInputValue> {
+        let value = match self.overrides.get(input) {
+            Some(&value) => Some(value),
+            None => self.configured_physical_value(input),
+        };
+        if self.suppressed {
+            value.map(|_| AnalogInputValue(0.0))
+        } else {
+            value.map(|value| AnalogInputValue(self.quantized(value.get())))
+        }
+    }
+
+    fn quantized(&self, value: f32) -> f32 {
+        match self.quantization {
+            Some(quantization) => quantization.quantize(value),
+            None => value,
+        }
+    }
+
+    fn configured_physical_value(&self, input: T) -> Option<AnalogInputValue> {
+        let value = self.smoothed.get(input).or(self.inputs.get(input))?.get();
+        let value = match self.configs.get(input) {
+            Some(config) => config.apply(value),
+            None => value,
+        };
+        Some(AnalogInputValue(value))
+    }
+
+    fn deadzone_of(&self, input: T) -> Deadzone {
+        self.deadzones.get(input).copied().unwrap_or(self.deadzone)
     }
 }
 
 impl<T> AnalogInput<T>
 where
-    T: Hash + Copy + Eq,
+    T: InputKind,
 {
     pub(crate) fn set(&mut self, input: T, value: AnalogInputValue) {
-        let old_value = self.inputs.insert(input, value);
-        let value = value.get();
-        let deadzone = self.deadzone.get();
-        let digital_deadzone = self.digital_deadzone.get();
+        self.set_at(input, value, Instant::now());
+    }
 
-        if let Some(old_value) = old_value {
-            let old_value = old_value.get();
+    /// Sets the value of an input, as reported by the backend at `time`.
+    pub(crate) fn set_at(&mut self, input: T, value: AnalogInputValue, time: Instant) {
+        let input = self.remapped(input);
+        let old_value = self.configured_value(input);
+        if self.inputs.insert(input, value) != Some(value) {
+            self.last_changed.insert(input, Timestamp::now(self.tick));
+            self.event_times.insert(
+                input,
+                Timestamp {
+                    tick: self.tick,
+                    instant: time,
+                },
+            );
+        }
+        // Smoothed values start at the first reading, and then follow it in `smooth`.
+        if self.config(input).smoothing > 0.0 {
+            self.smoothed.get_or_insert_with(input, || value);
+        }
+        self.track_edges(input, old_value);
+    }
 
-            if value.abs() < deadzone {
-                self.just_activated.remove(&input);
-                if old_value.abs() >= deadzone {
-                    self.just_deactivated.insert(input);
-                }
-            } else {
-                self.just_deactivated.remove(&input);
-                // It is possible for an analog input to completely pass through the deadzone
-                // between updates. In that case, both the old and new values would exceed the
-                // deadzone, but they would have opposite signs.
-                if old_value.abs() < deadzone || value.signum() != old_value.signum() {
-                    self.just_activated.insert(input);
-                }
+    /// Makes the backend's `from` input report to `to` instead, or to itself again if both are
+    /// equal.
+    ///
+    /// The input that `from` reported to until now is reset to `0.0`.
+    pub(crate) fn set_remap(&mut self, from: T, to: T) {
+        let previous = self.remapped(from);
+        if previous == to {
+            return;
+        }
+
+        if self.inputs.contains_key(previous) {
+            self.set(from, AnalogInputValue::from(0.0));
+        }
+        if from == to {
+            self.remaps.remove(from);
+        } else {
+            self.remaps.insert(from, to);
+        }
+    }
+
+    /// Resets every input to `0.0`, as if the backend and emulation had centered them, such as
+    /// when the device can no longer be read.
+    pub(crate) fn release_all(&mut self) {
+        for input in (0..T::COUNT).map(T::from_index) {
+            let old_value = self.configured_value(input);
+            let changed = self
+                .overrides
+                .remove(input)
+                .is_some_and(|value| value.get() != 0.0)
+                | self
+                    .inputs
+                    .get_mut(input)
+                    .map(|value| std::mem::take(value) != AnalogInputValue(0.0))
+                    .unwrap_or(false);
+            if let Some(smoothed) = self.smoothed.get_mut(input) {
+                *smoothed = AnalogInputValue(0.0);
             }
 
-            if value.abs() < digital_deadzone {
-                self.just_activated_digital.remove(&input);
-                if old_value.abs() >= digital_deadzone {
-                    self.just_deactivated_digital.insert(input);
-                }
-            } else {
-                self.just_deactivated_digital.remove(&input);
-                if old_value.abs() < digital_deadzone || value.signum() != old_value.signum() {
-                    self.just_activated_digital.insert(input);
-                }
+            if changed {
+                self.last_changed.insert(input, Timestamp::now(self.tick));
+                self.track_edges(input, old_value);
             }
-        } else {
-            if value.abs() >= deadzone {
-                self.just_activated.insert(input);
-                self.just_deactivated.remove(&input);
+        }
+    }
+
+    /// Makes every input read as `0.0` while suppressed, still tracking backend values, so that
+    /// inputs read their current value once no longer suppressed.
+    pub(crate) fn set_suppressed(&mut self, suppressed: bool) {
+        if self.suppressed == suppressed {
+            return;
+        }
+        let old_values: Vec<_> = (0..T::COUNT)
+            .map(|index| self.configured_value(T::from_index(index)))
+            .collect();
+        self.suppressed = suppressed;
+
+        for (index, old_value) in old_values.into_iter().enumerate() {
+            let input = T::from_index(index);
+            if old_value.is_some() && old_value != self.configured_value(input) {
+                self.last_changed.insert(input, Timestamp::now(self.tick));
+                self.track_edges(input, old_value);
             }
-            if value.abs() >= digital_deadzone {
-                self.just_activated_digital.insert(input);
-                self.just_deactivated_digital.remove(&input);
+        }
+    }
+
+    /// Gets the input that the backend's `from` input reports to.
+    pub(crate) fn remapped(&self, from: T) -> T {
+        self.remaps.get(from).copied().unwrap_or(from)
+    }
+
+    /// Moves smoothed values towards the latest backend values, once per update.
+    pub(crate) fn smooth(&mut self) {
+        // Only inputs that have been read with smoothing enabled have smoothed values.
+        if self.smoothed.is_empty() {
+            return;
+        }
+
+        for input in (0..T::COUNT).map(T::from_index) {
+            let Some(target) = self.inputs.get(input).map(AnalogInputValue::get) else {
+                continue;
+            };
+            if !self.smoothed.contains_key(input) {
+                continue;
             }
+
+            let smoothing = self.config(input).smoothing;
+            let old_value = self.configured_value(input);
+            let changed = if smoothing > 0.0 {
+                let current = self.smoothed.get(input).map_or(target, |value| value.get());
+                let mut value = current + (target - current) * (1.0 - smoothing);
+                if (target - value).abs() < SMOOTHING_EPSILON {
+                    value = target;
+                }
+                self.smoothed.insert(input, AnalogInputValue(value))
+                    != Some(AnalogInputValue(value))
+            } else {
+                self.smoothed.remove(input).is_some()
+            };
+
+            if changed {
+                self.last_changed.insert(input, Timestamp::now(self.tick));
+                self.track_edges(input, old_value);
+            }
+        }
+    }
+
+    /// Replaces the value of an input on behalf of an emulation layer, or gives control back
+    /// to the backend with `None`.
+    pub(crate) fn set_override(&mut self, input: T, value: Option<AnalogInputValue>) {
+        let old_value = self.configured_value(input);
+        let changed = match value {
+            Some(value) => self.overrides.insert(input, value) != Some(value),
+            None => self.overrides.remove(input).is_some(),
+        };
+
+        if changed {
+            self.last_changed.insert(input, Timestamp::now(self.tick));
+            self.track_edges(input, old_value);
         }
     }
 
+    /// Converts the backend value of an input to a digital value, ignoring emulation.
+    pub(crate) fn physical_digital_value(&self, input: T) -> f32 {
+        self.digital_value_of(self.configured_physical_value(input))
+    }
+
+    fn track_edges(&mut self, input: T, old_value: Option<AnalogInputValue>) {
+        let value = match self.configured_value(input) {
+            Some(value) => value.get(),
+            None => return,
+        };
+        let old_value = old_value.map(|value| value.get());
+        let deadzone = self.deadzone_of(input).get();
+        let digital_deadzone = self.digital_deadzone.get();
+        let hysteresis = self.config(input).hysteresis;
+
+        Crossing {
+            active: &mut self.active,
+            just_activated: &mut self.just_activated,
+            just_deactivated: &mut self.just_deactivated,
+        }
+        .track(input, value, old_value, deadzone, hysteresis);
+        Crossing {
+            active: &mut self.active_digital,
+            just_activated: &mut self.just_activated_digital,
+            just_deactivated: &mut self.just_deactivated_digital,
+        }
+        .track(input, value, old_value, digital_deadzone, hysteresis);
+    }
+
     pub(crate) fn update(&mut self) {
         self.just_activated.clear();
         self.just_deactivated.clear();
         self.just_activated_digital.clear();
         self.just_deactivated_digital.clear();
+        self.tick += 1;
+
+        for (input, _) in self.inputs.iter() {
+            self.previous_values.insert(input, self.value(input));
+        }
+        let now = Instant::now();
+        if let Some(updated_at) = self.updated_at {
+            self.update_interval = now.duration_since(updated_at);
+        }
+        self.updated_at = Some(now);
+    }
+
+    pub(crate) fn inputs(&self) -> impl Iterator<Item = T> + '_ {
+        self.inputs.keys()
+    }
+
+    pub(crate) fn raw_values(&self) -> impl Iterator<Item = (T, f32)> + '_ {
+        self.inputs
+            .iter()
+            .map(|(input, value)| (input, value.get()))
     }
 
     pub(crate) fn set_deadzone(&mut self, deadzone: Deadzone) {
@@ -198,21 +565,286 @@ where
     }
 }
 
-impl<T> Default for AnalogInput<T> {
+/// The edge state of an analog input crossing one of its deadzones.
+struct Crossing<'a, T> {
+    active: &'a mut InputSet<T>,
+    just_activated: &'a mut InputSet<T>,
+    just_deactivated: &'a mut InputSet<T>,
+}
+
+impl<T> Crossing<'_, T>
+where
+    T: InputKind,
+{
+    /// Records a new value of an input. Active inputs are only deactivated once they fall
+    /// `hysteresis` below the deadzone.
+    fn track(self, input: T, value: f32, old_value: Option<f32>, deadzone: f32, hysteresis: f32) {
+        let mut threshold = Threshold::new(self.active.contains(input));
+        match threshold.update(value, old_value, deadzone, hysteresis) {
+            Some(Edge::Activated) => {
+                self.just_activated.insert(input);
+                self.just_deactivated.remove(input);
+            }
+            Some(Edge::Deactivated) => {
+                self.just_activated.remove(input);
+                self.just_deactivated.insert(input);
+            }
+            None => (),
+        }
+
+        if threshold.is_active() {
+            self.active.insert(input);
+        } else {
+            self.active.remove(input);
+        }
+    }
+}
+
+impl<T: InputKind> Default for AnalogInput<T> {
     fn default() -> Self {
         Self {
             inputs: Default::default(),
+            overrides: Default::default(),
+            smoothed: Default::default(),
+            last_changed: Default::default(),
+            event_times: Default::default(),
+            tick: 0,
+
+            previous_values: Default::default(),
+            updated_at: None,
+            update_interval: Duration::ZERO,
 
+            active: Default::default(),
             just_activated: Default::default(),
             just_deactivated: Default::default(),
             deadzone: DEFAULT_DEADZONE,
+            deadzones: Default::default(),
+            configs: Default::default(),
+            remaps: Default::default(),
 
+            active_digital: Default::default(),
             just_activated_digital: Default::default(),
             just_deactivated_digital: Default::default(),
             digital_deadzone: DEFAULT_DEADZONE_DIGITAL,
+
+            suppressed: false,
+            quantization: None,
         }
     }
 }
 
-const DEFAULT_DEADZONE: Deadzone = Deadzone(0.1);
-const DEFAULT_DEADZONE_DIGITAL: Deadzone = Deadzone(0.5);
+/// Configuration of a single analog input, applied before deadzones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisConfig {
+    inverted: bool,
+    min: f32,
+    max: f32,
+    curve: f32,
+    smoothing: f32,
+    hysteresis: f32,
+    center: f32,
+}
+
+impl AxisConfig {
+    /// Inverts the input, e.g. for invert-Y camera controls.
+    pub fn invert(&mut self, inverted: bool) -> &mut Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Sets how far the input physically reaches in each direction.
+    ///
+    /// Values get scaled so that `min` reads as `ANALOG_MIN` and `max` reads as `ANALOG_MAX`.
+    /// Useful for worn sticks that no longer reach their full range.
+    /// `min` has to be negative and `max` positive, otherwise they are clamped.
+    pub fn range(&mut self, min: f32, max: f32) -> &mut Self {
+        if min.is_finite() && max.is_finite() {
+            self.min = min.clamp(ANALOG_MIN, -MIN_RANGE);
+            self.max = max.clamp(MIN_RANGE, ANALOG_MAX);
+        }
+        self
+    }
+
+    /// Sets where the input rests, such as for sticks that drift off-center.
+    ///
+    /// Values get shifted so that `center` reads as `0.0`, while the ends of the range still
+    /// read as `ANALOG_MIN` and `ANALOG_MAX`. Clamped to `[-0.5, 0.5]`.
+    pub fn center(&mut self, center: f32) -> &mut Self {
+        if center.is_finite() {
+            self.center = center.clamp(-MAX_CENTER, MAX_CENTER);
+        }
+        self
+    }
+
+    /// Gets where the input rests.
+    pub fn center_offset(&self) -> f32 {
+        self.center
+    }
+
+    /// Sets the response curve of the input, as an exponent applied after the deadzone.
+    ///
+    /// `1.0` is linear. Higher exponents give finer control near the center of a stick.
+    /// Exponents that are not positive are ignored.
+    pub fn curve(&mut self, exponent: f32) -> &mut Self {
+        if exponent.is_finite() && exponent > 0.0 {
+            self.curve = exponent;
+        }
+        self
+    }
+
+    /// Gets the response curve exponent of the input.
+    pub fn response_curve(&self) -> f32 {
+        self.curve
+    }
+
+    /// Smooths the input with a low-pass filter, to suppress jitter on noisy sticks.
+    ///
+    /// On every update, the value moves towards the latest reading by `1.0 - smoothing`
+    /// of the remaining distance. `0.0` disables smoothing, values closer to `1.0` smooth more
+    /// but add latency. Clamped to `[0.0, 0.95]`.
+    pub fn smoothing(&mut self, smoothing: f32) -> &mut Self {
+        if smoothing.is_finite() {
+            self.smoothing = smoothing.clamp(0.0, MAX_SMOOTHING);
+        }
+        self
+    }
+
+    /// Keeps the input activated until it falls `hysteresis` below the deadzones,
+    /// so that values hovering around a deadzone edge do not flicker between activated and
+    /// deactivated.
+    ///
+    /// Only affects just activated and just deactivated inputs. Clamped to `[0.0, ANALOG_MAX]`.
+    pub fn hysteresis(&mut self, hysteresis: f32) -> &mut Self {
+        if hysteresis.is_finite() {
+            self.hysteresis = hysteresis.clamp(0.0, ANALOG_MAX);
+        }
+        self
+    }
+
+    /// Gets the smoothing of the input.
+    pub fn smoothing_factor(&self) -> f32 {
+        self.smoothing
+    }
+
+    /// Gets the hysteresis of the input.
+    pub fn hysteresis_width(&self) -> f32 {
+        self.hysteresis
+    }
+
+    /// Checks if the input is inverted.
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Gets the physical range of the input, as `(min, max)`.
+    pub fn physical_range(&self) -> (f32, f32) {
+        (self.min, self.max)
+    }
+
+    fn apply(&self, value: f32) -> f32 {
+        core::scale_to_range(
+            value - self.center,
+            (self.min - self.center).min(-MIN_RANGE),
+            (self.max - self.center).max(MIN_RANGE),
+            self.inverted,
+        )
+    }
+}
+
+impl Default for AxisConfig {
+    fn default() -> Self {
+        Self {
+            inverted: false,
+            min: ANALOG_MIN,
+            max: ANALOG_MAX,
+            curve: 1.0,
+            smoothing: 0.0,
+            hysteresis: 0.0,
+            center: 0.0,
+        }
+    }
+}
+
+/// The smallest physical range an analog input can be configured with.
+const MIN_RANGE: f32 = 0.01;
+
+/// The furthest off-center an analog input can be configured to rest.
+const MAX_CENTER: f32 = 0.5;
+
+/// The strongest smoothing, which still lets values settle within about a hundred updates.
+const MAX_SMOOTHING: f32 = 0.95;
+
+/// How close a smoothed value has to get to its reading to snap to it.
+const SMOOTHING_EPSILON: f32 = 0.001;
+
+/// Samples the resting noise of analog inputs to suggest a deadzone for each of them.
+///
+/// Sample the analog inputs every update for a short window, such as one second,
+/// while the player leaves the controller untouched.
+#[derive(Debug)]
+pub struct Calibration<T: InputKind> {
+    noise: InputMap<T, f32>,
+    samples: usize,
+}
+
+impl<T> Calibration<T>
+where
+    T: InputKind,
+{
+    /// Creates a calibration without any samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the current raw values of all analog inputs.
+    pub fn sample(&mut self, analog_inputs: &AnalogInput<T>) {
+        for (input, value) in analog_inputs.raw_values() {
+            let noise = self.noise.get_or_insert_with(input, Default::default);
+            *noise = noise.max(value.abs());
+        }
+        self.samples += 1;
+    }
+
+    /// Gets the number of samples taken so far.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Suggests a deadzone for an analog input, slightly above the noise seen while it was at rest.
+    ///
+    /// Returns `None` if the input has not been sampled yet.
+    pub fn suggested_deadzone(&self, input: T) -> Option<f32> {
+        self.noise
+            .get(input)
+            .map(|noise| (noise + CALIBRATION_MARGIN).min(ANALOG_MAX))
+    }
+
+    /// Gets an iterator over the suggested deadzones of all sampled analog inputs.
+    pub fn suggested_deadzones(&self) -> impl Iterator<Item = (T, f32)> + '_ {
+        self.noise
+            .keys()
+            .filter_map(|input| Some((input, self.suggested_deadzone(input)?)))
+    }
+
+    /// Applies the suggested deadzones to the analog inputs.
+    pub fn apply(&self, analog_inputs: &mut AnalogInput<T>) {
+        for (input, deadzone) in self.suggested_deadzones() {
+            analog_inputs.set_deadzone_for(input, deadzone);
+        }
+    }
+}
+
+impl<T: InputKind> Default for Calibration<T> {
+    fn default() -> Self {
+        Self {
+            noise: Default::default(),
+            samples: 0,
+        }
+    }
+}
+
+/// Headroom added on top of the sampled resting noise.
+const CALIBRATION_MARGIN: f32 = 0.05;
+
+const DEFAULT_DEADZONE: Deadzone = Deadzone(DEFAULT_ANALOG_DEADZONE);
+const DEFAULT_DEADZONE_DIGITAL: Deadzone = Deadzone(DEFAULT_DIGITAL_DEADZONE);
@@ -1,23 +1,531 @@
-cfg_if::cfg_if! {
-    if #[cfg(feature = "sdl2")] {
-        #[path = "backend/sdl2.rs"]
-        mod implementation;
-    } else if #[cfg(feature = "gilrs")] {
-        #[path = "backend/gilrs.rs"]
-        mod implementation;
-    } else {
-        #[path = "backend/dummy.rs"]
-        mod implementation;
-    }
+/// Prints a message if `logging` is set, which backends take from [`InitOptions::logging`].
+macro_rules! log {
+    ($logging:expr, $($arg:tt)*) => {
+        if $logging {
+            println!($($arg)*);
+        }
+    };
 }
 
-pub use implementation::*;
+#[cfg(feature = "steam")]
+mod steam;
+
+#[cfg(feature = "sdl2")]
+pub mod sdl2;
+
+#[cfg(feature = "gilrs")]
+mod gilrs;
+
+#[cfg(all(feature = "xinput", windows))]
+mod xinput;
+
+#[cfg(all(feature = "evdev", target_os = "linux"))]
+mod evdev;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod web;
 
+#[cfg(all(
+    test,
+    any(
+        feature = "sdl2",
+        feature = "gilrs",
+        all(feature = "evdev", target_os = "linux")
+    )
+))]
+mod conformance;
+mod lazy;
+mod merged;
+
+#[cfg(not(any(
+    feature = "steam",
+    feature = "sdl2",
+    feature = "gilrs",
+    all(feature = "xinput", windows),
+    all(feature = "evdev", target_os = "linux"),
+    all(feature = "wasm", target_arch = "wasm32"),
+)))]
+compile_error!("no gamepad backend chosen");
+
+use crate::glyph::ControllerType;
+use crate::sensor::Sensor;
+use crate::trigger::TriggerEffect;
 use crate::{Gamepad, GamepadId};
+use std::any::Any;
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+#[cfg(any(feature = "sdl2", feature = "steam"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use crate::Result;
 
+/// An analog input of a gamepad, named after the SDL2 game controller axes.
+///
+/// Every backend maps its inputs onto these, so that games behave the same with any backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Axis {
+    /// Horizontal axis of the left stick, positive to the right.
+    LeftX,
+    /// Vertical axis of the left stick, positive downwards.
+    LeftY,
+    /// Horizontal axis of the right stick, positive to the right.
+    RightX,
+    /// Vertical axis of the right stick, positive downwards.
+    RightY,
+    /// The left trigger.
+    TriggerLeft,
+    /// The right trigger.
+    TriggerRight,
+}
+
+/// A digital input of a gamepad, named after the SDL2 game controller buttons.
+///
+/// Every backend maps its inputs onto these, so that games behave the same with any backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Button {
+    /// Bottom face button.
+    A,
+    /// Right face button.
+    B,
+    /// Left face button.
+    X,
+    /// Top face button.
+    Y,
+    /// Left center button.
+    Back,
+    /// Middle center button.
+    Guide,
+    /// Right center button.
+    Start,
+    /// Left stick click.
+    LeftStick,
+    /// Right stick click.
+    RightStick,
+    /// Left bumper.
+    LeftShoulder,
+    /// Right bumper.
+    RightShoulder,
+    /// D-pad up.
+    DPadUp,
+    /// D-pad down.
+    DPadDown,
+    /// D-pad left.
+    DPadLeft,
+    /// D-pad right.
+    DPadRight,
+    /// Extra button, such as the Xbox share button, the DualSense microphone button or the
+    /// Switch capture button.
+    Misc1,
+    /// Upper back paddle under the right hand, P1 on Xbox Elite controllers.
+    Paddle1,
+    /// Upper back paddle under the left hand, P3 on Xbox Elite controllers.
+    Paddle2,
+    /// Lower back paddle under the right hand, P2 on Xbox Elite controllers.
+    Paddle3,
+    /// Lower back paddle under the left hand, P4 on Xbox Elite controllers.
+    Paddle4,
+    /// Touchpad click.
+    Touchpad,
+    /// A button activated by an axis, registered with [`Gamepad::set_virtual_button`].
+    ///
+    /// Not part of [`BUTTONS`], since virtual buttons are defined by the game.
+    Virtual(u8),
+}
+
+/// An analog stick of a gamepad, made of two axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stick {
+    /// The left stick, made of [`Axis::LeftX`] and [`Axis::LeftY`].
+    Left,
+    /// The right stick, made of [`Axis::RightX`] and [`Axis::RightY`].
+    Right,
+}
+
+impl Stick {
+    /// Gets the horizontal axis of the stick.
+    pub fn x(self) -> Axis {
+        match self {
+            Self::Left => Axis::LeftX,
+            Self::Right => Axis::RightX,
+        }
+    }
+
+    /// Gets the vertical axis of the stick.
+    pub fn y(self) -> Axis {
+        match self {
+            Self::Left => Axis::LeftY,
+            Self::Right => Axis::RightY,
+        }
+    }
+}
+
+/// Every axis.
+pub const AXES: [Axis; 6] = [
+    Axis::LeftX,
+    Axis::LeftY,
+    Axis::RightX,
+    Axis::RightY,
+    Axis::TriggerLeft,
+    Axis::TriggerRight,
+];
+
+/// Every button.
+pub const BUTTONS: [Button; 21] = [
+    Button::A,
+    Button::B,
+    Button::X,
+    Button::Y,
+    Button::Back,
+    Button::Guide,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::LeftShoulder,
+    Button::RightShoulder,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Misc1,
+    Button::Paddle1,
+    Button::Paddle2,
+    Button::Paddle3,
+    Button::Paddle4,
+    Button::Touchpad,
+];
+
+/// A gamepad backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    /// Steam Input, enabled by the `steam` feature.
+    ///
+    /// The game has to initialize the Steam API before creating the context.
+    /// Steam Input works with actions rather than physical inputs, so the game's In-Game Actions
+    /// file has to define an action set named `fishsticks`, or the sets selected with
+    /// [`GamepadContext::set_steam_action_set`](crate::GamepadContext::set_steam_action_set).
+    /// They need a digital action for every [`Button`], named like its variant, and analog
+    /// actions named `LeftStick`, `RightStick`, `TriggerLeft` and `TriggerRight`. Players' Steam
+    /// configurations then decide which physical inputs trigger these actions, and
+    /// [`Gamepad::steam_button_glyph`](crate::Gamepad::steam_button_glyph) shows them.
+    #[cfg(feature = "steam")]
+    Steam,
+    /// SDL2, enabled by the `sdl2` feature.
+    #[cfg(feature = "sdl2")]
+    Sdl2,
+    /// gilrs, enabled by the `gilrs` feature.
+    ///
+    /// gilrs does not report [`Button::Misc1`], the paddles or [`Button::Touchpad`].
+    #[cfg(feature = "gilrs")]
+    Gilrs,
+    /// XInput on Windows, enabled by the `xinput` feature.
+    #[cfg(all(feature = "xinput", windows))]
+    XInput,
+    /// Linux evdev, enabled by the `evdev` feature.
+    #[cfg(all(feature = "evdev", target_os = "linux"))]
+    Evdev,
+    /// The browser Gamepad API, enabled by the `wasm` feature.
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    Web,
+}
+
+impl BackendKind {
+    /// Every backend compiled into this build, in order of preference.
+    pub const AVAILABLE: &'static [BackendKind] = &[
+        #[cfg(feature = "steam")]
+        BackendKind::Steam,
+        #[cfg(feature = "sdl2")]
+        BackendKind::Sdl2,
+        #[cfg(feature = "gilrs")]
+        BackendKind::Gilrs,
+        #[cfg(all(feature = "xinput", windows))]
+        BackendKind::XInput,
+        #[cfg(all(feature = "evdev", target_os = "linux"))]
+        BackendKind::Evdev,
+        #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+        BackendKind::Web,
+    ];
+
+    /// Gets the name of the backend, as used for its cargo feature.
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "steam")]
+            BackendKind::Steam => "steam",
+            #[cfg(feature = "sdl2")]
+            BackendKind::Sdl2 => "sdl2",
+            #[cfg(feature = "gilrs")]
+            BackendKind::Gilrs => "gilrs",
+            #[cfg(all(feature = "xinput", windows))]
+            BackendKind::XInput => "xinput",
+            #[cfg(all(feature = "evdev", target_os = "linux"))]
+            BackendKind::Evdev => "evdev",
+            #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+            BackendKind::Web => "wasm",
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether the backend of a context runs, as returned by
+/// [`GamepadContext::backend_state`](crate::GamepadContext::backend_state).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BackendState {
+    /// The backend has not been initialized yet.
+    Starting,
+    /// The backend runs.
+    Ready,
+    /// The last attempt to initialize the backend failed with this error. It is retried later.
+    Failed(String),
+}
+
+/// What the backends did during an update, as returned by
+/// [`GamepadContext::update_report`](crate::GamepadContext::update_report).
+///
+/// Failures are contained to the device or backend they happen in, so that the other gamepads
+/// are still updated.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UpdateReport {
+    /// The number of input and connection events the backends processed. Backends that poll
+    /// the state of gamepads, like XInput, count every new state they read as one event.
+    pub events: usize,
+    /// Gamepads that could not be read, with the error. They are disconnected.
+    pub device_errors: Vec<(GamepadId, String)>,
+    /// Errors that kept a backend from reading any of its gamepads during the update.
+    pub backend_errors: Vec<String>,
+}
+
+impl UpdateReport {
+    /// Checks if nothing failed.
+    pub fn is_ok(&self) -> bool {
+        self.device_errors.is_empty() && self.backend_errors.is_empty()
+    }
+}
+
 pub trait Backend {
-    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> Result<()>;
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport;
+
+    fn ignore_devices(&mut self);
+
+    fn set_raw_joysticks(&mut self, _enabled: bool) -> Result<()> {
+        Err("Raw joysticks are not supported by this backend".into())
+    }
+
+    /// Selects the action set that gamepads read, for Steam Input.
+    #[cfg(feature = "steam")]
+    fn set_steam_action_set(&mut self, _name: &str) -> Result<()> {
+        Err("Action sets are only supported by Steam Input".into())
+    }
+
+    /// Gets whether the backend runs, for backends that start lazily.
+    fn state(&self) -> BackendState {
+        BackendState::Ready
+    }
+
+    /// Gets the backends that run, for backends that only know them once they started.
+    fn kinds(&self) -> Option<&[BackendKind]> {
+        None
+    }
+
+    /// Gets the backend that reports a gamepad, for backends that merge several.
+    fn backend_of(&self, _id: GamepadId) -> Option<BackendKind> {
+        None
+    }
+
+    #[cfg(feature = "sdl2")]
+    fn feed_event(&mut self, _event: &::sdl2::event::Event) {}
+
+    /// Gets the buffer of events that are not gamepad input, which the caller drains.
+    #[cfg(feature = "sdl2")]
+    fn other_events(&mut self) -> Option<&mut Vec<::sdl2::event::Event>> {
+        None
+    }
+}
+
+/// Device features of a single gamepad, for backends that support them.
+pub trait GamepadDevice {
+    fn has_sensor(&self, sensor: Sensor) -> bool;
+
+    fn set_sensor_enabled(&self, sensor: Sensor, enabled: bool) -> Result<()>;
+
+    fn set_led(&self, red: u8, green: u8, blue: u8) -> Result<()>;
+
+    fn set_player_index(&self, index: Option<u8>) -> Result<()>;
+
+    fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()>;
+
+    /// Gets the controller type, if the backend knows better than the GUID.
+    fn controller_type(&self) -> Option<ControllerType>;
+
+    /// Checks if the triggers have motors that can play [`TriggerEffect`]s.
+    fn has_adaptive_triggers(&self) -> bool {
+        false
+    }
+
+    /// Sets the effect of a trigger. Does nothing without adaptive triggers.
+    fn set_trigger_effect(&self, _trigger: Axis, _effect: TriggerEffect) -> Result<()> {
+        Ok(())
+    }
+
+    /// Gets the device itself, so that its backend can expose its handles.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Claims a backend that only one context in the process can run at once, such as SDL2,
+/// releasing it on drop.
+#[cfg(any(feature = "sdl2", feature = "steam"))]
+pub(crate) struct Exclusive(&'static AtomicBool);
+
+#[cfg(any(feature = "sdl2", feature = "steam"))]
+impl Exclusive {
+    pub(crate) fn acquire(alive: &'static AtomicBool, kind: BackendKind) -> Result<Self> {
+        if alive.swap(true, Ordering::AcqRel) {
+            return Err(format!(
+                "Another GamepadContext runs the {} backend. Share it with a GamepadContextHandle instead",
+                kind
+            ));
+        }
+
+        Ok(Self(alive))
+    }
+}
+
+#[cfg(any(feature = "sdl2", feature = "steam"))]
+impl Drop for Exclusive {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Settings that backends apply when they are initialized.
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    /// Files of SDL2 controller mappings, for backends that read them.
+    pub mappings: Vec<PathBuf>,
+    /// Whether backends print messages about devices, in debug builds.
+    pub logging: bool,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            mappings: Vec::new(),
+            logging: true,
+        }
+    }
+}
+
+pub fn init(kind: BackendKind, options: &InitOptions) -> Result<Box<dyn Backend>> {
+    // Steam Input ignores the options, and may be the only backend.
+    let _ = options;
+    match kind {
+        #[cfg(feature = "steam")]
+        BackendKind::Steam => Ok(Box::new(steam::ImplementationContext::new()?)),
+        #[cfg(feature = "sdl2")]
+        BackendKind::Sdl2 => Ok(Box::new(sdl2::ImplementationContext::new(options)?)),
+        #[cfg(feature = "gilrs")]
+        BackendKind::Gilrs => Ok(Box::new(gilrs::ImplementationContext::new(options)?)),
+        #[cfg(all(feature = "xinput", windows))]
+        BackendKind::XInput => Ok(Box::new(xinput::ImplementationContext::new(options)?)),
+        #[cfg(all(feature = "evdev", target_os = "linux"))]
+        BackendKind::Evdev => Ok(Box::new(evdev::ImplementationContext::new(options)?)),
+        #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+        BackendKind::Web => Ok(Box::new(web::ImplementationContext::new(options)?)),
+    }
+}
+
+/// Initializes backends, either merged into one or the first that works, returning the
+/// backends that run.
+pub fn start(
+    kinds: &[BackendKind],
+    merged: bool,
+    options: &InitOptions,
+) -> Result<(Vec<BackendKind>, Box<dyn Backend>)> {
+    if merged {
+        let backend = init_merged(kinds, options)?;
+        let mut started = Vec::new();
+        for &kind in kinds {
+            if !started.contains(&kind) {
+                started.push(kind);
+            }
+        }
+        Ok((started, backend))
+    } else if let [kind] = kinds[..] {
+        Ok((vec![kind], init(kind, options)?))
+    } else {
+        let (kind, backend) = init_any(kinds, options)?;
+        Ok((vec![kind], backend))
+    }
+}
+
+/// Creates a backend that is initialized on its first update, and retried every `retry` until
+/// it works.
+pub fn start_lazily(
+    kinds: Vec<BackendKind>,
+    merged: bool,
+    options: InitOptions,
+    retry: Duration,
+) -> Box<dyn Backend> {
+    Box::new(lazy::ImplementationContext::new(
+        kinds, merged, options, retry,
+    ))
+}
+
+/// Initializes several backends, merged into one.
+pub fn init_merged(kinds: &[BackendKind], options: &InitOptions) -> Result<Box<dyn Backend>> {
+    if kinds.is_empty() {
+        return Err("No gamepad backend to merge".into());
+    }
+
+    let mut backends = Vec::new();
+    for &kind in kinds {
+        if backends.iter().any(|&(other, _)| other == kind) {
+            continue;
+        }
+        let backend = init(kind, options).map_err(|e| format!("{}: {}", kind, e))?;
+        backends.push((kind, backend));
+    }
+
+    Ok(Box::new(merged::ImplementationContext::new(backends)))
+}
+
+/// Initializes the first of several backends that works.
+pub fn init_any(
+    kinds: &[BackendKind],
+    options: &InitOptions,
+) -> Result<(BackendKind, Box<dyn Backend>)> {
+    let mut errors = Vec::new();
+    for &kind in kinds {
+        match init(kind, options) {
+            Ok(backend) => return Ok((kind, backend)),
+            Err(e) => errors.push(format!("{}: {}", kind, e)),
+        }
+    }
+
+    Err(format!(
+        "No gamepad backend could be initialized ({})",
+        errors.join(", ")
+    ))
+}
+
+#[cfg(all(test, any(feature = "sdl2", feature = "steam")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_backends_are_claimed_once() {
+        static ALIVE: AtomicBool = AtomicBool::new(false);
+        let first = Exclusive::acquire(&ALIVE, BackendKind::AVAILABLE[0]).unwrap();
+        assert!(Exclusive::acquire(&ALIVE, BackendKind::AVAILABLE[0]).is_err());
+        drop(first);
+        assert!(Exclusive::acquire(&ALIVE, BackendKind::AVAILABLE[0]).is_ok());
+    }
 }
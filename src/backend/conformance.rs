@@ -0,0 +1,160 @@
+//! A conformance suite that every backend runs, so that they report the same physical input
+//! the same way.
+//!
+//! A backend implements [`Driver`] by feeding its own native events through the code that
+//! applies them to gamepads, and runs the suite with [`conformance_tests`].
+
+use crate::{Axis, Button, Gamepad, Guid, Stick};
+
+/// Buttons that every backend reports.
+const COMMON_BUTTONS: [Button; 15] = [
+    Button::A,
+    Button::B,
+    Button::X,
+    Button::Y,
+    Button::Back,
+    Button::Guide,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::LeftShoulder,
+    Button::RightShoulder,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+/// How far values may be off, for backends that report integers.
+const TOLERANCE: f32 = 0.001;
+
+/// Drives a gamepad through the native events of a backend.
+pub(crate) trait Driver {
+    /// Presses or releases the physical button that reports as `button`. Returns `false` if the
+    /// backend has no such button.
+    fn set_button(gamepad: &mut Gamepad, button: Button, pressed: bool) -> bool;
+
+    /// Tilts a stick, with `x` positive to the right and `y` positive upwards, from `-1.0` to
+    /// `1.0`.
+    fn tilt_stick(gamepad: &mut Gamepad, stick: Stick, x: f32, y: f32);
+
+    /// Pulls a trigger, from `0.0` to `1.0`.
+    fn pull_trigger(gamepad: &mut Gamepad, trigger: Axis, pressure: f32);
+}
+
+fn gamepad() -> Gamepad {
+    Gamepad::new(None, Guid::default())
+}
+
+fn assert_close(value: f32, expected: f32) {
+    assert!(
+        (value - expected).abs() <= TOLERANCE,
+        "{} is not {}",
+        value,
+        expected
+    );
+}
+
+pub(crate) fn buttons_report_edges<D: Driver>() {
+    for button in COMMON_BUTTONS {
+        let mut gamepad = gamepad();
+        assert!(D::set_button(&mut gamepad, button, true), "{:?}", button);
+        let digital_inputs = &gamepad.digital_inputs;
+        assert!(digital_inputs.activated(button), "{:?}", button);
+        assert!(digital_inputs.just_activated(button), "{:?}", button);
+        assert_eq!(digital_inputs.activated_inputs().count(), 1, "{:?}", button);
+
+        gamepad.update_inputs();
+        assert!(gamepad.digital_inputs.activated(button), "{:?}", button);
+        assert!(
+            !gamepad.digital_inputs.just_activated(button),
+            "{:?}",
+            button
+        );
+
+        D::set_button(&mut gamepad, button, false);
+        assert!(!gamepad.digital_inputs.activated(button), "{:?}", button);
+        assert!(
+            gamepad.digital_inputs.just_deactivated(button),
+            "{:?}",
+            button
+        );
+    }
+}
+
+pub(crate) fn sticks_are_positive_right_and_down<D: Driver>() {
+    for stick in [Stick::Left, Stick::Right] {
+        let mut gamepad = gamepad();
+        D::tilt_stick(&mut gamepad, stick, 1.0, 1.0);
+        assert_close(gamepad.analog_inputs.value(stick.x()), 1.0);
+        assert_close(gamepad.analog_inputs.value(stick.y()), -1.0);
+
+        D::tilt_stick(&mut gamepad, stick, -1.0, -1.0);
+        assert_close(gamepad.analog_inputs.value(stick.x()), -1.0);
+        assert_close(gamepad.analog_inputs.value(stick.y()), 1.0);
+    }
+}
+
+pub(crate) fn sticks_apply_deadzones<D: Driver>() {
+    let mut gamepad = gamepad();
+    D::tilt_stick(&mut gamepad, Stick::Left, 0.05, 0.0);
+    assert_eq!(gamepad.analog_inputs.value(Axis::LeftX), 0.0);
+    assert!(gamepad.analog_inputs.raw_value(Axis::LeftX) > 0.0);
+    assert!(gamepad.analog_inputs.just_activated(Axis::LeftX).is_none());
+
+    D::tilt_stick(&mut gamepad, Stick::Left, 0.55, 0.0);
+    assert_close(gamepad.analog_inputs.value(Axis::LeftX), 0.5);
+    assert!(gamepad.analog_inputs.just_activated(Axis::LeftX).is_some());
+    assert_eq!(gamepad.analog_inputs.digital_value(Axis::LeftX), 1.0);
+
+    gamepad.update_inputs();
+    D::tilt_stick(&mut gamepad, Stick::Left, 0.0, 0.0);
+    assert_eq!(gamepad.analog_inputs.value(Axis::LeftX), 0.0);
+    assert!(gamepad.analog_inputs.just_deactivated(Axis::LeftX));
+}
+
+pub(crate) fn triggers_report_pressure<D: Driver>() {
+    for trigger in [Axis::TriggerLeft, Axis::TriggerRight] {
+        let mut gamepad = gamepad();
+        D::pull_trigger(&mut gamepad, trigger, 0.5);
+        assert_close(gamepad.analog_inputs.raw_value(trigger), 0.5);
+        assert!(gamepad.analog_inputs.just_activated(trigger).is_some());
+        assert_eq!(gamepad.digital_inputs.activated_inputs().count(), 0);
+
+        D::pull_trigger(&mut gamepad, trigger, 1.0);
+        assert_close(gamepad.analog_inputs.value(trigger), 1.0);
+        assert_eq!(gamepad.analog_inputs.digital_value(trigger), 1.0);
+
+        gamepad.update_inputs();
+        D::pull_trigger(&mut gamepad, trigger, 0.0);
+        assert_eq!(gamepad.analog_inputs.value(trigger), 0.0);
+        assert!(gamepad.analog_inputs.just_deactivated(trigger));
+    }
+}
+
+/// Runs the conformance suite against a [`Driver`].
+macro_rules! conformance_tests {
+    ($driver:ty) => {
+        #[test]
+        fn buttons_report_edges() {
+            $crate::backend::conformance::buttons_report_edges::<$driver>();
+        }
+
+        #[test]
+        fn sticks_are_positive_right_and_down() {
+            $crate::backend::conformance::sticks_are_positive_right_and_down::<$driver>();
+        }
+
+        #[test]
+        fn sticks_apply_deadzones() {
+            $crate::backend::conformance::sticks_apply_deadzones::<$driver>();
+        }
+
+        #[test]
+        fn triggers_report_pressure() {
+            $crate::backend::conformance::triggers_report_pressure::<$driver>();
+        }
+    };
+}
+
+pub(crate) use conformance_tests;
@@ -0,0 +1,557 @@
+use super::{InitOptions, UpdateReport};
+use crate::analog::AnalogInputValue;
+use crate::capabilities::Capabilities;
+use crate::timestamp::instant_from_system_time;
+use crate::{Axis, Button, Gamepad, GamepadId, Guid};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::Result;
+
+// Event types and codes from linux/input-event-codes.h.
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const SYN_DROPPED: u16 = 3;
+const BTN_SOUTH: u16 = 0x130;
+const KEY_MAX: usize = 0x2ff;
+const ABS_MAX: usize = 0x3f;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+/// How often to look for newly connected devices, when the input directory cannot be watched.
+const HOTPLUG_INTERVAL: Duration = Duration::from_secs(1);
+const INPUT_DIR: &str = "/dev/input";
+
+fn button(code: u16) -> Option<Button> {
+    match code {
+        0x130 => Some(Button::A),
+        0x131 => Some(Button::B),
+        0x133 => Some(Button::Y),
+        0x134 => Some(Button::X),
+        0x136 => Some(Button::LeftShoulder),
+        0x137 => Some(Button::RightShoulder),
+        0x13a => Some(Button::Back),
+        0x13b => Some(Button::Start),
+        0x13c => Some(Button::Guide),
+        0x13d => Some(Button::LeftStick),
+        0x13e => Some(Button::RightStick),
+        0x220 => Some(Button::DPadUp),
+        0x221 => Some(Button::DPadDown),
+        0x222 => Some(Button::DPadLeft),
+        0x223 => Some(Button::DPadRight),
+        // KEY_RECORD, the share button of Xbox Series controllers.
+        0xa7 => Some(Button::Misc1),
+        // BTN_TRIGGER_HAPPY5 to 8, the paddles of Xbox Elite controllers, in P1 to P4 order.
+        0x2c4 => Some(Button::Paddle1),
+        0x2c5 => Some(Button::Paddle3),
+        0x2c6 => Some(Button::Paddle2),
+        0x2c7 => Some(Button::Paddle4),
+        _ => None,
+    }
+}
+
+/// Digital triggers, mirrored onto the trigger axes for parity with SDL2.
+fn trigger_axis(code: u16) -> Option<Axis> {
+    match code {
+        0x138 => Some(Axis::TriggerLeft),
+        0x139 => Some(Axis::TriggerRight),
+        _ => None,
+    }
+}
+
+fn axis(code: u16) -> Option<Axis> {
+    match code {
+        ABS_X => Some(Axis::LeftX),
+        ABS_Y => Some(Axis::LeftY),
+        ABS_RX => Some(Axis::RightX),
+        ABS_RY => Some(Axis::RightY),
+        ABS_Z => Some(Axis::TriggerLeft),
+        ABS_RZ => Some(Axis::TriggerRight),
+        _ => None,
+    }
+}
+
+/// An opened evdev device node.
+struct Device {
+    file: File,
+    path: PathBuf,
+    // Maps absolute axis codes to their `(minimum, maximum)` values.
+    ranges: HashMap<u16, (i32, i32)>,
+}
+
+impl Device {
+    /// Opens a device node, returning `None` if it is not a gamepad.
+    fn open(path: &Path) -> Option<(Self, Guid, Capabilities)> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .ok()?;
+        let fd = file.as_raw_fd();
+
+        // Gamepads report `BTN_SOUTH`, also known as `BTN_GAMEPAD`.
+        let mut keys = [0u8; KEY_MAX / 8 + 1];
+        let request = ioc_read(0x20 + EV_KEY as u32, keys.len());
+        if unsafe { libc::ioctl(fd, request as _, keys.as_mut_ptr()) } < 0 {
+            return None;
+        }
+        if !has_bit(&keys, BTN_SOUTH) {
+            return None;
+        }
+
+        let mut id: libc::input_id = unsafe { mem::zeroed() };
+        let request = ioc_read(0x02, mem::size_of::<libc::input_id>());
+        if unsafe { libc::ioctl(fd, request as _, &mut id) } < 0 {
+            return None;
+        }
+
+        let mut ranges = HashMap::new();
+        for code in [ABS_X, ABS_Y, ABS_Z, ABS_RX, ABS_RY, ABS_RZ] {
+            let mut info: libc::input_absinfo = unsafe { mem::zeroed() };
+            let request = ioc_read(0x40 + code as u32, mem::size_of::<libc::input_absinfo>());
+            if unsafe { libc::ioctl(fd, request as _, &mut info) } >= 0
+                && info.maximum > info.minimum
+            {
+                ranges.insert(code, (info.minimum, info.maximum));
+            }
+        }
+
+        let mut abs = [0u8; ABS_MAX / 8 + 1];
+        let request = ioc_read(0x20 + EV_ABS as u32, abs.len());
+        if unsafe { libc::ioctl(fd, request as _, abs.as_mut_ptr()) } < 0 {
+            abs = [0; ABS_MAX / 8 + 1];
+        }
+        let capabilities = capabilities(&keys, &abs);
+
+        let device = Self {
+            file,
+            path: path.to_owned(),
+            ranges,
+        };
+
+        Some((device, guid(&id), capabilities))
+    }
+
+    /// Reads the name of the device, with `EVIOCGNAME`.
+    fn name(&self) -> Option<String> {
+        self.read_string(0x06)
+    }
+
+    /// Reads the serial number of the device, with `EVIOCGUNIQ`. Most devices have none.
+    fn serial(&self) -> Option<String> {
+        self.read_string(0x08).filter(|serial| !serial.is_empty())
+    }
+
+    /// Reads a string of the device with an `ioctl` request number.
+    fn read_string(&self, number: u32) -> Option<String> {
+        let mut string = [0u8; 256];
+        let request = ioc_read(number, string.len());
+        let len = unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, string.as_mut_ptr()) };
+        let len = usize::try_from(len).ok()?;
+        let string = &string[..len.min(string.len())];
+        let string = string.split(|&byte| byte == 0).next().unwrap_or_default();
+        Some(String::from_utf8_lossy(string).into_owned())
+    }
+
+    /// Reads all pending events into `events`, returning an error if the device is gone.
+    fn read_events(&mut self, events: &mut Vec<libc::input_event>) -> io::Result<()> {
+        const EVENT_SIZE: usize = mem::size_of::<libc::input_event>();
+
+        events.clear();
+        let mut buffer = [0u8; EVENT_SIZE * 64];
+        loop {
+            let len = match self.file.read(&mut buffer) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            for chunk in buffer[..len].chunks_exact(EVENT_SIZE) {
+                // Safety: the kernel writes whole `input_event` structs.
+                events
+                    .push(unsafe { (chunk.as_ptr() as *const libc::input_event).read_unaligned() });
+            }
+        }
+    }
+}
+
+/// Maps a raw axis value to `[-1.0, 1.0]`, or to `[0.0, 1.0]` for triggers.
+fn normalize(ranges: &HashMap<u16, (i32, i32)>, code: u16, value: i32) -> f32 {
+    let (min, max) = match ranges.get(&code) {
+        Some(&range) => range,
+        None => return 0.0,
+    };
+    let unit = (value - min) as f32 / (max - min) as f32;
+
+    match code {
+        ABS_Z | ABS_RZ => unit,
+        _ => unit * 2.0 - 1.0,
+    }
+}
+
+/// Watches the input directory for new device nodes, and for nodes whose permissions change,
+/// since udev only grants access after creating them.
+fn watch_input_dir() -> Option<File> {
+    // SAFETY: inotify_init1 has no preconditions.
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+    if fd < 0 {
+        return None;
+    }
+    // SAFETY: `fd` is a new descriptor that nothing else owns.
+    let watch = unsafe { File::from_raw_fd(fd) };
+
+    let dir = CString::new(INPUT_DIR).expect("the path contains no nul bytes");
+    // SAFETY: `watch` is an inotify descriptor and `dir` is nul-terminated.
+    let added = unsafe {
+        libc::inotify_add_watch(
+            watch.as_raw_fd(),
+            dir.as_ptr(),
+            libc::IN_CREATE | libc::IN_ATTRIB,
+        )
+    };
+    (added >= 0).then_some(watch)
+}
+
+/// Reads the pending events of an inotify watch, returning whether there were any.
+fn drain_watch(watch: &mut File) -> bool {
+    let mut buffer = [0; 4096];
+    let mut changed = false;
+    // The watch is non-blocking, so reading fails once it is drained.
+    while let Ok(1..) = watch.read(&mut buffer) {
+        changed = true;
+    }
+    changed
+}
+
+/// Checks if a code is set in a bitmap read with `EVIOCGBIT`.
+fn has_bit(bits: &[u8], code: u16) -> bool {
+    let code = code as usize;
+    bits.get(code / 8)
+        .is_some_and(|byte| byte & (1 << (code % 8)) != 0)
+}
+
+/// Gets the buttons and axes of a device from the codes it reports. Rumble is not supported.
+fn capabilities(keys: &[u8], abs: &[u8]) -> Capabilities {
+    let key_codes = (0..=KEY_MAX as u16).filter(|&code| has_bit(keys, code));
+    let abs_codes = (0..=ABS_MAX as u16).filter(|&code| has_bit(abs, code));
+
+    let mut buttons: Vec<_> = key_codes.clone().filter_map(button).collect();
+    if has_bit(abs, ABS_HAT0X) {
+        buttons.extend([Button::DPadLeft, Button::DPadRight]);
+    }
+    if has_bit(abs, ABS_HAT0Y) {
+        buttons.extend([Button::DPadUp, Button::DPadDown]);
+    }
+    let axes = key_codes
+        .filter_map(trigger_axis)
+        .chain(abs_codes.filter_map(axis));
+
+    Capabilities::with_inputs(buttons, axes)
+}
+
+/// Builds an `_IOC(_IOC_READ, 'E', nr, size)` ioctl request.
+fn ioc_read(nr: u32, size: usize) -> u64 {
+    (2 << 30) | ((size as u64) << 16) | ((b'E' as u64) << 8) | nr as u64
+}
+
+/// Builds a GUID the same way SDL2 does for Linux devices.
+fn guid(id: &libc::input_id) -> Guid {
+    let mut guid = [0; 16];
+    for (i, value) in [id.bustype, id.vendor, id.product, id.version]
+        .into_iter()
+        .enumerate()
+    {
+        guid[i * 4..i * 4 + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    Guid(guid)
+}
+
+pub struct ImplementationContext {
+    devices: HashMap<GamepadId, Device>,
+    // Events read from a device, kept to reuse its allocation.
+    events: Vec<libc::input_event>,
+    next_id: usize,
+    // An inotify watch of the input directory, or `None` to rescan it periodically.
+    watch: Option<File>,
+    last_scan: Option<Instant>,
+    ignore_devices: bool,
+    logging: bool,
+}
+
+impl ImplementationContext {
+    pub fn new(options: &InitOptions) -> Result<Self> {
+        fs::read_dir(INPUT_DIR).map_err(|e| format!("Failed to read {}: {}", INPUT_DIR, e))?;
+
+        Ok(Self {
+            devices: HashMap::new(),
+            events: Vec::new(),
+            next_id: 0,
+            watch: watch_input_dir(),
+            last_scan: None,
+            ignore_devices: false,
+            logging: options.logging,
+        })
+    }
+
+    /// Opens gamepads that are not open yet.
+    ///
+    /// Device nodes are watched through inotify rather than udev, so that the backend has no
+    /// system dependencies.
+    fn scan(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        let changed = match &mut self.watch {
+            Some(watch) => drain_watch(watch) || self.last_scan.is_none(),
+            None => self
+                .last_scan
+                .is_none_or(|last_scan| last_scan.elapsed() >= HOTPLUG_INTERVAL),
+        };
+        if !changed {
+            return;
+        }
+        self.last_scan = Some(Instant::now());
+
+        let entries = match fs::read_dir(INPUT_DIR) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_event_node = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"));
+            if !is_event_node || self.devices.values().any(|device| device.path == path) {
+                continue;
+            }
+
+            // Nodes that cannot be opened, usually for lack of permissions, are skipped.
+            if let Some((device, guid, capabilities)) = Device::open(&path) {
+                let id = GamepadId(self.next_id);
+                self.next_id += 1;
+                let mut gamepad = Gamepad::new(None, guid);
+                gamepad.capabilities = capabilities;
+                gamepad.name = device.name();
+                gamepad.serial = device.serial();
+                self.devices.insert(id, device);
+                gamepads.insert(id, gamepad);
+
+                #[cfg(debug_assertions)]
+                log!(self.logging, "Added gamepad \"{}\"", path.display());
+            }
+        }
+    }
+}
+
+impl super::Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        if self.ignore_devices {
+            return report;
+        }
+
+        self.scan(gamepads);
+
+        let mut removed = Vec::new();
+        for (&id, device) in self.devices.iter_mut() {
+            if let Err(e) = device.read_events(&mut self.events) {
+                // Unplugged devices end their file, or fail once their node is gone.
+                if !matches!(e.kind(), io::ErrorKind::UnexpectedEof)
+                    && e.raw_os_error() != Some(libc::ENODEV)
+                {
+                    let error = format!("Failed to read {}: {}", device.path.display(), e);
+                    report.device_errors.push((id, error));
+                }
+                removed.push(id);
+                continue;
+            }
+            let gamepad = match gamepads.get_mut(&id) {
+                Some(gamepad) => gamepad,
+                None => continue,
+            };
+
+            report.events += self.events.len();
+            for event in self.events.drain(..) {
+                apply_event(gamepad, &device.ranges, &event);
+            }
+        }
+
+        for id in removed {
+            #[cfg(debug_assertions)]
+            if let Some(device) = self.devices.get(&id) {
+                log!(
+                    self.logging,
+                    "Removed gamepad \"{}\"",
+                    device.path.display()
+                );
+            }
+
+            self.devices.remove(&id);
+            gamepads.remove(&id);
+        }
+
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+    }
+}
+
+/// Applies an event of a device, whose axes have the given ranges, to its gamepad.
+fn apply_event(
+    gamepad: &mut Gamepad,
+    ranges: &HashMap<u16, (i32, i32)>,
+    event: &libc::input_event,
+) {
+    let time = event_time(event);
+    match (event.type_, event.code) {
+        // The kernel buffer overflowed. Later events still carry absolute values,
+        // so the state recovers with the next change of every input.
+        (EV_SYN, SYN_DROPPED) => (),
+        (EV_KEY, code) => {
+            if let Some(axis) = trigger_axis(code) {
+                let value = if event.value == 0 { 0.0 } else { 1.0 };
+                gamepad
+                    .analog_inputs
+                    .set_at(axis, AnalogInputValue::from(value), time);
+            }
+
+            match (button(code), event.value) {
+                (Some(button), 0) => gamepad.digital_inputs.deactivate_at(button, time),
+                (Some(button), 1) => gamepad.digital_inputs.activate_at(button, time),
+                // Value 2 is a key repeat.
+                _ => (),
+            }
+        }
+        (EV_ABS, ABS_HAT0X) => set_hat(
+            gamepad,
+            event.value,
+            time,
+            Button::DPadLeft,
+            Button::DPadRight,
+        ),
+        (EV_ABS, ABS_HAT0Y) => {
+            set_hat(gamepad, event.value, time, Button::DPadUp, Button::DPadDown)
+        }
+        (EV_ABS, code) => {
+            if let Some(axis) = axis(code) {
+                let value = normalize(ranges, code, event.value);
+                gamepad
+                    .analog_inputs
+                    .set_at(axis, AnalogInputValue::from(value), time);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Maps a d-pad hat axis onto its two buttons.
+fn set_hat(gamepad: &mut Gamepad, value: i32, time: Instant, negative: Button, positive: Button) {
+    for (button, active) in [(negative, value < 0), (positive, value > 0)] {
+        if active {
+            gamepad.digital_inputs.activate_at(button, time);
+        } else {
+            gamepad.digital_inputs.deactivate_at(button, time);
+        }
+    }
+}
+
+/// Gets when the kernel received an event, which is timestamped with the realtime clock.
+fn event_time(event: &libc::input_event) -> Instant {
+    let since_epoch = Duration::new(
+        event.time.tv_sec.max(0) as u64,
+        (event.time.tv_usec.clamp(0, 999_999) * 1000) as u32,
+    );
+    instant_from_system_time(UNIX_EPOCH + since_epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stick;
+    use std::time::SystemTime;
+
+    struct Driver;
+
+    /// The ranges of an Xbox controller.
+    fn ranges() -> HashMap<u16, (i32, i32)> {
+        let mut ranges = HashMap::new();
+        for code in [ABS_X, ABS_Y, ABS_RX, ABS_RY] {
+            ranges.insert(code, (-32768, 32767));
+        }
+        for code in [ABS_Z, ABS_RZ] {
+            ranges.insert(code, (0, 1023));
+        }
+        ranges
+    }
+
+    fn send(gamepad: &mut Gamepad, type_: u16, code: u16, value: i32) {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let event = libc::input_event {
+            time: libc::timeval {
+                tv_sec: since_epoch.as_secs() as _,
+                tv_usec: since_epoch.subsec_micros() as _,
+            },
+            type_,
+            code,
+            value,
+        };
+        apply_event(gamepad, &ranges(), &event);
+    }
+
+    /// Converts a value to the range of an axis.
+    fn to_range(code: u16, value: f32) -> i32 {
+        let (min, max) = ranges()[&code];
+        let unit = match code {
+            ABS_Z | ABS_RZ => value,
+            _ => (value + 1.0) / 2.0,
+        };
+        (min as f32 + unit * (max - min) as f32).round() as i32
+    }
+
+    impl crate::backend::conformance::Driver for Driver {
+        fn set_button(gamepad: &mut Gamepad, button: Button, pressed: bool) -> bool {
+            let Some(code) = (0..=KEY_MAX as u16).find(|&code| self::button(code) == Some(button))
+            else {
+                return false;
+            };
+            send(gamepad, EV_KEY, code, pressed as i32);
+            true
+        }
+
+        fn tilt_stick(gamepad: &mut Gamepad, stick: Stick, x: f32, y: f32) {
+            let (x_code, y_code) = match stick {
+                Stick::Left => (ABS_X, ABS_Y),
+                Stick::Right => (ABS_RX, ABS_RY),
+            };
+            // evdev reports vertical axes as positive downwards.
+            send(gamepad, EV_ABS, x_code, to_range(x_code, x));
+            send(gamepad, EV_ABS, y_code, to_range(y_code, -y));
+        }
+
+        fn pull_trigger(gamepad: &mut Gamepad, trigger: Axis, pressure: f32) {
+            let code = match trigger {
+                Axis::TriggerLeft => ABS_Z,
+                _ => ABS_RZ,
+            };
+            send(gamepad, EV_ABS, code, to_range(code, pressure));
+        }
+    }
+
+    crate::backend::conformance::conformance_tests!(Driver);
+}
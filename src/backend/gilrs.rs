@@ -1,92 +1,427 @@
-pub use gilrs::{Axis, Button};
-
+use super::{GamepadDevice, InitOptions, UpdateReport};
 use crate::analog::AnalogInputValue;
-use crate::{Gamepad, GamepadId};
+use crate::capabilities::Capabilities;
+use crate::glyph::ControllerType;
+use crate::sensor::Sensor;
+use crate::timestamp::instant_from_system_time;
+use crate::{Axis, Button, Gamepad, GamepadId, Guid};
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat};
+use std::any::Any;
 use std::collections::HashMap;
+use std::time::Instant;
 
 use crate::Result;
 
-pub type ImplementationId = gilrs::GamepadId;
+/// Maps a gilrs axis, along with whether its direction has to be flipped.
+///
+/// gilrs reports vertical stick axes as positive upwards, unlike SDL2. D-pad axes are dropped,
+/// since gilrs also reports the d-pad as buttons.
+fn axis(axis: gilrs::Axis) -> Option<(Axis, bool)> {
+    match axis {
+        gilrs::Axis::LeftStickX => Some((Axis::LeftX, false)),
+        gilrs::Axis::LeftStickY => Some((Axis::LeftY, true)),
+        gilrs::Axis::LeftZ => Some((Axis::TriggerLeft, false)),
+        gilrs::Axis::RightStickX => Some((Axis::RightX, false)),
+        gilrs::Axis::RightStickY => Some((Axis::RightY, true)),
+        gilrs::Axis::RightZ => Some((Axis::TriggerRight, false)),
+        gilrs::Axis::DPadX | gilrs::Axis::DPadY | gilrs::Axis::Unknown => None,
+    }
+}
+
+/// Maps a gilrs button. Trigger buttons are reported as trigger axes instead, like SDL2 does.
+fn button(button: gilrs::Button) -> Option<Button> {
+    match button {
+        gilrs::Button::South => Some(Button::A),
+        gilrs::Button::East => Some(Button::B),
+        gilrs::Button::North => Some(Button::Y),
+        gilrs::Button::West => Some(Button::X),
+        gilrs::Button::LeftTrigger => Some(Button::LeftShoulder),
+        gilrs::Button::RightTrigger => Some(Button::RightShoulder),
+        gilrs::Button::Select => Some(Button::Back),
+        gilrs::Button::Start => Some(Button::Start),
+        gilrs::Button::Mode => Some(Button::Guide),
+        gilrs::Button::LeftThumb => Some(Button::LeftStick),
+        gilrs::Button::RightThumb => Some(Button::RightStick),
+        gilrs::Button::DPadUp => Some(Button::DPadUp),
+        gilrs::Button::DPadDown => Some(Button::DPadDown),
+        gilrs::Button::DPadLeft => Some(Button::DPadLeft),
+        gilrs::Button::DPadRight => Some(Button::DPadRight),
+        gilrs::Button::LeftTrigger2
+        | gilrs::Button::RightTrigger2
+        | gilrs::Button::C
+        | gilrs::Button::Z
+        | gilrs::Button::Unknown => None,
+    }
+}
+
+/// Every gilrs button that maps to a button or an axis, to ask gamepads which ones they have.
+const GILRS_BUTTONS: [gilrs::Button; 17] = [
+    gilrs::Button::South,
+    gilrs::Button::East,
+    gilrs::Button::North,
+    gilrs::Button::West,
+    gilrs::Button::LeftTrigger,
+    gilrs::Button::LeftTrigger2,
+    gilrs::Button::RightTrigger,
+    gilrs::Button::RightTrigger2,
+    gilrs::Button::Select,
+    gilrs::Button::Start,
+    gilrs::Button::Mode,
+    gilrs::Button::LeftThumb,
+    gilrs::Button::RightThumb,
+    gilrs::Button::DPadUp,
+    gilrs::Button::DPadDown,
+    gilrs::Button::DPadLeft,
+    gilrs::Button::DPadRight,
+];
+
+/// Every gilrs axis that maps to an axis, like [`GILRS_BUTTONS`].
+const GILRS_AXES: [gilrs::Axis; 6] = [
+    gilrs::Axis::LeftStickX,
+    gilrs::Axis::LeftStickY,
+    gilrs::Axis::LeftZ,
+    gilrs::Axis::RightStickX,
+    gilrs::Axis::RightStickY,
+    gilrs::Axis::RightZ,
+];
+
+/// Gets the buttons and axes that the mapping of a gamepad has.
+fn capabilities(gamepad: gilrs::Gamepad<'_>) -> Capabilities {
+    let mut buttons = Vec::new();
+    let mut axes = Vec::new();
+    for gilrs_button in GILRS_BUTTONS {
+        if gamepad.button_code(gilrs_button).is_none() {
+            continue;
+        }
+        match (button(gilrs_button), trigger_axis(gilrs_button)) {
+            (Some(button), _) => buttons.push(button),
+            (None, Some(axis)) => axes.push(axis),
+            (None, None) => (),
+        }
+    }
+    // Some gamepads report the D-pad as axes, which gilrs turns into buttons.
+    if gamepad.axis_code(gilrs::Axis::DPadX).is_some() {
+        buttons.extend([Button::DPadLeft, Button::DPadRight]);
+    }
+    if gamepad.axis_code(gilrs::Axis::DPadY).is_some() {
+        buttons.extend([Button::DPadUp, Button::DPadDown]);
+    }
+    for gilrs_axis in GILRS_AXES {
+        if let (Some(_), Some((axis, _))) = (gamepad.axis_code(gilrs_axis), axis(gilrs_axis)) {
+            axes.push(axis);
+        }
+    }
+
+    let mut capabilities = Capabilities::with_inputs(buttons, axes);
+    capabilities.rumble = gamepad.is_ff_supported();
+    capabilities
+}
+
+fn id(id: gilrs::GamepadId) -> GamepadId {
+    GamepadId(id.into())
+}
+
+/// Rumble through gilrs force feedback.
+///
+/// Each motor plays an endless effect at full strength, whose gain is changed to set the rumble.
+struct Device {
+    id: gilrs::GamepadId,
+    motors: Option<(Effect, Effect)>,
+}
+
+impl Device {
+    fn new(context: &mut gilrs::Gilrs, id: gilrs::GamepadId) -> Self {
+        let motors = if context.gamepad(id).is_ff_supported() {
+            let strong = motor(
+                context,
+                id,
+                BaseEffectType::Strong {
+                    magnitude: u16::MAX,
+                },
+            );
+            let weak = motor(
+                context,
+                id,
+                BaseEffectType::Weak {
+                    magnitude: u16::MAX,
+                },
+            );
+            strong.ok().zip(weak.ok())
+        } else {
+            None
+        };
+
+        Self { id, motors }
+    }
+}
+
+fn motor(
+    context: &mut gilrs::Gilrs,
+    id: gilrs::GamepadId,
+    kind: BaseEffectType,
+) -> std::result::Result<Effect, gilrs::ff::Error> {
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind,
+            ..Default::default()
+        })
+        .gamepads(&[id])
+        .repeat(Repeat::Infinitely)
+        .gain(0.0)
+        .finish(context)?;
+    effect.play()?;
+
+    Ok(effect)
+}
+
+impl GamepadDevice for Device {
+    fn has_sensor(&self, _: Sensor) -> bool {
+        false
+    }
+
+    fn set_sensor_enabled(&self, _: Sensor, _: bool) -> Result<()> {
+        Err("Motion sensors are not supported by gilrs".into())
+    }
+
+    fn set_led(&self, _: u8, _: u8, _: u8) -> Result<()> {
+        Err("LEDs are not supported by gilrs".into())
+    }
+
+    fn set_player_index(&self, _: Option<u8>) -> Result<()> {
+        Err("Player indices are not supported by gilrs".into())
+    }
+
+    fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()> {
+        let (strong, weak) = self
+            .motors
+            .as_ref()
+            .ok_or("This gamepad does not support rumble")?;
+        strong
+            .set_gain(low_frequency.clamp(0.0, 1.0))
+            .and_then(|_| weak.set_gain(high_frequency.clamp(0.0, 1.0)))
+            .map_err(|e| e.to_string())
+    }
+
+    fn controller_type(&self) -> Option<ControllerType> {
+        None
+    }
 
-impl From<GamepadId> for usize {
-    fn from(id: GamepadId) -> Self {
-        id.0.into()
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
 
-pub enum OwnedImplementationGamepad {}
+impl Gamepad {
+    /// Gets the gilrs id of the gamepad, for features that fishsticks does not wrap.
+    ///
+    /// Returns `None` for gamepads of other backends.
+    pub fn gilrs_id(&self) -> Option<gilrs::GamepadId> {
+        Some(self.device::<Device>()?.id)
+    }
+}
 
 pub struct ImplementationContext {
     context: gilrs::Gilrs,
-    init_gamepads: Vec<GamepadId>,
+    init_gamepads: Vec<gilrs::GamepadId>,
+    ignore_devices: bool,
+    logging: bool,
 }
 
 impl ImplementationContext {
-    pub fn new() -> Result<Self> {
-        match gilrs::Gilrs::new() {
+    pub fn new(options: &InitOptions) -> Result<Self> {
+        let mut builder = gilrs::GilrsBuilder::new();
+        for path in &options.mappings {
+            let mappings = std::fs::read_to_string(path).map_err(|e| {
+                format!(
+                    "Failed to load controller mappings from {}: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+            builder = builder.add_mappings(&mappings);
+        }
+
+        match builder.build() {
             Ok(context) => {
                 let mut init_gamepads = Vec::new();
 
                 for (gamepad_id, _) in context.gamepads() {
-                    init_gamepads.push(GamepadId(gamepad_id));
+                    init_gamepads.push(gamepad_id);
                 }
 
                 Ok(Self {
                     context,
                     init_gamepads,
+                    ignore_devices: false,
+                    logging: options.logging,
                 })
             }
             Err(e) => Err(e.to_string()),
         }
     }
+
+    fn gamepad(&mut self, id: gilrs::GamepadId) -> Gamepad {
+        let guid = Guid(self.context.gamepad(id).uuid());
+        let name = self.context.gamepad(id).name().to_owned();
+        let capabilities = capabilities(self.context.gamepad(id));
+        let device = Device::new(&mut self.context, id);
+        let mut gamepad = Gamepad::new(Some(Box::new(device)), guid);
+        gamepad.name = Some(name);
+        gamepad.capabilities = capabilities;
+        gamepad
+    }
 }
 
 impl super::Backend for ImplementationContext {
-    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> Result<()> {
-        for gamepad in self.init_gamepads.drain(..) {
-            gamepads.insert(gamepad, Gamepad::new(None));
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        for gamepad in std::mem::take(&mut self.init_gamepads) {
+            gamepads.insert(id(gamepad), self.gamepad(gamepad));
         }
 
-        for (_, gamepad) in gamepads.iter_mut() {
-            gamepad.update_inputs();
-        }
-
-        while let Some(gilrs::Event { id, event, .. }) = self.context.next_event() {
+        while let Some(gilrs::Event {
+            id: gilrs_id,
+            event,
+            time,
+        }) = self.context.next_event()
+        {
             use gilrs::EventType;
+            report.events += 1;
+            let id = id(gilrs_id);
+            let time = instant_from_system_time(time);
             match event {
+                EventType::Connected if self.ignore_devices => (),
                 EventType::Connected => {
-                    gamepads.insert(GamepadId(id), Gamepad::new(None));
+                    gamepads.insert(id, self.gamepad(gilrs_id));
 
                     #[cfg(debug_assertions)]
-                    println!("Added gamepad \"{}\"", self.context.gamepad(id).name());
+                    log!(
+                        self.logging,
+                        "Added gamepad \"{}\"",
+                        self.context.gamepad(gilrs_id).name()
+                    );
                 }
                 EventType::Disconnected => {
-                    gamepads.remove(&GamepadId(id));
+                    gamepads.remove(&id);
 
                     #[cfg(debug_assertions)]
-                    println!("Removed gamepad \"{}\"", self.context.gamepad(id).name());
+                    log!(
+                        self.logging,
+                        "Removed gamepad \"{}\"",
+                        self.context.gamepad(gilrs_id).name()
+                    );
                 }
                 EventType::AxisChanged(axis, value, _) => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(id)) {
-                        gamepad
-                            .analog_inputs
-                            .set(axis, AnalogInputValue::from(value));
+                    if let Some(gamepad) = gamepads.get_mut(&id) {
+                        set_axis(gamepad, axis, value, time);
+                    }
+                }
+                EventType::ButtonChanged(button, value, _) => {
+                    if let Some(gamepad) = gamepads.get_mut(&id) {
+                        set_button_value(gamepad, button, value, time);
                     }
                 }
                 EventType::ButtonPressed(button, _) => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(id)) {
-                        gamepad.digital_inputs.activate(button);
+                    if let Some(gamepad) = gamepads.get_mut(&id) {
+                        set_button(gamepad, button, true, time);
                     }
                 }
                 EventType::ButtonReleased(button, _) => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(id)) {
-                        gamepad.digital_inputs.deactivate(button);
+                    if let Some(gamepad) = gamepads.get_mut(&id) {
+                        set_button(gamepad, button, false, time);
                     }
                 }
-                _ => (),
+                // Repeats are synthesized by gilrs filters and do not change the button state.
+                // Dropped events have been discarded by a filter and must be ignored.
+                EventType::ButtonRepeated(..) | EventType::Dropped => (),
             }
         }
 
-        Ok(())
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+        self.init_gamepads.clear();
+    }
+}
+
+/// Applies a change of an axis to a gamepad.
+fn set_axis(gamepad: &mut Gamepad, axis: gilrs::Axis, value: f32, time: Instant) {
+    if let Some((axis, flipped)) = self::axis(axis) {
+        let value = if flipped { -value } else { value };
+        gamepad
+            .analog_inputs
+            .set_at(axis, AnalogInputValue::from(value), time);
+    }
+}
+
+/// Applies the pressure of a button to a gamepad.
+///
+/// Pressure-sensitive triggers are reported as buttons by gilrs, but as axes by SDL2. Their
+/// values are mirrored onto the trigger axes for parity.
+fn set_button_value(gamepad: &mut Gamepad, button: gilrs::Button, value: f32, time: Instant) {
+    if let Some(axis) = trigger_axis(button) {
+        gamepad
+            .analog_inputs
+            .set_at(axis, AnalogInputValue::from(value), time);
+    }
+}
+
+/// Applies a press or release of a button to a gamepad.
+fn set_button(gamepad: &mut Gamepad, button: gilrs::Button, pressed: bool, time: Instant) {
+    match self::button(button) {
+        Some(button) if pressed => gamepad.digital_inputs.activate_at(button, time),
+        Some(button) => gamepad.digital_inputs.deactivate_at(button, time),
+        None => (),
+    }
+}
+
+/// Gets the axis that mirrors a pressure-sensitive trigger button.
+fn trigger_axis(button: gilrs::Button) -> Option<Axis> {
+    match button {
+        gilrs::Button::LeftTrigger2 => Some(Axis::TriggerLeft),
+        gilrs::Button::RightTrigger2 => Some(Axis::TriggerRight),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stick;
+
+    struct Driver;
+
+    impl crate::backend::conformance::Driver for Driver {
+        fn set_button(gamepad: &mut Gamepad, button: Button, pressed: bool) -> bool {
+            let Some(gilrs_button) = GILRS_BUTTONS
+                .into_iter()
+                .find(|&gilrs_button| self::button(gilrs_button) == Some(button))
+            else {
+                return false;
+            };
+            set_button(gamepad, gilrs_button, pressed, Instant::now());
+            true
+        }
+
+        fn tilt_stick(gamepad: &mut Gamepad, stick: Stick, x: f32, y: f32) {
+            let (x_axis, y_axis) = match stick {
+                Stick::Left => (gilrs::Axis::LeftStickX, gilrs::Axis::LeftStickY),
+                Stick::Right => (gilrs::Axis::RightStickX, gilrs::Axis::RightStickY),
+            };
+            set_axis(gamepad, x_axis, x, Instant::now());
+            set_axis(gamepad, y_axis, y, Instant::now());
+        }
+
+        fn pull_trigger(gamepad: &mut Gamepad, trigger: Axis, pressure: f32) {
+            let button = match trigger {
+                Axis::TriggerLeft => gilrs::Button::LeftTrigger2,
+                _ => gilrs::Button::RightTrigger2,
+            };
+            set_button_value(gamepad, button, pressure, Instant::now());
+        }
     }
+
+    crate::backend::conformance::conformance_tests!(Driver);
 }
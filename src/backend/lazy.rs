@@ -0,0 +1,236 @@
+use super::{Backend, BackendKind, BackendState, InitOptions, UpdateReport};
+use crate::{Gamepad, GamepadId};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::Result;
+
+/// Initializes backends on their first update rather than when the context is created, and
+/// retries until they work, so that games run without gamepads on systems where they fail.
+///
+/// SDL2, Steam Input and the web backend are initialized on the thread that updates the
+/// context, since they have to stay on the thread that created them. Other backends are first
+/// tried on a worker thread, so that failing or hanging attempts do not stall updates, and only
+/// started on the updating thread once an attempt works, since gilrs cannot be moved between
+/// threads.
+pub struct ImplementationContext {
+    kinds: Vec<BackendKind>,
+    merged: bool,
+    options: InitOptions,
+    retry: Duration,
+    last_attempt: Option<Instant>,
+    // The outcome of an attempt on the worker thread, while it runs.
+    attempt: Option<Receiver<Result<()>>>,
+    started: Option<(Vec<BackendKind>, Box<dyn Backend>)>,
+    error: Option<String>,
+    // Settings made before the backends started, applied once they do.
+    ignore_devices: bool,
+    raw_joysticks: Option<bool>,
+    #[cfg(feature = "steam")]
+    steam_action_set: Option<String>,
+}
+
+impl ImplementationContext {
+    pub fn new(
+        kinds: Vec<BackendKind>,
+        merged: bool,
+        options: InitOptions,
+        retry: Duration,
+    ) -> Self {
+        Self {
+            kinds,
+            merged,
+            options,
+            retry,
+            last_attempt: None,
+            attempt: None,
+            started: None,
+            error: None,
+            ignore_devices: false,
+            raw_joysticks: None,
+            #[cfg(feature = "steam")]
+            steam_action_set: None,
+        }
+    }
+
+    fn try_start(&mut self) {
+        if let Some(attempt) = &self.attempt {
+            let result = match attempt.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    Err("The backend panicked while starting".into())
+                }
+            };
+            self.attempt = None;
+            match result {
+                Ok(()) => self.start(),
+                Err(e) => self.error = Some(e),
+            }
+            return;
+        }
+
+        let now = Instant::now();
+        if self
+            .last_attempt
+            .is_some_and(|last_attempt| now.duration_since(last_attempt) < self.retry)
+        {
+            return;
+        }
+        self.last_attempt = Some(now);
+
+        if self.kinds.iter().all(|&kind| thread_agnostic(kind)) {
+            let (sender, receiver) = mpsc::channel();
+            let (kinds, merged, options) = (self.kinds.clone(), self.merged, self.options.clone());
+            let spawned = thread::Builder::new()
+                .name("fishsticks-start".into())
+                .spawn(move || {
+                    let result = super::start(&kinds, merged, &options).map(drop);
+                    let _ = sender.send(result);
+                });
+            if spawned.is_ok() {
+                self.attempt = Some(receiver);
+                return;
+            }
+        }
+        self.start();
+    }
+
+    /// Starts the backends on this thread.
+    fn start(&mut self) {
+        match super::start(&self.kinds, self.merged, &self.options) {
+            Ok((kinds, mut backend)) => {
+                if self.ignore_devices {
+                    backend.ignore_devices();
+                }
+                if let Some(enabled) = self.raw_joysticks {
+                    // Backends without raw joysticks ignore the setting.
+                    let _ = backend.set_raw_joysticks(enabled);
+                }
+                #[cfg(feature = "steam")]
+                if let Some(name) = &self.steam_action_set {
+                    // Backends without action sets ignore the setting.
+                    let _ = backend.set_steam_action_set(name);
+                }
+                self.started = Some((kinds, backend));
+                self.error = None;
+            }
+            // Reported through the backend state rather than printed on every retry.
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// Checks if a backend can be initialized on any thread.
+fn thread_agnostic(kind: BackendKind) -> bool {
+    match kind {
+        #[cfg(feature = "steam")]
+        BackendKind::Steam => false,
+        #[cfg(feature = "sdl2")]
+        BackendKind::Sdl2 => false,
+        #[cfg(feature = "gilrs")]
+        BackendKind::Gilrs => true,
+        #[cfg(all(feature = "xinput", windows))]
+        BackendKind::XInput => true,
+        #[cfg(all(feature = "evdev", target_os = "linux"))]
+        BackendKind::Evdev => true,
+        #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+        BackendKind::Web => false,
+    }
+}
+
+impl Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        if self.started.is_none() {
+            self.try_start();
+        }
+
+        match &mut self.started {
+            Some((_, backend)) => backend.update(gamepads),
+            // Failures are reported by the state, so that the game goes on without gamepads.
+            None => UpdateReport::default(),
+        }
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+        if let Some((_, backend)) = &mut self.started {
+            backend.ignore_devices();
+        }
+    }
+
+    fn set_raw_joysticks(&mut self, enabled: bool) -> Result<()> {
+        match &mut self.started {
+            Some((_, backend)) => backend.set_raw_joysticks(enabled),
+            None => {
+                self.raw_joysticks = Some(enabled);
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(feature = "steam")]
+    fn set_steam_action_set(&mut self, name: &str) -> Result<()> {
+        match &mut self.started {
+            Some((_, backend)) => backend.set_steam_action_set(name),
+            None => {
+                self.steam_action_set = Some(name.to_owned());
+                Ok(())
+            }
+        }
+    }
+
+    fn state(&self) -> BackendState {
+        match (&self.started, &self.error) {
+            (Some(_), _) => BackendState::Ready,
+            (None, Some(error)) => BackendState::Failed(error.clone()),
+            (None, None) => BackendState::Starting,
+        }
+    }
+
+    fn kinds(&self) -> Option<&[BackendKind]> {
+        self.started.as_ref().map(|(kinds, _)| &kinds[..])
+    }
+
+    fn backend_of(&self, id: GamepadId) -> Option<BackendKind> {
+        let (kinds, backend) = self.started.as_ref()?;
+        backend.backend_of(id).or(kinds.first().copied())
+    }
+
+    #[cfg(feature = "sdl2")]
+    fn feed_event(&mut self, event: &::sdl2::event::Event) {
+        if let Some((_, backend)) = &mut self.started {
+            backend.feed_event(event);
+        }
+    }
+
+    #[cfg(feature = "sdl2")]
+    fn other_events(&mut self) -> Option<&mut Vec<::sdl2::event::Event>> {
+        self.started.as_mut()?.1.other_events()
+    }
+}
+
+#[cfg(all(test, feature = "evdev", target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_agnostic_backends_are_tried_on_a_worker() {
+        let retry = Duration::from_secs(100);
+        let kinds = vec![BackendKind::Evdev];
+        let mut lazy = ImplementationContext::new(kinds, false, InitOptions::default(), retry);
+        let mut gamepads = HashMap::new();
+
+        lazy.update(&mut gamepads);
+        assert_eq!(lazy.state(), BackendState::Starting);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while lazy.state() == BackendState::Starting && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+            lazy.update(&mut gamepads);
+        }
+        assert_ne!(lazy.state(), BackendState::Starting);
+    }
+}
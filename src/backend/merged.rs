@@ -0,0 +1,221 @@
+use super::{Backend, BackendKind, UpdateReport};
+use crate::{Gamepad, GamepadId, Guid};
+use std::collections::HashMap;
+
+use crate::Result;
+
+/// Identifies a device model across backends, from the bus, vendor and product of its GUID.
+///
+/// The CRC of the name and the version are left out, since backends fill them in differently.
+/// Returns `None` for GUIDs without a vendor and product, which cannot be told apart.
+fn device_key(guid: Guid) -> Option<[u8; 6]> {
+    let guid = guid.0;
+    let key = [guid[0], guid[1], guid[4], guid[5], guid[8], guid[9]];
+    (key[2..] != [0; 4]).then_some(key)
+}
+
+struct Child {
+    kind: BackendKind,
+    backend: Box<dyn Backend>,
+    // Maps the Ids of the backend to the Ids of the merged backend.
+    ids: HashMap<GamepadId, GamepadId>,
+    // The gamepads of the backend during its update, by its own Ids.
+    gamepads: HashMap<GamepadId, Gamepad>,
+}
+
+/// Runs several backends side by side, as if they were one.
+///
+/// Gamepads get Ids of their own, since the Ids of different backends overlap. A device that
+/// several backends report is only reported by the one that reported it first, preferring
+/// earlier backends within an update. The others are hidden until it disconnects.
+pub struct ImplementationContext {
+    children: Vec<Child>,
+    next_id: usize,
+    hidden: HashMap<GamepadId, Gamepad>,
+}
+
+impl ImplementationContext {
+    pub fn new(backends: Vec<(BackendKind, Box<dyn Backend>)>) -> Self {
+        Self {
+            children: backends
+                .into_iter()
+                .map(|(kind, backend)| Child {
+                    kind,
+                    backend,
+                    ids: HashMap::new(),
+                    gamepads: HashMap::new(),
+                })
+                .collect(),
+            next_id: 0,
+            hidden: HashMap::new(),
+        }
+    }
+
+    fn update_child(
+        &mut self,
+        index: usize,
+        gamepads: &mut HashMap<GamepadId, Gamepad>,
+    ) -> UpdateReport {
+        let child = &mut self.children[index];
+        let mut hidden = Vec::new();
+        for (&child_id, &id) in &child.ids {
+            if let Some(gamepad) = gamepads.remove(&id) {
+                child.gamepads.insert(child_id, gamepad);
+            } else if let Some(gamepad) = self.hidden.remove(&id) {
+                child.gamepads.insert(child_id, gamepad);
+                hidden.push(id);
+            }
+        }
+
+        let mut report = child.backend.update(&mut child.gamepads);
+        for (id, error) in &mut report.device_errors {
+            if let Some(&merged_id) = child.ids.get(id) {
+                *id = merged_id;
+            }
+            *error = format!("{}: {}", child.kind, error);
+        }
+        for error in &mut report.backend_errors {
+            *error = format!("{}: {}", child.kind, error);
+        }
+
+        // Gamepads that the backend removed have disconnected.
+        let child_gamepads = &child.gamepads;
+        child
+            .ids
+            .retain(|child_id, _| child_gamepads.contains_key(child_id));
+
+        let mut gamepads_of_child = std::mem::take(&mut child.gamepads);
+        for (child_id, gamepad) in gamepads_of_child.drain() {
+            match self.children[index].ids.get(&child_id) {
+                Some(&id) if hidden.contains(&id) => {
+                    self.hidden.insert(id, gamepad);
+                }
+                Some(&id) => {
+                    gamepads.insert(id, gamepad);
+                }
+                None => {
+                    let id = GamepadId(self.next_id);
+                    self.next_id += 1;
+                    self.children[index].ids.insert(child_id, id);
+                    if self.is_reported_elsewhere(index, gamepad.guid(), gamepads) {
+                        self.hidden.insert(id, gamepad);
+                    } else {
+                        gamepads.insert(id, gamepad);
+                    }
+                }
+            }
+        }
+        self.children[index].gamepads = gamepads_of_child;
+
+        report
+    }
+
+    /// Checks if another backend reports a device of the same model.
+    fn is_reported_elsewhere(
+        &self,
+        index: usize,
+        guid: Guid,
+        gamepads: &HashMap<GamepadId, Gamepad>,
+    ) -> bool {
+        let Some(key) = device_key(guid) else {
+            return false;
+        };
+
+        self.children
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .flat_map(|(_, child)| child.ids.values())
+            .filter_map(|id| gamepads.get(id))
+            .any(|gamepad| device_key(gamepad.guid()) == Some(key))
+    }
+
+    /// Reports hidden gamepads once no other backend reports their device anymore.
+    fn reveal_hidden(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        for index in 0..self.children.len() {
+            let hidden: Vec<_> = self.children[index]
+                .ids
+                .values()
+                .copied()
+                .filter(|id| self.hidden.contains_key(id))
+                .collect();
+            for id in hidden {
+                let guid = self.hidden[&id].guid();
+                if !self.is_reported_elsewhere(index, guid, gamepads) {
+                    if let Some(gamepad) = self.hidden.remove(&id) {
+                        gamepads.insert(id, gamepad);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        for index in 0..self.children.len() {
+            // Every backend is updated even if one fails, so that none of them falls behind.
+            let child_report = self.update_child(index, gamepads);
+            report.events += child_report.events;
+            report.device_errors.extend(child_report.device_errors);
+            report.backend_errors.extend(child_report.backend_errors);
+        }
+        self.reveal_hidden(gamepads);
+
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        for child in &mut self.children {
+            child.backend.ignore_devices();
+        }
+    }
+
+    fn set_raw_joysticks(&mut self, enabled: bool) -> Result<()> {
+        let results: Vec<_> = self
+            .children
+            .iter_mut()
+            .map(|child| child.backend.set_raw_joysticks(enabled))
+            .collect();
+        if results.iter().any(Result::is_ok) {
+            Ok(())
+        } else {
+            Err("Raw joysticks are not supported by any of the backends".into())
+        }
+    }
+
+    #[cfg(feature = "steam")]
+    fn set_steam_action_set(&mut self, name: &str) -> Result<()> {
+        let results: Vec<_> = self
+            .children
+            .iter_mut()
+            .map(|child| child.backend.set_steam_action_set(name))
+            .collect();
+        results
+            .into_iter()
+            .find(Result::is_ok)
+            .unwrap_or_else(|| Err("Action sets are only supported by Steam Input".into()))
+    }
+
+    fn backend_of(&self, id: GamepadId) -> Option<BackendKind> {
+        self.children
+            .iter()
+            .find(|child| child.ids.values().any(|&merged_id| merged_id == id))
+            .map(|child| child.kind)
+    }
+
+    #[cfg(feature = "sdl2")]
+    fn feed_event(&mut self, event: &::sdl2::event::Event) {
+        for child in &mut self.children {
+            child.backend.feed_event(event);
+        }
+    }
+
+    #[cfg(feature = "sdl2")]
+    fn other_events(&mut self) -> Option<&mut Vec<::sdl2::event::Event>> {
+        self.children
+            .iter_mut()
+            .find_map(|child| child.backend.other_events())
+    }
+}
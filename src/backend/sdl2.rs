@@ -1,103 +1,846 @@
-pub use sdl2::controller::{Axis, Button};
-
+use super::{Exclusive, GamepadDevice, InitOptions, UpdateReport};
 use crate::analog::AnalogInputValue;
-use crate::{Gamepad, GamepadId};
+use crate::capabilities::Capabilities;
+use crate::glyph::ControllerType;
+use crate::raw::{HatDirection, RawHat};
+use crate::sensor::Sensor;
+use crate::touchpad::{Finger, FingerId};
+use crate::trigger::TriggerEffect;
+use crate::{Axis, BackendKind, Button, Gamepad, GamepadId, Guid};
+use sdl2::controller::GameController;
+use sdl2::joystick::{HatState, Joystick};
+use sdl2::sys;
+use std::any::Any;
 use std::collections::HashMap;
+use std::ffi::{c_int, c_void};
+use std::sync::atomic::AtomicBool;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 use crate::Result;
 
-pub type ImplementationId = u32;
+fn axis(axis: sdl2::controller::Axis) -> Axis {
+    use sdl2::controller::Axis as SdlAxis;
+    match axis {
+        SdlAxis::LeftX => Axis::LeftX,
+        SdlAxis::LeftY => Axis::LeftY,
+        SdlAxis::RightX => Axis::RightX,
+        SdlAxis::RightY => Axis::RightY,
+        SdlAxis::TriggerLeft => Axis::TriggerLeft,
+        SdlAxis::TriggerRight => Axis::TriggerRight,
+    }
+}
+
+fn button(button: sdl2::controller::Button) -> Button {
+    use sdl2::controller::Button as SdlButton;
+    match button {
+        SdlButton::A => Button::A,
+        SdlButton::B => Button::B,
+        SdlButton::X => Button::X,
+        SdlButton::Y => Button::Y,
+        SdlButton::Back => Button::Back,
+        SdlButton::Guide => Button::Guide,
+        SdlButton::Start => Button::Start,
+        SdlButton::LeftStick => Button::LeftStick,
+        SdlButton::RightStick => Button::RightStick,
+        SdlButton::LeftShoulder => Button::LeftShoulder,
+        SdlButton::RightShoulder => Button::RightShoulder,
+        SdlButton::DPadUp => Button::DPadUp,
+        SdlButton::DPadDown => Button::DPadDown,
+        SdlButton::DPadLeft => Button::DPadLeft,
+        SdlButton::DPadRight => Button::DPadRight,
+        SdlButton::Misc1 => Button::Misc1,
+        SdlButton::Paddle1 => Button::Paddle1,
+        SdlButton::Paddle2 => Button::Paddle2,
+        SdlButton::Paddle3 => Button::Paddle3,
+        SdlButton::Paddle4 => Button::Paddle4,
+        SdlButton::Touchpad => Button::Touchpad,
+    }
+}
+
+/// Every button of the sdl2 crate, to ask controllers which ones they have.
+const SDL_BUTTONS: [sdl2::controller::Button; 21] = {
+    use sdl2::controller::Button as SdlButton;
+    [
+        SdlButton::A,
+        SdlButton::B,
+        SdlButton::X,
+        SdlButton::Y,
+        SdlButton::Back,
+        SdlButton::Guide,
+        SdlButton::Start,
+        SdlButton::LeftStick,
+        SdlButton::RightStick,
+        SdlButton::LeftShoulder,
+        SdlButton::RightShoulder,
+        SdlButton::DPadUp,
+        SdlButton::DPadDown,
+        SdlButton::DPadLeft,
+        SdlButton::DPadRight,
+        SdlButton::Misc1,
+        SdlButton::Paddle1,
+        SdlButton::Paddle2,
+        SdlButton::Paddle3,
+        SdlButton::Paddle4,
+        SdlButton::Touchpad,
+    ]
+};
+
+/// Every axis of the sdl2 crate, like [`SDL_BUTTONS`].
+const SDL_AXES: [sdl2::controller::Axis; 6] = {
+    use sdl2::controller::Axis as SdlAxis;
+    [
+        SdlAxis::LeftX,
+        SdlAxis::LeftY,
+        SdlAxis::RightX,
+        SdlAxis::RightY,
+        SdlAxis::TriggerLeft,
+        SdlAxis::TriggerRight,
+    ]
+};
+
+/// Reads a serial number returned by SDL2, which is null or empty when the device has none.
+///
+/// # Safety
+///
+/// `serial` has to be null or point to a nul-terminated string.
+unsafe fn serial(serial: *const std::os::raw::c_char) -> Option<String> {
+    if serial.is_null() {
+        return None;
+    }
+    let serial = std::ffi::CStr::from_ptr(serial).to_string_lossy();
+    (!serial.is_empty()).then(|| serial.into_owned())
+}
+
+/// Device features of an open controller.
+///
+/// The context owns the controller and closes it when the gamepad is removed.
+/// The sdl2 crate does not expose the raw controller, and only wraps part of the controller API,
+/// so the raw controller is looked up through its instance id instead.
+struct Device(u32);
+
+impl Device {
+    fn guid(&self) -> Guid {
+        // SAFETY: SDL checks for null controllers and joysticks, returning a zeroed GUID.
+        let guid = unsafe {
+            let joystick = sys::SDL_GameControllerGetJoystick(self.raw());
+            sys::SDL_JoystickGetGUID(joystick)
+        };
+        Guid(guid.data)
+    }
+
+    fn serial(&self) -> Option<String> {
+        // SAFETY: SDL checks for null controllers, returning null.
+        unsafe { serial(sys::SDL_GameControllerGetSerial(self.raw())) }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        let controller = self.raw();
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let (buttons, axes, touchpads, led, rumble) = unsafe {
+            let buttons: Vec<_> = SDL_BUTTONS
+                .into_iter()
+                .filter(|sdl_button| {
+                    sys::SDL_GameControllerHasButton(controller, sdl_button.to_ll())
+                        == sys::SDL_bool::SDL_TRUE
+                })
+                .map(button)
+                .collect();
+            let axes: Vec<_> = SDL_AXES
+                .into_iter()
+                .filter(|sdl_axis| {
+                    sys::SDL_GameControllerHasAxis(controller, sdl_axis.to_ll())
+                        == sys::SDL_bool::SDL_TRUE
+                })
+                .map(axis)
+                .collect();
+            (
+                buttons,
+                axes,
+                sys::SDL_GameControllerGetNumTouchpads(controller),
+                sys::SDL_GameControllerHasLED(controller) == sys::SDL_bool::SDL_TRUE,
+                // Stopping the rumble fails on controllers without motors.
+                sys::SDL_GameControllerRumble(controller, 0, 0, 0) == 0,
+            )
+        };
 
-impl From<GamepadId> for usize {
-    fn from(id: GamepadId) -> Self {
-        id.0 as usize
+        let mut capabilities = Capabilities::with_inputs(buttons, axes);
+        capabilities.rumble = rumble;
+        capabilities.touchpad = touchpads > 0;
+        capabilities.gyroscope = self.has_sensor(Sensor::Gyroscope);
+        capabilities.accelerometer = self.has_sensor(Sensor::Accelerometer);
+        capabilities.led = led;
+        capabilities.adaptive_triggers = self.has_adaptive_triggers();
+        capabilities
+    }
+
+    fn raw(&self) -> *mut sys::SDL_GameController {
+        // SAFETY: looking up a controller by id has no preconditions.
+        unsafe { sys::SDL_GameControllerFromInstanceID(self.0 as i32) }
     }
 }
 
-pub struct OwnedImplementationGamepad(sdl2::controller::GameController);
+impl GamepadDevice for Device {
+    fn has_sensor(&self, sensor: Sensor) -> bool {
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let result = unsafe { sys::SDL_GameControllerHasSensor(self.raw(), sensor_to_ll(sensor)) };
+        result == sys::SDL_bool::SDL_TRUE
+    }
+
+    fn set_sensor_enabled(&self, sensor: Sensor, enabled: bool) -> Result<()> {
+        let enabled = if enabled {
+            sys::SDL_bool::SDL_TRUE
+        } else {
+            sys::SDL_bool::SDL_FALSE
+        };
+
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let result = unsafe {
+            sys::SDL_GameControllerSetSensorEnabled(self.raw(), sensor_to_ll(sensor), enabled)
+        };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(sdl2::get_error())
+        }
+    }
+
+    fn set_led(&self, red: u8, green: u8, blue: u8) -> Result<()> {
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let result = unsafe { sys::SDL_GameControllerSetLED(self.raw(), red, green, blue) };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(sdl2::get_error())
+        }
+    }
+
+    fn set_player_index(&self, index: Option<u8>) -> Result<()> {
+        let index = index.map_or(-1, i32::from);
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        unsafe { sys::SDL_GameControllerSetPlayerIndex(self.raw(), index) };
+
+        Ok(())
+    }
+
+    fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()> {
+        let low_frequency = (low_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        let high_frequency = (high_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+
+        // Rumble lasts until it is changed again, like with XInput.
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let result = unsafe {
+            sys::SDL_GameControllerRumble(self.raw(), low_frequency, high_frequency, u32::MAX)
+        };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(sdl2::get_error())
+        }
+    }
+
+    fn controller_type(&self) -> Option<ControllerType> {
+        use sys::SDL_GameControllerType::*;
+
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        match unsafe { sys::SDL_GameControllerGetType(self.raw()) } {
+            SDL_CONTROLLER_TYPE_XBOX360 | SDL_CONTROLLER_TYPE_XBOXONE => Some(ControllerType::Xbox),
+            SDL_CONTROLLER_TYPE_PS3 | SDL_CONTROLLER_TYPE_PS4 | SDL_CONTROLLER_TYPE_PS5 => {
+                Some(ControllerType::PlayStation)
+            }
+            SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_PRO => Some(ControllerType::SwitchPro),
+            SDL_CONTROLLER_TYPE_UNKNOWN | SDL_CONTROLLER_TYPE_VIRTUAL => None,
+        }
+    }
+
+    fn has_adaptive_triggers(&self) -> bool {
+        // SAFETY: SDL checks for null controllers, which the lookup returns once closed.
+        let kind = unsafe { sys::SDL_GameControllerGetType(self.raw()) };
+        kind == sys::SDL_GameControllerType::SDL_CONTROLLER_TYPE_PS5
+    }
+
+    fn set_trigger_effect(&self, trigger: Axis, effect: TriggerEffect) -> Result<()> {
+        if !self.has_adaptive_triggers() {
+            return Ok(());
+        }
+
+        // The effect packet is the start of the DualSense output report, after its report id.
+        // The first byte selects which parts of the report apply.
+        let mut packet = [0u8; 47];
+        let (flag, offset) = match trigger {
+            Axis::TriggerRight => (0x04, 10),
+            _ => (0x08, 21),
+        };
+        packet[0] = flag;
+        packet[offset..offset + 11].copy_from_slice(&effect.to_dualsense());
+
+        let send_effect =
+            send_effect().ok_or("Trigger effects need SDL 2.0.16 or newer through SDL2")?;
+        // SAFETY: the packet is a live local of the given size.
+        let result =
+            unsafe { send_effect(self.raw(), packet.as_ptr().cast(), packet.len() as c_int) };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(sdl2::get_error())
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+type SendEffect = unsafe extern "C" fn(*mut sys::SDL_GameController, *const c_void, c_int) -> c_int;
+
+/// Looks up `SDL_GameControllerSendEffect`, which was added in SDL 2.0.16, after the version
+/// that the sdl2 crate binds. It is not linked, so that older versions of SDL2 still load.
+fn send_effect() -> Option<SendEffect> {
+    static SEND_EFFECT: OnceLock<Option<SendEffect>> = OnceLock::new();
+    *SEND_EFFECT.get_or_init(|| {
+        #[cfg(unix)]
+        let library = libloading::os::unix::Library::this();
+        #[cfg(windows)]
+        let library = libloading::os::windows::Library::open_already_loaded("SDL2.dll").ok()?;
+
+        // SAFETY: the function has this signature in every version of SDL2 that has it.
+        let symbol = unsafe { library.get::<SendEffect>(b"SDL_GameControllerSendEffect\0") };
+        symbol.ok().map(|symbol| *symbol)
+    })
+}
+
+impl Gamepad {
+    /// Gets the SDL2 game controller of the gamepad, for features that fishsticks does not wrap.
+    ///
+    /// The controller is owned by the context, and stays valid while the gamepad is connected.
+    /// Returns `None` for raw joysticks and gamepads of other backends.
+    pub fn sdl_controller(&self) -> Option<*mut sys::SDL_GameController> {
+        let controller = self.device::<Device>()?.raw();
+        (!controller.is_null()).then_some(controller)
+    }
+}
 
 pub struct ImplementationContext {
-    sdl_context: sdl2::Sdl,
     controller_subsystem: sdl2::GameControllerSubsystem,
+    joystick_subsystem: sdl2::JoystickSubsystem,
+    // SDL only allows a single event pump to exist at a time, so it is created once and reused.
+    // When integrating with an existing SDL context, the host owns the pump and forwards events.
+    event_pump: Option<sdl2::EventPump>,
+    fed_events: Vec<sdl2::event::Event>,
+    // Non-controller events from our own event pump, kept until the next update for the application.
+    other_events: Vec<sdl2::event::Event>,
+    controllers: HashMap<GamepadId, GameController>,
+    // Joysticks without a controller mapping, opened as raw gamepads.
+    joysticks: HashMap<GamepadId, Joystick>,
+    raw_joysticks: bool,
+    // Set when raw joysticks are toggled, so that they are opened or closed on the next update.
+    rescan_joysticks: bool,
+    ignore_devices: bool,
+    logging: bool,
+    // SDL2 only runs once per process.
+    _exclusive: Exclusive,
 }
 
+// Set while a context runs SDL2.
+static SDL_ALIVE: AtomicBool = AtomicBool::new(false);
+
 impl ImplementationContext {
-    pub fn new() -> Result<Self> {
+    pub fn new(options: &InitOptions) -> Result<Self> {
+        let exclusive = Exclusive::acquire(&SDL_ALIVE, BackendKind::Sdl2)?;
+        use_button_positions();
         let sdl_context = sdl2::init()?;
         let controller_subsystem = sdl_context.game_controller()?;
+        for path in &options.mappings {
+            controller_subsystem.load_mappings(path).map_err(|e| {
+                format!(
+                    "Failed to load controller mappings from {}: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+        }
+        let joystick_subsystem = sdl_context.joystick()?;
+        let event_pump = sdl_context.event_pump()?;
 
         Ok(Self {
-            sdl_context,
             controller_subsystem,
+            joystick_subsystem,
+            event_pump: Some(event_pump),
+            fed_events: Vec::new(),
+            other_events: Vec::new(),
+            controllers: HashMap::new(),
+            joysticks: HashMap::new(),
+            raw_joysticks: false,
+            rescan_joysticks: false,
+            ignore_devices: false,
+            logging: options.logging,
+            _exclusive: exclusive,
         })
     }
-}
 
-impl super::Backend for ImplementationContext {
-    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> Result<()> {
-        let mut event_pump = self.sdl_context.event_pump()?;
+    pub fn from_sdl(sdl_context: &sdl2::Sdl) -> Result<Self> {
+        let exclusive = Exclusive::acquire(&SDL_ALIVE, BackendKind::Sdl2)?;
+        use_button_positions();
+        let controller_subsystem = sdl_context.game_controller()?;
+        let joystick_subsystem = sdl_context.joystick()?;
 
-        for (_, gamepad) in gamepads.iter_mut() {
-            gamepad.update_inputs();
-        }
+        Ok(Self {
+            controller_subsystem,
+            joystick_subsystem,
+            event_pump: None,
+            fed_events: Vec::new(),
+            other_events: Vec::new(),
+            controllers: HashMap::new(),
+            joysticks: HashMap::new(),
+            raw_joysticks: false,
+            rescan_joysticks: false,
+            ignore_devices: false,
+            logging: true,
+            _exclusive: exclusive,
+        })
+    }
 
-        for event in event_pump.poll_iter() {
-            use sdl2::event::Event;
-            match event {
-                Event::ControllerDeviceAdded { which, .. } => {
-                    let gamepad = self.controller_subsystem.open(which);
-                    if let Ok(gamepad) = gamepad {
-                        #[cfg(debug_assertions)]
-                        let name = gamepad.name();
+    fn handle_event(
+        &mut self,
+        gamepads: &mut HashMap<GamepadId, Gamepad>,
+        event: sdl2::event::Event,
+    ) {
+        use sdl2::event::Event;
+        match event {
+            Event::ControllerDeviceAdded { .. } if self.ignore_devices => (),
+            Event::ControllerDeviceAdded { which, .. } => {
+                let controller = self.controller_subsystem.open(which);
+                if let Ok(controller) = controller {
+                    #[cfg(debug_assertions)]
+                    log!(self.logging, "Added gamepad \"{}\"", controller.name());
 
-                        gamepads.insert(
-                            GamepadId(gamepad.instance_id()),
-                            Gamepad::new(Some(OwnedImplementationGamepad(gamepad))),
-                        );
+                    let device = Device(controller.instance_id());
+                    let id = GamepadId(controller.instance_id() as usize);
+                    let guid = device.guid();
+                    let serial = device.serial();
+                    let capabilities = device.capabilities();
+                    let mut gamepad = Gamepad::new(Some(Box::new(device)), guid);
+                    gamepad.name = Some(controller.name());
+                    gamepad.serial = serial;
+                    gamepad.capabilities = capabilities;
+                    self.controllers.insert(id, controller);
+                    gamepads.insert(id, gamepad);
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                // Controllers that were never opened, such as when devices are ignored, are skipped.
+                let id = GamepadId(which as usize);
+                #[cfg(debug_assertions)]
+                if let Some(controller) = self.controllers.get(&id) {
+                    log!(self.logging, "Removed gamepad \"{}\"", controller.name());
+                }
 
-                        #[cfg(debug_assertions)]
-                        println!("Added gamepad \"{}\"", name);
-                    }
+                self.controllers.remove(&id);
+                gamepads.remove(&id);
+            }
+            Event::ControllerAxisMotion {
+                timestamp,
+                which,
+                axis,
+                value,
+            } => {
+                if let Some(gamepad) = gamepads.get_mut(&GamepadId(which as usize)) {
+                    set_axis(gamepad, axis, value, event_time(timestamp));
+                }
+            }
+            Event::ControllerButtonDown {
+                timestamp,
+                which,
+                button,
+            } => {
+                if let Some(gamepad) = gamepads.get_mut(&GamepadId(which as usize)) {
+                    set_button(gamepad, button, true, event_time(timestamp));
+                }
+            }
+            Event::ControllerButtonUp {
+                timestamp,
+                which,
+                button,
+            } => {
+                if let Some(gamepad) = gamepads.get_mut(&GamepadId(which as usize)) {
+                    set_button(gamepad, button, false, event_time(timestamp));
                 }
-                Event::ControllerDeviceRemoved { which, .. } => {
+            }
+            Event::JoyDeviceAdded { which, .. }
+                if self.raw_joysticks
+                    && !self.ignore_devices
+                    && !self.controller_subsystem.is_game_controller(which) =>
+            {
+                self.open_joystick(gamepads, which);
+            }
+            Event::JoyDeviceRemoved { which, .. } => {
+                let id = GamepadId(which as usize);
+                if let Some(joystick) = self.joysticks.remove(&id) {
                     #[cfg(debug_assertions)]
-                    let name = gamepads
-                        .get(&GamepadId(which))
-                        .unwrap()
-                        .owned_internal_gamepad
-                        .as_ref()
-                        .unwrap()
-                        .0
-                        .name();
-
-                    gamepads.remove(&GamepadId(which));
+                    log!(self.logging, "Removed joystick \"{}\"", joystick.name());
 
-                    #[cfg(debug_assertions)]
-                    println!("Removed gamepad \"{}\"", name);
+                    drop(joystick);
+                    gamepads.remove(&id);
                 }
-                Event::ControllerAxisMotion {
-                    which, axis, value, ..
-                } => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(which)) {
-                        gamepad
-                            .analog_inputs
-                            .set(axis, AnalogInputValue::from(value));
-                    }
+            }
+            // Controllers report joystick events as well, so only raw joysticks are handled.
+            Event::JoyAxisMotion {
+                timestamp,
+                which,
+                axis_idx,
+                value,
+            } => {
+                if let Some(gamepad) = self.joystick_gamepad(gamepads, which) {
+                    gamepad.raw_inputs.axes.set_at(
+                        axis_idx,
+                        AnalogInputValue::from(value),
+                        event_time(timestamp),
+                    );
                 }
-                Event::ControllerButtonDown { which, button, .. } => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(which)) {
-                        gamepad.digital_inputs.activate(button);
-                    }
+            }
+            Event::JoyButtonDown {
+                timestamp,
+                which,
+                button_idx,
+            } => {
+                if let Some(gamepad) = self.joystick_gamepad(gamepads, which) {
+                    let time = event_time(timestamp);
+                    gamepad.raw_inputs.buttons.activate_at(button_idx, time);
                 }
-                Event::ControllerButtonUp { which, button, .. } => {
-                    if let Some(gamepad) = gamepads.get_mut(&GamepadId(which)) {
-                        gamepad.digital_inputs.deactivate(button);
+            }
+            Event::JoyButtonUp {
+                timestamp,
+                which,
+                button_idx,
+            } => {
+                if let Some(gamepad) = self.joystick_gamepad(gamepads, which) {
+                    let time = event_time(timestamp);
+                    gamepad.raw_inputs.buttons.deactivate_at(button_idx, time);
+                }
+            }
+            Event::JoyHatMotion {
+                timestamp,
+                which,
+                hat_idx,
+                state,
+            } => {
+                if let Some(gamepad) = self.joystick_gamepad(gamepads, which) {
+                    let time = event_time(timestamp);
+                    for (direction, active) in hat_directions(state) {
+                        let hat = RawHat {
+                            hat: hat_idx,
+                            direction,
+                        };
+                        if active {
+                            gamepad.raw_inputs.hats.activate_at(hat, time);
+                        } else {
+                            gamepad.raw_inputs.hats.deactivate_at(hat, time);
+                        }
                     }
                 }
-                _ => (),
+            }
+            _ => (),
+        }
+    }
+
+    fn joystick_gamepad<'a>(
+        &self,
+        gamepads: &'a mut HashMap<GamepadId, Gamepad>,
+        which: u32,
+    ) -> Option<&'a mut Gamepad> {
+        let id = GamepadId(which as usize);
+        if self.joysticks.contains_key(&id) {
+            gamepads.get_mut(&id)
+        } else {
+            None
+        }
+    }
+
+    fn open_joystick(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>, index: u32) {
+        let joystick = match self.joystick_subsystem.open(index) {
+            Ok(joystick) => joystick,
+            Err(_) => return,
+        };
+        let id = GamepadId(joystick.instance_id() as usize);
+        if self.joysticks.contains_key(&id) {
+            return;
+        }
+
+        #[cfg(debug_assertions)]
+        log!(self.logging, "Added joystick \"{}\"", joystick.name());
+
+        let mut gamepad = Gamepad::new(None, Guid(joystick.guid().raw().data));
+        gamepad.name = Some(joystick.name());
+        // SAFETY: SDL checks for null joysticks, returning null.
+        gamepad.serial = unsafe {
+            serial(sys::SDL_JoystickGetSerial(sys::SDL_JoystickFromInstanceID(
+                joystick.instance_id() as i32,
+            )))
+        };
+        gamepad.raw = true;
+        gamepad.capabilities = Capabilities::none();
+        self.joysticks.insert(id, joystick);
+        gamepads.insert(id, gamepad);
+    }
+
+    fn rescan_joysticks(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) {
+        if self.raw_joysticks && !self.ignore_devices {
+            let count = self.joystick_subsystem.num_joysticks().unwrap_or(0);
+            for index in 0..count {
+                if !self.controller_subsystem.is_game_controller(index) {
+                    self.open_joystick(gamepads, index);
+                }
+            }
+        } else {
+            for (id, _) in self.joysticks.drain() {
+                gamepads.remove(&id);
+            }
+        }
+    }
+}
+
+/// Applies a change of a controller axis to a gamepad.
+fn set_axis(gamepad: &mut Gamepad, axis: sdl2::controller::Axis, value: i16, time: Instant) {
+    gamepad
+        .analog_inputs
+        .set_at(self::axis(axis), AnalogInputValue::from(value), time);
+}
+
+/// Applies a press or release of a controller button to a gamepad.
+fn set_button(
+    gamepad: &mut Gamepad,
+    button: sdl2::controller::Button,
+    pressed: bool,
+    time: Instant,
+) {
+    let button = self::button(button);
+    if pressed {
+        gamepad.digital_inputs.activate_at(button, time);
+    } else {
+        gamepad.digital_inputs.deactivate_at(button, time);
+    }
+}
+
+/// Converts the timestamp of an SDL event, in milliseconds since SDL was initialized, to an
+/// instant.
+fn event_time(timestamp: u32) -> Instant {
+    let now = Instant::now();
+    // SAFETY: SDL is initialized while events are handled.
+    let age = unsafe { sys::SDL_GetTicks() }.wrapping_sub(timestamp);
+    now.checked_sub(Duration::from_millis(age.into()))
+        .unwrap_or(now)
+}
+
+/// Splits a hat state into whether each of its directions is held.
+fn hat_directions(state: HatState) -> [(HatDirection, bool); 4] {
+    let raw = state.to_raw();
+    [
+        (HatDirection::Up, raw & HatState::Up.to_raw() != 0),
+        (HatDirection::Right, raw & HatState::Right.to_raw() != 0),
+        (HatDirection::Down, raw & HatState::Down.to_raw() != 0),
+        (HatDirection::Left, raw & HatState::Left.to_raw() != 0),
+    ]
+}
+
+impl super::Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        let mut events = std::mem::take(&mut self.fed_events);
+        self.other_events.clear();
+        if let Some(event_pump) = &mut self.event_pump {
+            for event in event_pump.poll_iter() {
+                if is_input(&event) {
+                    events.push(event);
+                } else {
+                    self.other_events.push(event);
+                }
+            }
+        }
+
+        if std::mem::take(&mut self.rescan_joysticks) {
+            self.rescan_joysticks(gamepads);
+        }
+
+        report.events += events.len();
+        for event in events.drain(..) {
+            self.handle_event(gamepads, event);
+        }
+        // Hand the buffer back so its allocation is reused.
+        self.fed_events = events;
+
+        // The sdl2 crate does not expose touchpad and sensor events, so they are polled instead.
+        for (id, controller) in &self.controllers {
+            let gamepad = match gamepads.get_mut(id) {
+                Some(gamepad) => gamepad,
+                None => continue,
+            };
+
+            let controller = Device(controller.instance_id()).raw();
+            if !controller.is_null() {
+                poll_touchpads(controller, gamepad);
+                poll_sensors(controller, gamepad);
             }
         }
 
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+    }
+
+    fn set_raw_joysticks(&mut self, enabled: bool) -> Result<()> {
+        self.raw_joysticks = enabled;
+        self.rescan_joysticks = true;
         Ok(())
     }
+
+    fn feed_event(&mut self, event: &sdl2::event::Event) {
+        if is_input(event) {
+            self.fed_events.push(event.clone());
+        }
+    }
+
+    fn other_events(&mut self) -> Option<&mut Vec<sdl2::event::Event>> {
+        Some(&mut self.other_events)
+    }
+}
+
+/// Checks if an event is handled by the backend rather than passed through to the application.
+fn is_input(event: &sdl2::event::Event) -> bool {
+    event.is_controller() || event.is_joy()
+}
+
+/// Makes SDL2 report Nintendo face buttons by position, like every other backend,
+/// rather than by label.
+fn use_button_positions() {
+    sdl2::hint::set("SDL_GAMECONTROLLER_USE_BUTTON_LABELS", "0");
+}
+
+fn sensor_to_ll(sensor: Sensor) -> sys::SDL_SensorType {
+    match sensor {
+        Sensor::Gyroscope => sys::SDL_SensorType::SDL_SENSOR_GYRO,
+        Sensor::Accelerometer => sys::SDL_SensorType::SDL_SENSOR_ACCEL,
+    }
+}
+
+fn poll_sensors(controller: *mut sys::SDL_GameController, gamepad: &mut Gamepad) {
+    for sensor in [Sensor::Gyroscope, Sensor::Accelerometer] {
+        let sensor_type = sensor_to_ll(sensor);
+        let mut data = [0.0; 3];
+
+        // SAFETY: `controller` is a valid, open controller and `data` holds 3 values.
+        let reading = unsafe {
+            sys::SDL_GameControllerIsSensorEnabled(controller, sensor_type)
+                == sys::SDL_bool::SDL_TRUE
+                && sys::SDL_GameControllerGetSensorData(
+                    controller,
+                    sensor_type,
+                    data.as_mut_ptr(),
+                    data.len() as i32,
+                ) == 0
+        };
+
+        gamepad.sensors.set(sensor, reading.then_some(data));
+    }
+}
+
+fn poll_touchpads(controller: *mut sys::SDL_GameController, gamepad: &mut Gamepad) {
+    // SAFETY: `controller` is a valid, open controller.
+    let num_touchpads = unsafe { sys::SDL_GameControllerGetNumTouchpads(controller) };
+    for touchpad in 0..num_touchpads.max(0) {
+        // SAFETY: `controller` is a valid, open controller and `touchpad` is in range.
+        let num_fingers =
+            unsafe { sys::SDL_GameControllerGetNumTouchpadFingers(controller, touchpad) };
+
+        for finger in 0..num_fingers.max(0) {
+            let mut state = 0;
+            let mut x = 0.0;
+            let mut y = 0.0;
+            let mut pressure = 0.0;
+
+            // SAFETY: `controller` is a valid, open controller, `touchpad` and `finger` are in
+            // range, and all output pointers point to live locals.
+            let result = unsafe {
+                sys::SDL_GameControllerGetTouchpadFinger(
+                    controller,
+                    touchpad,
+                    finger,
+                    &mut state,
+                    &mut x,
+                    &mut y,
+                    &mut pressure,
+                )
+            };
+
+            let finger_id = FingerId {
+                touchpad: touchpad as usize,
+                finger: finger as usize,
+            };
+            let finger = (result == 0 && state != 0).then_some(Finger { x, y, pressure });
+            gamepad.touchpad_inputs.set(finger_id, finger);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stick;
+    use sdl2::controller::Axis as SdlAxis;
+
+    struct Driver;
+
+    /// Converts a value to the integer range of SDL2 axes.
+    fn to_i16(value: f32) -> i16 {
+        (value * i16::MAX as f32).round() as i16
+    }
+
+    impl crate::backend::conformance::Driver for Driver {
+        fn set_button(gamepad: &mut Gamepad, button: Button, pressed: bool) -> bool {
+            let Some(sdl_button) = SDL_BUTTONS
+                .into_iter()
+                .find(|&sdl_button| self::button(sdl_button) == button)
+            else {
+                return false;
+            };
+            set_button(gamepad, sdl_button, pressed, Instant::now());
+            true
+        }
+
+        fn tilt_stick(gamepad: &mut Gamepad, stick: Stick, x: f32, y: f32) {
+            let (x_axis, y_axis) = match stick {
+                Stick::Left => (SdlAxis::LeftX, SdlAxis::LeftY),
+                Stick::Right => (SdlAxis::RightX, SdlAxis::RightY),
+            };
+            // SDL2 reports vertical axes as positive downwards.
+            set_axis(gamepad, x_axis, to_i16(x), Instant::now());
+            set_axis(gamepad, y_axis, to_i16(-y), Instant::now());
+        }
+
+        fn pull_trigger(gamepad: &mut Gamepad, trigger: Axis, pressure: f32) {
+            let axis = match trigger {
+                Axis::TriggerLeft => SdlAxis::TriggerLeft,
+                _ => SdlAxis::TriggerRight,
+            };
+            set_axis(gamepad, axis, to_i16(pressure), Instant::now());
+        }
+    }
+
+    crate::backend::conformance::conformance_tests!(Driver);
+
+    #[test]
+    fn a_second_context_is_refused() {
+        // Stands in for a running context, as SDL2 may not start where the tests run.
+        let _first = Exclusive::acquire(&SDL_ALIVE, BackendKind::Sdl2).unwrap();
+        let second = crate::GamepadContext::builder()
+            .backends(&[BackendKind::Sdl2])
+            .build();
+        let error = second.err().unwrap();
+        assert!(error.starts_with("Another GamepadContext"), "{}", error);
+    }
 }
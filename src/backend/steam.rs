@@ -0,0 +1,456 @@
+use super::{Exclusive, GamepadDevice, UpdateReport};
+use crate::analog::AnalogInputValue;
+use crate::glyph::ControllerType;
+use crate::sensor::Sensor;
+use crate::{Axis, BackendKind, Button, Gamepad, GamepadId, Guid, BUTTONS};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+
+use crate::Result;
+
+type InputHandle = u64;
+type ActionSetHandle = u64;
+type DigitalActionHandle = u64;
+type AnalogActionHandle = u64;
+/// `EInputActionOrigin`.
+type ActionOrigin = i32;
+
+/// `STEAM_INPUT_MAX_COUNT`.
+const MAX_CONTROLLERS: usize = 16;
+/// `STEAM_INPUT_MAX_ORIGINS`.
+const MAX_ORIGINS: usize = 8;
+/// `k_ESteamInputGlyphSize_Medium`, 128 by 128 pixels.
+const GLYPH_SIZE: i32 = 1;
+
+/// Steam Input does not expose vendor and product ids.
+const STEAM_GUID: Guid = Guid(*b"steaminput\0\0\0\0\0\0");
+
+/// The action set that is read until the game selects another one.
+const DEFAULT_ACTION_SET: &str = "fishsticks";
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct DigitalActionData {
+    state: bool,
+    active: bool,
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+struct AnalogActionData {
+    mode: i32,
+    x: f32,
+    y: f32,
+    active: bool,
+}
+
+#[link(name = "steam_api")]
+extern "C" {
+    fn SteamAPI_SteamInput_v006() -> *mut c_void;
+    fn SteamAPI_ISteamInput_Init(input: *mut c_void, explicitly_call_run_frame: bool) -> bool;
+    fn SteamAPI_ISteamInput_RunFrame(input: *mut c_void, reserved: bool);
+    fn SteamAPI_ISteamInput_GetConnectedControllers(
+        input: *mut c_void,
+        handles: *mut InputHandle,
+    ) -> i32;
+    fn SteamAPI_ISteamInput_GetActionSetHandle(
+        input: *mut c_void,
+        name: *const c_char,
+    ) -> ActionSetHandle;
+    fn SteamAPI_ISteamInput_ActivateActionSet(
+        input: *mut c_void,
+        controller: InputHandle,
+        action_set: ActionSetHandle,
+    );
+    fn SteamAPI_ISteamInput_GetDigitalActionHandle(
+        input: *mut c_void,
+        name: *const c_char,
+    ) -> DigitalActionHandle;
+    fn SteamAPI_ISteamInput_GetDigitalActionData(
+        input: *mut c_void,
+        controller: InputHandle,
+        action: DigitalActionHandle,
+    ) -> DigitalActionData;
+    fn SteamAPI_ISteamInput_GetAnalogActionHandle(
+        input: *mut c_void,
+        name: *const c_char,
+    ) -> AnalogActionHandle;
+    fn SteamAPI_ISteamInput_GetAnalogActionData(
+        input: *mut c_void,
+        controller: InputHandle,
+        action: AnalogActionHandle,
+    ) -> AnalogActionData;
+    fn SteamAPI_ISteamInput_TriggerVibration(
+        input: *mut c_void,
+        controller: InputHandle,
+        left_speed: u16,
+        right_speed: u16,
+    );
+    fn SteamAPI_ISteamInput_GetInputTypeForHandle(
+        input: *mut c_void,
+        controller: InputHandle,
+    ) -> i32;
+    fn SteamAPI_ISteamInput_GetDigitalActionOrigins(
+        input: *mut c_void,
+        controller: InputHandle,
+        action_set: ActionSetHandle,
+        action: DigitalActionHandle,
+        origins: *mut ActionOrigin,
+    ) -> i32;
+    fn SteamAPI_ISteamInput_GetAnalogActionOrigins(
+        input: *mut c_void,
+        controller: InputHandle,
+        action_set: ActionSetHandle,
+        action: AnalogActionHandle,
+        origins: *mut ActionOrigin,
+    ) -> i32;
+    fn SteamAPI_ISteamInput_GetGlyphPNGForActionOrigin(
+        input: *mut c_void,
+        origin: ActionOrigin,
+        size: i32,
+        flags: u32,
+    ) -> *const c_char;
+    fn SteamAPI_ISteamInput_SetLEDColor(
+        input: *mut c_void,
+        controller: InputHandle,
+        red: u8,
+        green: u8,
+        blue: u8,
+        flags: u32,
+    );
+}
+
+/// Analog actions, and the axes they report to.
+///
+/// Stick actions report both axes of a stick, the second one flipped,
+/// since Steam Input reports vertical axes as positive upwards, unlike SDL2.
+const ANALOG_ACTIONS: [(&str, Axis, Option<Axis>); 4] = [
+    ("LeftStick", Axis::LeftX, Some(Axis::LeftY)),
+    ("RightStick", Axis::RightX, Some(Axis::RightY)),
+    ("TriggerLeft", Axis::TriggerLeft, None),
+    ("TriggerRight", Axis::TriggerRight, None),
+];
+
+/// Action handles, looked up once Steam has loaded the action manifest.
+struct Handles {
+    action_set: ActionSetHandle,
+    digital: Vec<(Button, DigitalActionHandle)>,
+    analog: Vec<(AnalogActionHandle, Axis, Option<Axis>)>,
+}
+
+impl Handles {
+    fn lookup(input: *mut c_void, action_set: &CStr) -> Option<Self> {
+        let handle_of = |name: &str| CString::new(name).expect("action names contain no nul bytes");
+
+        // SAFETY: `input` is the Steam Input interface and the names are nul-terminated.
+        let action_set =
+            unsafe { SteamAPI_ISteamInput_GetActionSetHandle(input, action_set.as_ptr()) };
+        if action_set == 0 {
+            return None;
+        }
+
+        let digital = BUTTONS
+            .iter()
+            .map(|&button| {
+                let name = handle_of(&format!("{:?}", button));
+                // SAFETY: as above.
+                let handle =
+                    unsafe { SteamAPI_ISteamInput_GetDigitalActionHandle(input, name.as_ptr()) };
+                (button, handle)
+            })
+            .filter(|&(_, handle)| handle != 0)
+            .collect();
+        let analog = ANALOG_ACTIONS
+            .iter()
+            .map(|&(name, x, y)| {
+                let name = handle_of(name);
+                // SAFETY: as above.
+                let handle =
+                    unsafe { SteamAPI_ISteamInput_GetAnalogActionHandle(input, name.as_ptr()) };
+                (handle, x, y)
+            })
+            .filter(|&(handle, _, _)| handle != 0)
+            .collect();
+
+        Some(Self {
+            action_set,
+            digital,
+            analog,
+        })
+    }
+}
+
+/// Device features of a controller, through Steam Input.
+struct Device {
+    input: *mut c_void,
+    controller: InputHandle,
+    // The handles of the context, which change along with the action set.
+    handles: Rc<RefCell<Option<Handles>>>,
+}
+
+impl Device {
+    /// Gets the glyph of the first origin of an action, read by `read_origins` into a buffer of
+    /// `STEAM_INPUT_MAX_ORIGINS` origins.
+    fn glyph(
+        &self,
+        read_origins: impl FnOnce(&Handles, *mut ActionOrigin) -> Option<i32>,
+    ) -> Option<PathBuf> {
+        let mut origins = [0; MAX_ORIGINS];
+        let count = read_origins(self.handles.borrow().as_ref()?, origins.as_mut_ptr())?;
+        if count <= 0 {
+            return None;
+        }
+
+        // SAFETY: `input` is the Steam Input interface. The glyph is a nul-terminated path that
+        // Steam keeps alive.
+        let path = unsafe {
+            let path = SteamAPI_ISteamInput_GetGlyphPNGForActionOrigin(
+                self.input, origins[0], GLYPH_SIZE, 0,
+            );
+            if path.is_null() {
+                return None;
+            }
+            CStr::from_ptr(path)
+        };
+        Some(PathBuf::from(path.to_str().ok()?))
+    }
+}
+
+// SAFETY for all of the below: `input` is the Steam Input interface, which stays valid while
+// Steam is running, and Steam ignores handles of disconnected controllers.
+impl GamepadDevice for Device {
+    fn has_sensor(&self, _: Sensor) -> bool {
+        false
+    }
+
+    fn set_sensor_enabled(&self, _: Sensor, _: bool) -> Result<()> {
+        Err("Motion sensors are not supported through Steam Input".into())
+    }
+
+    fn set_led(&self, red: u8, green: u8, blue: u8) -> Result<()> {
+        // Flag 0 sets the color, rather than restoring the player's setting.
+        unsafe {
+            SteamAPI_ISteamInput_SetLEDColor(self.input, self.controller, red, green, blue, 0)
+        };
+
+        Ok(())
+    }
+
+    fn set_player_index(&self, _: Option<u8>) -> Result<()> {
+        Err("Steam assigns player indices itself".into())
+    }
+
+    fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()> {
+        let left = (low_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        let right = (high_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        unsafe { SteamAPI_ISteamInput_TriggerVibration(self.input, self.controller, left, right) };
+
+        Ok(())
+    }
+
+    fn controller_type(&self) -> Option<ControllerType> {
+        // Values of `ESteamInputType`.
+        match unsafe { SteamAPI_ISteamInput_GetInputTypeForHandle(self.input, self.controller) } {
+            2 | 3 => Some(ControllerType::Xbox),
+            5 | 12 | 13 => Some(ControllerType::PlayStation),
+            8..=10 => Some(ControllerType::SwitchPro),
+            _ => Some(ControllerType::Generic),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Gamepad {
+    /// Gets the Steam Input handle of the gamepad, for features that fishsticks does not wrap.
+    ///
+    /// Returns `None` for gamepads of other backends.
+    pub fn steam_input_handle(&self) -> Option<u64> {
+        Some(self.device::<Device>()?.controller)
+    }
+
+    /// Gets the image that Steam shows for the input that the player bound to a button, as the
+    /// path of a PNG file, such as for button prompts that follow the player's configuration.
+    ///
+    /// Returns `None` for gamepads of other backends and unbound buttons.
+    pub fn steam_button_glyph(&self, button: Button) -> Option<PathBuf> {
+        let device = self.device::<Device>()?;
+        device.glyph(|handles, origins| {
+            let &(_, action) = handles.digital.iter().find(|&&(b, _)| b == button)?;
+            // SAFETY: `origins` has room for `STEAM_INPUT_MAX_ORIGINS` origins.
+            Some(unsafe {
+                SteamAPI_ISteamInput_GetDigitalActionOrigins(
+                    device.input,
+                    device.controller,
+                    handles.action_set,
+                    action,
+                    origins,
+                )
+            })
+        })
+    }
+
+    /// Gets the image that Steam shows for the input that the player bound to an axis, like
+    /// [`Self::steam_button_glyph`]. Both axes of a stick have the same glyph.
+    pub fn steam_axis_glyph(&self, axis: Axis) -> Option<PathBuf> {
+        let device = self.device::<Device>()?;
+        device.glyph(|handles, origins| {
+            let &(action, _, _) = handles
+                .analog
+                .iter()
+                .find(|&&(_, x, y)| x == axis || y == Some(axis))?;
+            // SAFETY: `origins` has room for `STEAM_INPUT_MAX_ORIGINS` origins.
+            Some(unsafe {
+                SteamAPI_ISteamInput_GetAnalogActionOrigins(
+                    device.input,
+                    device.controller,
+                    handles.action_set,
+                    action,
+                    origins,
+                )
+            })
+        })
+    }
+}
+
+pub struct ImplementationContext {
+    input: *mut c_void,
+    action_set: CString,
+    // Shared with the devices, for their glyphs.
+    handles: Rc<RefCell<Option<Handles>>>,
+    ignore_devices: bool,
+    // Steam Input is shared by the whole process.
+    _exclusive: Exclusive,
+}
+
+// Set while a context runs Steam Input.
+static STEAM_ALIVE: AtomicBool = AtomicBool::new(false);
+
+impl ImplementationContext {
+    /// Connects to Steam Input. The game has to initialize the Steam API beforehand.
+    pub fn new() -> Result<Self> {
+        let exclusive = Exclusive::acquire(&STEAM_ALIVE, BackendKind::Steam)?;
+        // SAFETY: getting the interface has no preconditions. It is null without Steam.
+        let input = unsafe { SteamAPI_SteamInput_v006() };
+        // SAFETY: `input` is the Steam Input interface.
+        if input.is_null() || !unsafe { SteamAPI_ISteamInput_Init(input, true) } {
+            return Err("Steam is not running, or the Steam API was not initialized".into());
+        }
+
+        Ok(Self {
+            input,
+            action_set: CString::new(DEFAULT_ACTION_SET).expect("the name contains no nul bytes"),
+            handles: Default::default(),
+            ignore_devices: false,
+            _exclusive: exclusive,
+        })
+    }
+}
+
+impl super::Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        if self.ignore_devices {
+            return report;
+        }
+
+        // SAFETY: `input` is the Steam Input interface.
+        unsafe { SteamAPI_ISteamInput_RunFrame(self.input, false) };
+
+        // Action handles are only available once Steam has loaded the game's configuration.
+        if self.handles.borrow().is_none() {
+            *self.handles.borrow_mut() = Handles::lookup(self.input, &self.action_set);
+        }
+        let shared_handles = &self.handles;
+        let handles = shared_handles.borrow();
+        let handles = match &*handles {
+            Some(handles) => handles,
+            None => return report,
+        };
+
+        let mut controllers = [0; MAX_CONTROLLERS];
+        // SAFETY: `controllers` has room for `STEAM_INPUT_MAX_COUNT` handles.
+        let count = unsafe {
+            SteamAPI_ISteamInput_GetConnectedControllers(self.input, controllers.as_mut_ptr())
+        };
+        let controllers = &controllers[..count.clamp(0, MAX_CONTROLLERS as i32) as usize];
+
+        gamepads.retain(|id, _| {
+            controllers
+                .iter()
+                .any(|&controller| GamepadId(controller as usize) == *id)
+        });
+
+        for &controller in controllers {
+            report.events += 1;
+            let input = self.input;
+            let gamepad = gamepads
+                .entry(GamepadId(controller as usize))
+                .or_insert_with(|| {
+                    let device = Device {
+                        input,
+                        controller,
+                        handles: Rc::clone(shared_handles),
+                    };
+                    let mut gamepad = Gamepad::new(Some(Box::new(device)), STEAM_GUID);
+                    gamepad.capabilities.led = true;
+                    gamepad
+                });
+
+            // SAFETY: `input` is the Steam Input interface and the handles came from it.
+            unsafe {
+                SteamAPI_ISteamInput_ActivateActionSet(input, controller, handles.action_set)
+            };
+
+            for &(button, action) in &handles.digital {
+                // SAFETY: as above.
+                let data =
+                    unsafe { SteamAPI_ISteamInput_GetDigitalActionData(input, controller, action) };
+                if data.active && data.state {
+                    gamepad.digital_inputs.activate(button);
+                } else {
+                    gamepad.digital_inputs.deactivate(button);
+                }
+            }
+
+            for &(action, x_axis, y_axis) in &handles.analog {
+                // SAFETY: as above.
+                let data =
+                    unsafe { SteamAPI_ISteamInput_GetAnalogActionData(input, controller, action) };
+                let (x, y) = if data.active {
+                    (data.x, data.y)
+                } else {
+                    (0.0, 0.0)
+                };
+
+                gamepad.analog_inputs.set(x_axis, AnalogInputValue::from(x));
+                if let Some(y_axis) = y_axis {
+                    gamepad
+                        .analog_inputs
+                        .set(y_axis, AnalogInputValue::from(-y));
+                }
+            }
+        }
+
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+    }
+
+    fn set_steam_action_set(&mut self, name: &str) -> Result<()> {
+        self.action_set =
+            CString::new(name).map_err(|_| "Action set names cannot contain nul bytes")?;
+        // Looked up again on the next update.
+        *self.handles.borrow_mut() = None;
+        Ok(())
+    }
+}
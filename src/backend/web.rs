@@ -0,0 +1,189 @@
+use super::{InitOptions, UpdateReport};
+use crate::analog::AnalogInputValue;
+use crate::capabilities::Capabilities;
+use crate::{Axis, Button, Gamepad, GamepadId, Guid};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::JsCast;
+
+use crate::Result;
+
+/// Inputs by their index in the standard layout.
+/// See <https://w3c.github.io/gamepad/#remapping>.
+const STANDARD_AXES: [Axis; 4] = [Axis::LeftX, Axis::LeftY, Axis::RightX, Axis::RightY];
+const STANDARD_BUTTONS: [Input; 17] = [
+    Input::Button(Button::A),
+    Input::Button(Button::B),
+    Input::Button(Button::X),
+    Input::Button(Button::Y),
+    Input::Button(Button::LeftShoulder),
+    Input::Button(Button::RightShoulder),
+    // Triggers are reported as buttons by browsers, but as axes by SDL2.
+    // Map them to axes for parity.
+    Input::Axis(Axis::TriggerLeft),
+    Input::Axis(Axis::TriggerRight),
+    Input::Button(Button::Back),
+    Input::Button(Button::Start),
+    Input::Button(Button::LeftStick),
+    Input::Button(Button::RightStick),
+    Input::Button(Button::DPadUp),
+    Input::Button(Button::DPadDown),
+    Input::Button(Button::DPadLeft),
+    Input::Button(Button::DPadRight),
+    Input::Button(Button::Guide),
+];
+
+enum Input {
+    Axis(Axis),
+    Button(Button),
+}
+
+pub struct ImplementationContext {
+    navigator: web_sys::Navigator,
+    // Gamepads seen during the current update, kept to reuse its allocation.
+    connected: HashSet<GamepadId>,
+    ignore_devices: bool,
+    raw_joysticks: bool,
+    logging: bool,
+}
+
+impl ImplementationContext {
+    pub fn new(options: &InitOptions) -> Result<Self> {
+        let window = web_sys::window().ok_or("No browser window available")?;
+
+        Ok(Self {
+            navigator: window.navigator(),
+            connected: HashSet::new(),
+            ignore_devices: false,
+            raw_joysticks: false,
+            logging: options.logging,
+        })
+    }
+}
+
+impl super::Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        if self.ignore_devices {
+            return report;
+        }
+
+        let web_gamepads = match self.navigator.get_gamepads() {
+            Ok(web_gamepads) => web_gamepads,
+            Err(e) => {
+                let error = format!("Failed to get gamepads: {:?}", e);
+                report.backend_errors.push(error);
+                return report;
+            }
+        };
+
+        // The Gamepad API has no input events, so compare the state of every connected gamepad.
+        let connected = &mut self.connected;
+        connected.clear();
+        for web_gamepad in web_gamepads.iter() {
+            // Empty slots are `null`.
+            let web_gamepad = match web_gamepad.dyn_into::<web_sys::Gamepad>() {
+                Ok(web_gamepad) if web_gamepad.connected() => web_gamepad,
+                _ => continue,
+            };
+            // Without the standard mapping, the index of an input says nothing about what it
+            // is, so such gamepads are raw joysticks.
+            let raw = web_gamepad.mapping() != web_sys::GamepadMappingType::Standard;
+            if raw && !self.raw_joysticks {
+                continue;
+            }
+
+            let id = GamepadId(web_gamepad.index() as usize);
+            connected.insert(id);
+            report.events += 1;
+            let gamepad = gamepads.entry(id).or_insert_with(|| {
+                #[cfg(debug_assertions)]
+                log!(self.logging, "Added gamepad \"{}\"", web_gamepad.id());
+
+                let mut gamepad = Gamepad::new(None, guid(&web_gamepad.id()));
+                gamepad.name = Some(web_gamepad.id());
+                if raw {
+                    gamepad.raw = true;
+                    gamepad.capabilities = Capabilities::none();
+                } else {
+                    // The standard mapping has every button and axis, but nothing can rumble.
+                    gamepad.capabilities.rumble = false;
+                }
+                gamepad
+            });
+            if raw {
+                update_raw(gamepad, &web_gamepad);
+                continue;
+            }
+
+            for (value, &axis) in web_gamepad.axes().iter().zip(STANDARD_AXES.iter()) {
+                let value = value.as_f64().unwrap_or_default() as f32;
+                gamepad
+                    .analog_inputs
+                    .set(axis, AnalogInputValue::from(value));
+            }
+
+            for (button, input) in web_gamepad.buttons().iter().zip(STANDARD_BUTTONS.iter()) {
+                let button = button.unchecked_into::<web_sys::GamepadButton>();
+                match *input {
+                    Input::Axis(axis) => {
+                        let value = button.value() as f32;
+                        gamepad
+                            .analog_inputs
+                            .set(axis, AnalogInputValue::from(value));
+                    }
+                    Input::Button(input) if button.pressed() => {
+                        gamepad.digital_inputs.activate(input);
+                    }
+                    Input::Button(input) => {
+                        gamepad.digital_inputs.deactivate(input);
+                    }
+                }
+            }
+        }
+
+        gamepads.retain(|id, _| connected.contains(id));
+
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+    }
+
+    /// Raw joysticks are removed by the next update once disabled.
+    fn set_raw_joysticks(&mut self, enabled: bool) -> Result<()> {
+        self.raw_joysticks = enabled;
+        Ok(())
+    }
+}
+
+/// Updates the numbered inputs of a gamepad without the standard mapping.
+fn update_raw(gamepad: &mut Gamepad, web_gamepad: &web_sys::Gamepad) {
+    for (value, index) in web_gamepad.axes().iter().zip(0..=u8::MAX) {
+        let value = value.as_f64().unwrap_or_default() as f32;
+        gamepad
+            .raw_inputs
+            .axes
+            .set(index, AnalogInputValue::from(value));
+    }
+
+    for (button, index) in web_gamepad.buttons().iter().zip(0..=u8::MAX) {
+        let button = button.unchecked_into::<web_sys::GamepadButton>();
+        if button.pressed() {
+            gamepad.raw_inputs.buttons.activate(index);
+        } else {
+            gamepad.raw_inputs.buttons.deactivate(index);
+        }
+    }
+}
+
+/// Browsers do not expose GUIDs. Use the start of the id string instead,
+/// which holds the vendor and product ids in most browsers.
+fn guid(id: &str) -> Guid {
+    let mut guid = [0; 16];
+    for (byte, &id_byte) in guid.iter_mut().zip(id.as_bytes()) {
+        *byte = id_byte;
+    }
+
+    Guid(guid)
+}
@@ -0,0 +1,195 @@
+use super::{GamepadDevice, InitOptions, UpdateReport};
+use crate::analog::AnalogInputValue;
+use crate::glyph::ControllerType;
+use crate::sensor::Sensor;
+use crate::{Axis, Button, Gamepad, GamepadId, Guid};
+use std::any::Any;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use winapi::shared::winerror::ERROR_SUCCESS;
+use winapi::um::xinput;
+
+use crate::Result;
+
+/// XInput supports up to four controllers, in fixed slots.
+const SLOTS: u32 = 4;
+
+/// How often to check empty slots for newly connected controllers.
+///
+/// Polling an empty slot is slow, so it is not done on every update.
+const HOTPLUG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// XInput does not expose vendor and product ids. Use the GUID SDL2 reports for XInput devices.
+const XINPUT_GUID: Guid = Guid(*b"xinput\0\0\0\0\0\0\0\0\0\0");
+
+const BUTTONS: [(u16, Button); 14] = [
+    (xinput::XINPUT_GAMEPAD_A, Button::A),
+    (xinput::XINPUT_GAMEPAD_B, Button::B),
+    (xinput::XINPUT_GAMEPAD_X, Button::X),
+    (xinput::XINPUT_GAMEPAD_Y, Button::Y),
+    (xinput::XINPUT_GAMEPAD_BACK, Button::Back),
+    (xinput::XINPUT_GAMEPAD_START, Button::Start),
+    (xinput::XINPUT_GAMEPAD_LEFT_THUMB, Button::LeftStick),
+    (xinput::XINPUT_GAMEPAD_RIGHT_THUMB, Button::RightStick),
+    (xinput::XINPUT_GAMEPAD_LEFT_SHOULDER, Button::LeftShoulder),
+    (xinput::XINPUT_GAMEPAD_RIGHT_SHOULDER, Button::RightShoulder),
+    (xinput::XINPUT_GAMEPAD_DPAD_UP, Button::DPadUp),
+    (xinput::XINPUT_GAMEPAD_DPAD_DOWN, Button::DPadDown),
+    (xinput::XINPUT_GAMEPAD_DPAD_LEFT, Button::DPadLeft),
+    (xinput::XINPUT_GAMEPAD_DPAD_RIGHT, Button::DPadRight),
+];
+
+/// Device features of the controller in an XInput slot.
+struct Device(u32);
+
+impl GamepadDevice for Device {
+    fn has_sensor(&self, _: Sensor) -> bool {
+        false
+    }
+
+    fn set_sensor_enabled(&self, _: Sensor, _: bool) -> Result<()> {
+        Err("Motion sensors are not supported by XInput".into())
+    }
+
+    fn set_led(&self, _: u8, _: u8, _: u8) -> Result<()> {
+        Err("LEDs are not supported by XInput".into())
+    }
+
+    fn set_player_index(&self, _: Option<u8>) -> Result<()> {
+        Err("XInput assigns player indices itself".into())
+    }
+
+    fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()> {
+        let mut vibration = xinput::XINPUT_VIBRATION {
+            wLeftMotorSpeed: (low_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+            wRightMotorSpeed: (high_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+        };
+
+        // SAFETY: `vibration` points to a live local.
+        let result = unsafe { xinput::XInputSetState(self.0, &mut vibration) };
+
+        if result == ERROR_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!("Failed to set rumble (error {})", result))
+        }
+    }
+
+    fn controller_type(&self) -> Option<ControllerType> {
+        Some(ControllerType::Xbox)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Gamepad {
+    /// Gets the XInput user index of the gamepad, for features that fishsticks does not wrap.
+    ///
+    /// Returns `None` for gamepads of other backends.
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        Some(self.device::<Device>()?.0)
+    }
+}
+
+pub struct ImplementationContext {
+    // The last packet number of each connected slot, to skip unchanged states.
+    packets: HashMap<u32, u32>,
+    last_scan: Option<Instant>,
+    ignore_devices: bool,
+    logging: bool,
+}
+
+impl ImplementationContext {
+    pub fn new(options: &InitOptions) -> Result<Self> {
+        Ok(Self {
+            packets: HashMap::new(),
+            last_scan: None,
+            ignore_devices: false,
+            logging: options.logging,
+        })
+    }
+}
+
+impl super::Backend for ImplementationContext {
+    fn update(&mut self, gamepads: &mut HashMap<GamepadId, Gamepad>) -> UpdateReport {
+        let mut report = UpdateReport::default();
+        if self.ignore_devices {
+            return report;
+        }
+
+        let scan = self
+            .last_scan
+            .is_none_or(|last_scan| last_scan.elapsed() >= HOTPLUG_INTERVAL);
+        if scan {
+            self.last_scan = Some(Instant::now());
+        }
+
+        for slot in 0..SLOTS {
+            let id = GamepadId(slot as usize);
+            let connected = self.packets.contains_key(&slot);
+            if !connected && !scan {
+                continue;
+            }
+
+            // SAFETY: an all-zero state is valid.
+            let mut state: xinput::XINPUT_STATE = unsafe { std::mem::zeroed() };
+            // SAFETY: `state` points to a live local.
+            let result = unsafe { xinput::XInputGetState(slot, &mut state) };
+            if result != ERROR_SUCCESS {
+                if self.packets.remove(&slot).is_some() {
+                    gamepads.remove(&id);
+
+                    #[cfg(debug_assertions)]
+                    log!(self.logging, "Removed gamepad \"XInput {}\"", slot);
+                }
+                continue;
+            }
+
+            if self.packets.insert(slot, state.dwPacketNumber) == Some(state.dwPacketNumber) {
+                continue;
+            }
+            report.events += 1;
+            let gamepad = gamepads.entry(id).or_insert_with(|| {
+                #[cfg(debug_assertions)]
+                log!(self.logging, "Added gamepad \"XInput {}\"", slot);
+
+                Gamepad::new(Some(Box::new(Device(slot))), XINPUT_GUID)
+            });
+
+            let pad = state.Gamepad;
+            for (mask, button) in BUTTONS {
+                if pad.wButtons & mask != 0 {
+                    gamepad.digital_inputs.activate(button);
+                } else {
+                    gamepad.digital_inputs.deactivate(button);
+                }
+            }
+
+            // XInput reports vertical stick axes as positive upwards, unlike SDL2.
+            let axes = [
+                (Axis::LeftX, pad.sThumbLX as f32 / i16::MAX as f32),
+                (Axis::LeftY, -(pad.sThumbLY as f32) / i16::MAX as f32),
+                (Axis::RightX, pad.sThumbRX as f32 / i16::MAX as f32),
+                (Axis::RightY, -(pad.sThumbRY as f32) / i16::MAX as f32),
+                (Axis::TriggerLeft, pad.bLeftTrigger as f32 / u8::MAX as f32),
+                (
+                    Axis::TriggerRight,
+                    pad.bRightTrigger as f32 / u8::MAX as f32,
+                ),
+            ];
+            for (axis, value) in axes {
+                gamepad
+                    .analog_inputs
+                    .set(axis, AnalogInputValue::from(value));
+            }
+        }
+
+        report
+    }
+
+    fn ignore_devices(&mut self) {
+        self.ignore_devices = true;
+    }
+}
@@ -0,0 +1,136 @@
+//! Bevy integration.
+//!
+//! Add [`FishsticksPlugin`] to an app to initialize a [`GamepadContext`] and update it every frame.
+//! Gamepad state is then available through the [`Gamepads`] resource, and changes are sent as
+//! [`GamepadEvent`]s. Errors are reported through [`Gamepads::error`] rather than printed.
+
+use crate::event::GamepadEvent;
+use crate::state::GamepadSnapshot;
+use crate::{Axis, Button, GamepadContext, GamepadId, BUTTONS};
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::event::EventWriter;
+use bevy_ecs::system::{NonSendMut, ResMut, Resource};
+use std::collections::{HashMap, HashSet};
+
+/// Initializes a [`GamepadContext`] and updates it at the start of every frame.
+///
+/// The context is stored as a non-send resource, since it is not `Send` with every backend.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FishsticksPlugin;
+
+impl Plugin for FishsticksPlugin {
+    fn build(&self, app: &mut App) {
+        let mut gamepads = Gamepads::default();
+        match GamepadContext::init() {
+            Ok(context) => {
+                app.insert_non_send_resource(context);
+            }
+            Err(e) => gamepads.error = Some(e),
+        }
+
+        app.add_event::<GamepadEvent>()
+            .insert_resource(gamepads)
+            .add_systems(PreUpdate, update_gamepads);
+    }
+}
+
+/// The input state of every connected gamepad, as of the start of the frame.
+#[derive(Debug, Default, Resource)]
+pub struct Gamepads {
+    snapshots: HashMap<GamepadId, GamepadSnapshot>,
+    just_activated: HashSet<(GamepadId, Button)>,
+    just_deactivated: HashSet<(GamepadId, Button)>,
+    error: Option<String>,
+}
+
+impl Gamepads {
+    /// Gets why the gamepads could not be initialized, or why a backend failed during the last
+    /// update.
+    ///
+    /// Gamepads of the backends that still work are updated anyway.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Gets an iterator over the ids of all gamepads.
+    pub fn ids(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.snapshots.keys().copied()
+    }
+
+    /// Gets the state of a specific gamepad.
+    ///
+    /// Returns `None` if the gamepad is not found.
+    pub fn get(&self, id: GamepadId) -> Option<&GamepadSnapshot> {
+        self.snapshots.get(&id)
+    }
+
+    /// Checks if a button is activated on a gamepad.
+    pub fn activated(&self, id: GamepadId, button: Button) -> bool {
+        self.get(id)
+            .is_some_and(|gamepad| gamepad.activated(button))
+    }
+
+    /// Checks if a button has just been activated on a gamepad.
+    pub fn just_activated(&self, id: GamepadId, button: Button) -> bool {
+        self.just_activated.contains(&(id, button))
+    }
+
+    /// Checks if a button has just been deactivated on a gamepad.
+    pub fn just_deactivated(&self, id: GamepadId, button: Button) -> bool {
+        self.just_deactivated.contains(&(id, button))
+    }
+
+    /// Gets the value of an axis on a gamepad.
+    ///
+    /// Returns `0.0` if the gamepad is not found.
+    pub fn value(&self, id: GamepadId, axis: Axis) -> f32 {
+        self.get(id).map_or(0.0, |gamepad| gamepad.value(axis))
+    }
+
+    fn refresh(&mut self, context: &GamepadContext) {
+        self.just_activated.clear();
+        self.just_deactivated.clear();
+
+        // Buttons of disconnected gamepads are released, so that systems see them let go.
+        for (&id, snapshot) in &self.snapshots {
+            if context.gamepad(id).is_none() {
+                for &button in BUTTONS.iter() {
+                    if snapshot.activated(button) {
+                        self.just_deactivated.insert((id, button));
+                    }
+                }
+            }
+        }
+        self.snapshots.clear();
+
+        for (id, gamepad) in context.gamepads() {
+            self.snapshots.insert(id, gamepad.snapshot());
+            for &button in BUTTONS.iter() {
+                if gamepad.digital_inputs.just_activated(button) {
+                    self.just_activated.insert((id, button));
+                }
+                if gamepad.digital_inputs.just_deactivated(button) {
+                    self.just_deactivated.insert((id, button));
+                }
+            }
+        }
+    }
+}
+
+fn update_gamepads(
+    context: Option<NonSendMut<GamepadContext>>,
+    mut gamepads: ResMut<Gamepads>,
+    mut events: EventWriter<GamepadEvent>,
+) {
+    let mut context = match context {
+        Some(context) => context,
+        None => return,
+    };
+
+    gamepads.error = context.update().err();
+
+    while let Some(event) = context.poll_event() {
+        events.send(event);
+    }
+    gamepads.refresh(&context);
+}
@@ -0,0 +1,68 @@
+//! Input buffering, for fighting-game style leniency.
+
+use crate::digital::DigitalInput;
+use crate::storage::{InputKind, InputMap};
+
+/// Remembers recent activations of the digital inputs of a gamepad.
+///
+/// Lets games accept inputs that arrive slightly too early, such as a jump pressed just before
+/// landing. Call [`InputBuffer::update`] once per update with the gamepad's digital inputs.
+#[derive(Debug)]
+pub struct InputBuffer<T: InputKind> {
+    // Maps buffered inputs to the tick they were last activated at.
+    activations: InputMap<T, u64>,
+    tick: u64,
+}
+
+impl<T> InputBuffer<T>
+where
+    T: InputKind,
+{
+    /// Creates an empty input buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the inputs that have just been activated.
+    pub fn update(&mut self, inputs: &DigitalInput<T>) {
+        self.tick = inputs.tick();
+        for input in inputs.just_activated_inputs() {
+            self.activations.insert(input, self.tick);
+        }
+    }
+
+    /// Checks if an input was activated within the last `frames` updates and not consumed since.
+    ///
+    /// With `frames` set to `1`, this is equivalent to [`DigitalInput::just_activated`].
+    pub fn buffered_activated(&self, input: T, frames: u64) -> bool {
+        self.activations
+            .get(input)
+            .is_some_and(|&tick| self.tick.saturating_sub(tick) < frames)
+    }
+
+    /// Removes the buffered activation of an input, so that it is only acted on once.
+    ///
+    /// Returns `true` if there was a buffered activation.
+    pub fn consume(&mut self, input: T) -> bool {
+        self.activations.remove(input).is_some()
+    }
+
+    /// Checks for and consumes a buffered activation in one go.
+    pub fn consume_buffered(&mut self, input: T, frames: u64) -> bool {
+        self.buffered_activated(input, frames) && self.consume(input)
+    }
+
+    /// Removes all buffered activations.
+    pub fn clear(&mut self) {
+        self.activations.clear();
+    }
+}
+
+impl<T: InputKind> Default for InputBuffer<T> {
+    fn default() -> Self {
+        Self {
+            activations: Default::default(),
+            tick: 0,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::admission::{DeviceFilter, JoinPolicy};
+use crate::backend::{self, InitOptions};
+use crate::config::Config;
+use crate::{BackendKind, GamepadContext};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::Result;
+
+/// Configures a [`GamepadContext`] before it is created, so that the first gamepads to connect
+/// already see the configuration.
+///
+/// Created with [`GamepadContext::builder`].
+#[derive(Debug, Default, Clone)]
+pub struct GamepadContextBuilder {
+    backends: Option<Vec<BackendKind>>,
+    merged: bool,
+    // How often to retry starting the backends, when they start on the first update.
+    lazy: Option<Duration>,
+    options: InitOptions,
+    config: Config,
+    deadzone: Option<f32>,
+    digital_deadzone: Option<f32>,
+    max_gamepads: Option<usize>,
+    join_policy: JoinPolicy,
+    device_filter: DeviceFilter,
+    without_devices: bool,
+}
+
+impl GamepadContextBuilder {
+    /// Creates a builder for a context with the first available backend that works.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tries these backends in order, instead of every backend of
+    /// [`BackendKind::AVAILABLE`].
+    pub fn backends(mut self, backends: &[BackendKind]) -> Self {
+        self.backends = Some(backends.to_vec());
+        self
+    }
+
+    /// Runs all backends side by side instead of picking the first that works.
+    ///
+    /// See [`GamepadContext::init_merged`].
+    pub fn merged(mut self, merged: bool) -> Self {
+        self.merged = merged;
+        self
+    }
+
+    /// Starts the backends on the first update rather than in [`Self::build`], retrying every
+    /// `retry` until they work, so that the game runs without gamepads on systems where they
+    /// fail or hang.
+    ///
+    /// SDL2, Steam Input and the web backend start within an update, since they have to stay on
+    /// the thread that updates the context. Other backends are tried on a worker thread first,
+    /// so that attempts that fail or hang do not stall updates.
+    ///
+    /// `build` then only fails without any backend to try. Check whether the backends run with
+    /// [`GamepadContext::backend_state`].
+    pub fn lazy(mut self, retry: Duration) -> Self {
+        self.lazy = Some(retry);
+        self
+    }
+
+    /// Loads SDL2 controller mappings from a file, such as the community
+    /// `gamecontrollerdb.txt`, on top of the built-in ones. Can be called several times.
+    ///
+    /// Used by the SDL2 and gilrs backends, and ignored by the others.
+    pub fn mappings_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.mappings.push(path.into());
+        self
+    }
+
+    /// Sets the config that gamepads start with. See [`GamepadContext::set_config`].
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the analog deadzone of every gamepad, overriding the one of the config.
+    pub fn deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = Some(deadzone);
+        self
+    }
+
+    /// Sets the digital deadzone of every gamepad, overriding the one of the config.
+    pub fn digital_deadzone(mut self, deadzone: f32) -> Self {
+        self.digital_deadzone = Some(deadzone);
+        self
+    }
+
+    /// See [`GamepadContext::set_max_gamepads`].
+    pub fn max_gamepads(mut self, max: Option<usize>) -> Self {
+        self.max_gamepads = max;
+        self
+    }
+
+    /// See [`GamepadContext::set_join_policy`].
+    pub fn join_policy(mut self, policy: JoinPolicy) -> Self {
+        self.join_policy = policy;
+        self
+    }
+
+    /// See [`GamepadContext::set_device_filter`].
+    pub fn device_filter(mut self, filter: DeviceFilter) -> Self {
+        self.device_filter = filter;
+        self
+    }
+
+    /// Enables or disables the messages that the backends of this context print in debug builds
+    /// when gamepads connect or disconnect. Enabled by default. Other contexts are not affected.
+    pub fn logging(mut self, enabled: bool) -> Self {
+        self.options.logging = enabled;
+        self
+    }
+
+    /// Opens no physical gamepads. See [`GamepadContext::init_without_devices`].
+    pub fn without_devices(mut self) -> Self {
+        self.without_devices = true;
+        self
+    }
+
+    /// Creates the context.
+    ///
+    /// Returns an error if no backend could be initialized, or any backend could not be
+    /// initialized when they are merged. Backends that another context runs exclusively, see
+    /// [`GamepadContext`], cannot be initialized.
+    /// Lazy backends are not initialized yet, see [`Self::lazy`].
+    pub fn build(self) -> Result<GamepadContext> {
+        let backends = self
+            .backends
+            .unwrap_or_else(|| BackendKind::AVAILABLE.to_vec());
+        let (kinds, gamepad_system) = match self.lazy {
+            Some(retry) => {
+                if backends.is_empty() {
+                    return Err("No gamepad backend to start".into());
+                }
+                let gamepad_system =
+                    backend::start_lazily(backends.clone(), self.merged, self.options, retry);
+                (backends, gamepad_system)
+            }
+            None => backend::start(&backends, self.merged, &self.options)?,
+        };
+
+        let mut context = GamepadContext::with_system(kinds, gamepad_system);
+        let mut config = self.config;
+        if self.deadzone.is_some() {
+            config.defaults.deadzone = self.deadzone;
+        }
+        if self.digital_deadzone.is_some() {
+            config.defaults.digital_deadzone = self.digital_deadzone;
+        }
+        context.set_config(config);
+        context.set_max_gamepads(self.max_gamepads);
+        context.set_join_policy(self.join_policy);
+        context.set_device_filter(self.device_filter);
+        if self.without_devices {
+            context.gamepad_system.ignore_devices();
+        }
+
+        Ok(context)
+    }
+}
@@ -0,0 +1,118 @@
+//! What a gamepad actually has, so that games can hide prompts for buttons and features that
+//! are missing instead of assuming the layout of an Xbox controller.
+
+use crate::storage::InputSet;
+use crate::{Axis, Button, AXES, BUTTONS};
+
+/// The buttons, axes and features of a gamepad, as returned by
+/// [`Gamepad::capabilities`](crate::Gamepad::capabilities).
+///
+/// Backends that cannot tell report the standard layout of [`Self::standard`].
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    buttons: InputSet<Button>,
+    axes: InputSet<Axis>,
+    /// Whether the gamepad can rumble.
+    pub rumble: bool,
+    /// Whether the gamepad has a touchpad.
+    pub touchpad: bool,
+    /// Whether the gamepad has a gyroscope.
+    pub gyroscope: bool,
+    /// Whether the gamepad has an accelerometer.
+    pub accelerometer: bool,
+    /// Whether the gamepad has an LED that can be set.
+    pub led: bool,
+    /// Whether the triggers of the gamepad can play
+    /// [`TriggerEffect`](crate::trigger::TriggerEffect)s.
+    pub adaptive_triggers: bool,
+}
+
+impl Capabilities {
+    /// Creates capabilities without any button, axis or feature, such as for raw joysticks.
+    pub fn none() -> Self {
+        Self {
+            buttons: InputSet::new(),
+            axes: InputSet::new(),
+            rumble: false,
+            touchpad: false,
+            gyroscope: false,
+            accelerometer: false,
+            led: false,
+            adaptive_triggers: false,
+        }
+    }
+
+    /// Creates the capabilities of an Xbox controller: the buttons and axes it has, and rumble.
+    pub fn standard() -> Self {
+        Self {
+            buttons: BUTTONS
+                .into_iter()
+                .filter(|button| !is_extra(*button))
+                .collect(),
+            axes: AXES.into_iter().collect(),
+            rumble: true,
+            ..Self::none()
+        }
+    }
+
+    /// Creates capabilities with these buttons and axes, and no feature.
+    pub fn with_inputs(
+        buttons: impl IntoIterator<Item = Button>,
+        axes: impl IntoIterator<Item = Axis>,
+    ) -> Self {
+        Self {
+            buttons: buttons.into_iter().collect(),
+            axes: axes.into_iter().collect(),
+            ..Self::none()
+        }
+    }
+
+    /// Checks if the gamepad has a button.
+    pub fn has_button(&self, button: Button) -> bool {
+        self.buttons.contains(button)
+    }
+
+    /// Checks if the gamepad has an axis.
+    pub fn has_axis(&self, axis: Axis) -> bool {
+        self.axes.contains(axis)
+    }
+
+    /// Iterates over the buttons of the gamepad.
+    pub fn buttons(&self) -> impl Iterator<Item = Button> + '_ {
+        self.buttons.iter()
+    }
+
+    /// Iterates over the axes of the gamepad.
+    pub fn axes(&self) -> impl Iterator<Item = Axis> + '_ {
+        self.axes.iter()
+    }
+
+    /// Gets the number of buttons of the gamepad.
+    pub fn button_count(&self) -> usize {
+        self.buttons.len()
+    }
+
+    /// Gets the number of axes of the gamepad.
+    pub fn axis_count(&self) -> usize {
+        self.axes.len()
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Checks if a button is missing from the standard layout.
+fn is_extra(button: Button) -> bool {
+    matches!(
+        button,
+        Button::Misc1
+            | Button::Paddle1
+            | Button::Paddle2
+            | Button::Paddle3
+            | Button::Paddle4
+            | Button::Touchpad
+    )
+}
@@ -0,0 +1,689 @@
+//! Declarative configuration of the whole input system.
+//!
+//! A [`Config`] holds default settings for every gamepad, per-device overrides, admin devices,
+//! action bindings and haptic cues. With the `config` feature enabled, it can be loaded from a
+//! TOML file:
+//!
+//! ```toml
+//! admin = ["030000005e0400008e02000010010000:3032363030"]
+//!
+//! [defaults]
+//! deadzone = 0.1
+//! digital_deadzone = 0.5
+//! stick_to_dpad = true
+//! socd = "LastWins"
+//! curve = 1.5
+//!
+//! [defaults.axes.RightY]
+//! invert = true
+//! curve = 2.0
+//!
+//! [defaults.buttons.Start]
+//! rate_limit = 2.0
+//!
+//! [defaults.buttons.X]
+//! turbo = 10.0
+//!
+//! [defaults.buttons.Paddle1]
+//! remap = "A"
+//!
+//! [defaults.buttons.RightShoulder]
+//! press_mode = "Toggle"
+//!
+//! [defaults.virtual_buttons.0]
+//! axis = "TriggerLeft"
+//! threshold = 0.3
+//!
+//! [devices.030000004c050000c405000000010000.axes.LeftX]
+//! deadzone = 0.2
+//! range = [-0.9, 0.9]
+//! smoothing = 0.5
+//! hysteresis = 0.05
+//!
+//! [bindings]
+//! jump = [{ Button = "A" }, { RawButton = 2 }]
+//!
+//! [haptics.hit]
+//! waveform = { Constant = 0.8 }
+//! duration = 0.2
+//! ```
+//!
+//! Axes, buttons and inputs are named like the variants of [`Axis`], [`Button`] and [`Input`],
+//! and durations are in seconds.
+
+use crate::analog::{AxisConfig, DEFAULT_ANALOG_DEADZONE, DEFAULT_DIGITAL_DEADZONE};
+use crate::digital::PressMode;
+use crate::direction::Socd;
+use crate::haptic::HapticEffect;
+use crate::profile::Input;
+use crate::virtual_button::VirtualButton;
+use crate::{Axis, Button, DeviceFingerprint, Gamepad, Guid, AXES};
+use std::collections::HashMap;
+use std::mem;
+
+/// Configuration of the whole input system.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Config {
+    /// Settings applied to every gamepad.
+    pub defaults: Settings,
+    /// Settings applied on top of the defaults to gamepads with a specific GUID.
+    #[cfg_attr(feature = "serde", serde(with = "string_keys"))]
+    pub devices: HashMap<Guid, Settings>,
+    /// Fingerprints of admin gamepads, written like `<guid>:<serial>` in files.
+    ///
+    /// See [`GamepadContext::set_admin`](crate::GamepadContext::set_admin).
+    #[cfg_attr(feature = "serde", serde(with = "strings"))]
+    pub admin: Vec<DeviceFingerprint>,
+    /// Maps the game's actions to the inputs that trigger them, unless a player's profile binds
+    /// them.
+    ///
+    /// See [`GamepadContext::binding`](crate::GamepadContext::binding).
+    pub bindings: HashMap<String, Vec<Input>>,
+    /// Haptic effects by name, so that their feel can be tuned without rebuilding the game.
+    ///
+    /// See [`GamepadContext::play_cue`](crate::GamepadContext::play_cue).
+    pub haptics: HashMap<String, HapticEffect>,
+}
+
+impl Config {
+    /// Gets the settings of gamepads with a GUID, which are its device settings on top of the
+    /// defaults.
+    pub fn settings(&self, guid: Guid) -> Settings {
+        let mut settings = self.defaults.clone();
+        if let Some(device) = self.devices.get(&guid) {
+            settings.layer(device);
+        }
+        settings
+    }
+
+    /// Applies the settings that match a gamepad's GUID to it.
+    ///
+    /// See [`Settings::apply`].
+    pub fn apply(&self, gamepad: &mut Gamepad) {
+        self.settings(gamepad.guid()).apply(gamepad);
+    }
+}
+
+/// Settings for a gamepad. Settings that are `None` fall back to the layer below, or to the
+/// defaults of the crate.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Settings {
+    /// The analog deadzone shared by all axes.
+    pub deadzone: Option<f32>,
+    /// The digital deadzone shared by all axes.
+    pub digital_deadzone: Option<f32>,
+    /// The response curve exponent shared by all axes.
+    pub curve: Option<f32>,
+    /// Settings for individual axes.
+    #[cfg_attr(feature = "serde", serde(with = "variant_keys"))]
+    pub axes: HashMap<Axis, AxisSettings>,
+    /// Settings for individual buttons.
+    #[cfg_attr(feature = "serde", serde(with = "variant_keys"))]
+    pub buttons: HashMap<Button, ButtonSettings>,
+    /// Whether the left stick is mirrored onto the D-pad.
+    pub stick_to_dpad: Option<bool>,
+    /// Whether the D-pad is mirrored onto the left stick.
+    pub dpad_to_stick: Option<bool>,
+    /// How simultaneous opposing directions on the D-pad are cleaned.
+    pub socd: Option<Socd>,
+    /// Virtual buttons, by Id.
+    #[cfg_attr(feature = "serde", serde(with = "string_keys"))]
+    pub virtual_buttons: HashMap<u8, VirtualButton>,
+}
+
+impl Settings {
+    /// Overrides these settings with the ones that are set in `settings`.
+    pub fn layer(&mut self, settings: &Settings) {
+        self.deadzone = settings.deadzone.or(self.deadzone);
+        self.digital_deadzone = settings.digital_deadzone.or(self.digital_deadzone);
+        self.curve = settings.curve.or(self.curve);
+        self.stick_to_dpad = settings.stick_to_dpad.or(self.stick_to_dpad);
+        self.dpad_to_stick = settings.dpad_to_stick.or(self.dpad_to_stick);
+        self.socd = settings.socd.or(self.socd);
+        for (&axis, axis_settings) in &settings.axes {
+            self.axes.entry(axis).or_default().layer(axis_settings);
+        }
+        for (&button, button_settings) in &settings.buttons {
+            self.buttons
+                .entry(button)
+                .or_default()
+                .layer(button_settings);
+        }
+        self.virtual_buttons.extend(&settings.virtual_buttons);
+    }
+
+    /// Applies the settings to a gamepad.
+    ///
+    /// Settings that were applied to the gamepad before and are `None` now are reset to their
+    /// defaults, so that they do not outlive a reloaded configuration. Other settings of the
+    /// gamepad are left unchanged.
+    pub fn apply(&self, gamepad: &mut Gamepad) {
+        let previous = mem::replace(&mut gamepad.settings, self.clone());
+        previous.reset_unset(self, gamepad);
+
+        if let Some(deadzone) = self.deadzone {
+            gamepad.set_deadzone(deadzone);
+        }
+        if let Some(deadzone) = self.digital_deadzone {
+            gamepad.set_digital_deadzone(deadzone);
+        }
+        if let Some(exponent) = self.curve {
+            for axis in AXES {
+                gamepad.analog_inputs.config_mut(axis).curve(exponent);
+            }
+        }
+        if let Some(enabled) = self.stick_to_dpad {
+            gamepad.set_stick_to_dpad(enabled);
+        }
+        if let Some(enabled) = self.dpad_to_stick {
+            gamepad.set_dpad_to_stick(enabled);
+        }
+        if let Some(socd) = self.socd {
+            gamepad.set_socd(Some(socd));
+        }
+
+        for (&axis, settings) in &self.axes {
+            if let Some(deadzone) = settings.deadzone {
+                gamepad.set_axis_deadzone(axis, deadzone);
+            }
+
+            let config = gamepad.analog_inputs.config_mut(axis);
+            if let Some(invert) = settings.invert {
+                config.invert(invert);
+            }
+            if let Some((min, max)) = settings.range {
+                config.range(min, max);
+            }
+            if let Some(exponent) = settings.curve {
+                config.curve(exponent);
+            }
+            if let Some(smoothing) = settings.smoothing {
+                config.smoothing(smoothing);
+            }
+            if let Some(hysteresis) = settings.hysteresis {
+                config.hysteresis(hysteresis);
+            }
+            if let Some(to) = settings.remap {
+                gamepad.remap_axis(axis, to);
+            }
+        }
+
+        for (&id, &button) in &self.virtual_buttons {
+            gamepad.set_virtual_button(id, button);
+        }
+
+        for (&button, settings) in &self.buttons {
+            let digital_inputs = &mut gamepad.digital_inputs;
+            if let Some(rate_limit) = settings.rate_limit {
+                digital_inputs.set_rate_limit(button, rate_limit);
+            }
+            if let Some(to) = settings.remap {
+                digital_inputs.set_remap(button, to);
+            }
+            // Setting the same mode again would release a toggled button.
+            if let Some(mode) = settings.press_mode {
+                if digital_inputs.press_mode(button) != mode {
+                    digital_inputs.set_press_mode(button, mode);
+                }
+            }
+            if let Some(turbo) = settings.turbo {
+                if digital_inputs.turbo(button) != Some(turbo) {
+                    digital_inputs.set_turbo(button, turbo);
+                }
+            }
+        }
+    }
+
+    /// Resets the settings of a gamepad that are set in `self` but not in `current`.
+    fn reset_unset(&self, current: &Settings, gamepad: &mut Gamepad) {
+        let default_axis = AxisConfig::default();
+
+        if self.deadzone.is_some() && current.deadzone.is_none() {
+            gamepad.set_deadzone(DEFAULT_ANALOG_DEADZONE);
+        }
+        if self.digital_deadzone.is_some() && current.digital_deadzone.is_none() {
+            gamepad.set_digital_deadzone(DEFAULT_DIGITAL_DEADZONE);
+        }
+        if self.curve.is_some() && current.curve.is_none() {
+            for axis in AXES {
+                let config = gamepad.analog_inputs.config_mut(axis);
+                config.curve(default_axis.response_curve());
+            }
+        }
+        if self.stick_to_dpad.is_some() && current.stick_to_dpad.is_none() {
+            gamepad.set_stick_to_dpad(false);
+        }
+        if self.dpad_to_stick.is_some() && current.dpad_to_stick.is_none() {
+            gamepad.set_dpad_to_stick(false);
+        }
+        if self.socd.is_some() && current.socd.is_none() {
+            gamepad.set_socd(None);
+        }
+
+        for (&axis, previous) in &self.axes {
+            let settings = current.axes.get(&axis).copied().unwrap_or_default();
+            if previous.deadzone.is_some() && settings.deadzone.is_none() {
+                gamepad.analog_inputs.reset_deadzone_for(axis);
+            }
+
+            let config = gamepad.analog_inputs.config_mut(axis);
+            if previous.invert.is_some() && settings.invert.is_none() {
+                config.invert(default_axis.is_inverted());
+            }
+            if previous.range.is_some() && settings.range.is_none() {
+                let (min, max) = default_axis.physical_range();
+                config.range(min, max);
+            }
+            if previous.curve.is_some() && settings.curve.is_none() {
+                let exponent = current.curve.unwrap_or(default_axis.response_curve());
+                config.curve(exponent);
+            }
+            if previous.smoothing.is_some() && settings.smoothing.is_none() {
+                config.smoothing(default_axis.smoothing_factor());
+            }
+            if previous.hysteresis.is_some() && settings.hysteresis.is_none() {
+                config.hysteresis(default_axis.hysteresis_width());
+            }
+            if previous.remap.is_some() && settings.remap.is_none() {
+                gamepad.remap_axis(axis, axis);
+            }
+        }
+
+        for &id in self.virtual_buttons.keys() {
+            if !current.virtual_buttons.contains_key(&id) {
+                gamepad.remove_virtual_button(id);
+            }
+        }
+
+        for (&button, previous) in &self.buttons {
+            let settings = current.buttons.get(&button).copied().unwrap_or_default();
+            let digital_inputs = &mut gamepad.digital_inputs;
+            if previous.rate_limit.is_some() && settings.rate_limit.is_none() {
+                digital_inputs.remove_rate_limit(button);
+            }
+            if previous.remap.is_some() && settings.remap.is_none() {
+                digital_inputs.set_remap(button, button);
+            }
+            if previous.press_mode.is_some() && settings.press_mode.is_none() {
+                digital_inputs.set_press_mode(button, PressMode::Hold);
+            }
+            if previous.turbo.is_some() && settings.turbo.is_none() {
+                digital_inputs.remove_turbo(button);
+            }
+        }
+    }
+}
+
+/// Settings for a single axis. Settings that are `None` fall back like in [`Settings`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct AxisSettings {
+    /// The analog deadzone of the axis.
+    pub deadzone: Option<f32>,
+    /// Whether the axis is inverted.
+    pub invert: Option<bool>,
+    /// The physical range of the axis, as `(min, max)`.
+    pub range: Option<(f32, f32)>,
+    /// The response curve exponent of the axis.
+    pub curve: Option<f32>,
+    /// The low-pass filter strength of the axis.
+    pub smoothing: Option<f32>,
+    /// How far below the deadzones the axis has to fall to be deactivated.
+    pub hysteresis: Option<f32>,
+    /// The axis that the physical axis reports to instead.
+    pub remap: Option<Axis>,
+}
+
+impl AxisSettings {
+    fn layer(&mut self, settings: &AxisSettings) {
+        self.deadzone = settings.deadzone.or(self.deadzone);
+        self.invert = settings.invert.or(self.invert);
+        self.range = settings.range.or(self.range);
+        self.curve = settings.curve.or(self.curve);
+        self.smoothing = settings.smoothing.or(self.smoothing);
+        self.hysteresis = settings.hysteresis.or(self.hysteresis);
+        self.remap = settings.remap.or(self.remap);
+    }
+}
+
+/// Settings for a single button. Settings that are `None` fall back like in [`Settings`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ButtonSettings {
+    /// The maximum number of activations per second.
+    pub rate_limit: Option<f32>,
+    /// The button that the physical button activates instead.
+    pub remap: Option<Button>,
+    /// How the button reacts to being pressed.
+    pub press_mode: Option<PressMode>,
+    /// How many times per second the button fires while held.
+    pub turbo: Option<f32>,
+}
+
+impl ButtonSettings {
+    fn layer(&mut self, settings: &ButtonSettings) {
+        self.rate_limit = settings.rate_limit.or(self.rate_limit);
+        self.remap = settings.remap.or(self.remap);
+        self.press_mode = settings.press_mode.or(self.press_mode);
+        self.turbo = settings.turbo.or(self.turbo);
+    }
+}
+
+#[cfg(feature = "config")]
+mod file {
+    use super::Config;
+    use crate::error::Result;
+    use std::fs;
+    use std::path::Path;
+
+    impl Config {
+        /// Parses a configuration from TOML.
+        pub fn from_toml(toml: &str) -> Result<Self> {
+            toml::from_str(toml).map_err(|e| e.to_string())
+        }
+
+        /// Loads a configuration from a TOML file.
+        pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+            let toml = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            Self::from_toml(&toml)
+        }
+    }
+}
+
+/// (De)serializes a map with keys written as strings, such as GUIDs, which file formats only
+/// allow as keys.
+#[cfg(feature = "serde")]
+mod string_keys {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::fmt::Display;
+    use std::hash::Hash;
+    use std::str::FromStr;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Display,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_map(map.iter().map(|(key, value)| (key.to_string(), value)))
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: FromStr + Eq + Hash,
+        K::Err: Display,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        HashMap::<String, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, value)| Ok((key.parse().map_err(D::Error::custom)?, value)))
+            .collect()
+    }
+}
+
+/// (De)serializes a map with enum keys written as the names of their variants, which file formats
+/// do not parse as enums by themselves.
+#[cfg(feature = "serde")]
+mod variant_keys {
+    use serde::de::IntoDeserializer;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Eq + Hash,
+        V: Serialize,
+        S: Serializer,
+    {
+        map.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        HashMap::<String, V>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(key, value)| Ok((K::deserialize(key.into_deserializer())?, value)))
+            .collect()
+    }
+}
+
+/// (De)serializes a list of values written as strings, like [`string_keys`].
+#[cfg(feature = "serde")]
+mod strings {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(ToString::to_string))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| value.parse().map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// (De)serializes durations as seconds.
+#[cfg(feature = "serde")]
+pub(crate) mod seconds {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Duration::try_from_secs_f64(f64::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gamepad() -> Gamepad {
+        Gamepad::new(None, Guid::default())
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn files_cover_every_setting() {
+        let config = Config::from_toml(
+            r#"
+            admin = ["030000005e0400008e02000010010000:3032363030"]
+
+            [defaults]
+            deadzone = 0.2
+            curve = 1.5
+            socd = "LastWins"
+
+            [defaults.axes.RightY]
+            invert = true
+
+            [defaults.buttons.RightShoulder]
+            press_mode = "Toggle"
+            remap = "A"
+
+            [defaults.virtual_buttons.3]
+            axis = "TriggerLeft"
+            threshold = 0.3
+
+            [devices.030000004c050000c405000000010000]
+            deadzone = 0.3
+
+            [bindings]
+            jump = [{ Button = "A" }, { RawButton = 2 }]
+
+            [haptics.hit]
+            waveform = { Constant = 0.8 }
+            duration = 0.25
+            "#,
+        )
+        .unwrap();
+
+        let defaults = &config.defaults;
+        assert_eq!(defaults.deadzone, Some(0.2));
+        assert_eq!(defaults.curve, Some(1.5));
+        assert_eq!(defaults.socd, Some(Socd::LastWins));
+        assert_eq!(defaults.axes[&Axis::RightY].invert, Some(true));
+        let shoulder = defaults.buttons[&Button::RightShoulder];
+        assert_eq!(shoulder.press_mode, Some(PressMode::Toggle));
+        assert_eq!(shoulder.remap, Some(Button::A));
+        assert_eq!(
+            defaults.virtual_buttons[&3],
+            VirtualButton::from_axis(Axis::TriggerLeft, 0.3)
+        );
+
+        let guid = "030000004c050000c405000000010000".parse().unwrap();
+        assert_eq!(config.devices[&guid].deadzone, Some(0.3));
+        assert_eq!(config.settings(guid).deadzone, Some(0.3));
+        assert_eq!(config.settings(guid).curve, Some(1.5));
+        assert_eq!(
+            config.admin,
+            ["030000005e0400008e02000010010000:3032363030"
+                .parse()
+                .unwrap()]
+        );
+        assert_eq!(
+            config.bindings["jump"],
+            [Input::Button(Button::A), Input::RawButton(2)]
+        );
+        assert_eq!(
+            config.haptics["hit"],
+            HapticEffect::constant(0.8, std::time::Duration::from_millis(250))
+        );
+
+        assert!(Config::from_toml("[defaults]\ndeadzon = 0.2").is_err());
+        assert!(Config::from_toml("[devices.nope]\ndeadzone = 0.2").is_err());
+    }
+
+    #[test]
+    fn removed_settings_are_reset() {
+        let mut gamepad = gamepad();
+        let mut settings = Settings {
+            deadzone: Some(0.3),
+            curve: Some(2.0),
+            stick_to_dpad: Some(true),
+            ..Default::default()
+        };
+        settings.axes.insert(
+            Axis::RightY,
+            AxisSettings {
+                invert: Some(true),
+                ..Default::default()
+            },
+        );
+        settings.buttons.insert(
+            Button::X,
+            ButtonSettings {
+                remap: Some(Button::Y),
+                turbo: Some(10.0),
+                press_mode: Some(PressMode::Toggle),
+                ..Default::default()
+            },
+        );
+        settings
+            .virtual_buttons
+            .insert(0, VirtualButton::from_axis(Axis::TriggerLeft, 0.5));
+        settings.apply(&mut gamepad);
+
+        assert_eq!(gamepad.analog_inputs.deadzone(), 0.3);
+        assert!(gamepad.analog_inputs.config(Axis::RightY).is_inverted());
+        assert_eq!(gamepad.remapped(Button::X), Button::Y);
+
+        Settings {
+            deadzone: Some(0.2),
+            ..Default::default()
+        }
+        .apply(&mut gamepad);
+
+        let analog_inputs = &gamepad.analog_inputs;
+        assert_eq!(analog_inputs.deadzone(), 0.2);
+        assert_eq!(analog_inputs.config(Axis::LeftX), AxisConfig::default());
+        assert_eq!(analog_inputs.config(Axis::RightY), AxisConfig::default());
+        assert!(!gamepad.stick_to_dpad());
+        assert_eq!(gamepad.remapped(Button::X), Button::X);
+        assert_eq!(gamepad.digital_inputs.turbo(Button::X), None);
+        assert_eq!(
+            gamepad.digital_inputs.press_mode(Button::X),
+            PressMode::Hold
+        );
+        assert_eq!(gamepad.virtual_button(0), None);
+    }
+
+    #[test]
+    fn settings_set_directly_are_kept() {
+        let mut gamepad = gamepad();
+        gamepad.set_turbo(Button::A, 5.0);
+        Settings {
+            deadzone: Some(0.3),
+            ..Default::default()
+        }
+        .apply(&mut gamepad);
+        Settings::default().apply(&mut gamepad);
+
+        assert_eq!(gamepad.digital_inputs.turbo(Button::A), Some(5.0));
+        assert_eq!(gamepad.analog_inputs.deadzone(), DEFAULT_ANALOG_DEADZONE);
+    }
+
+    #[test]
+    fn axis_curves_override_the_shared_curve() {
+        let mut gamepad = gamepad();
+        let mut settings = Settings {
+            curve: Some(2.0),
+            ..Default::default()
+        };
+        settings.axes.insert(
+            Axis::LeftX,
+            AxisSettings {
+                curve: Some(3.0),
+                ..Default::default()
+            },
+        );
+        settings.apply(&mut gamepad);
+        assert_eq!(
+            gamepad.analog_inputs.config(Axis::LeftX).response_curve(),
+            3.0
+        );
+        assert_eq!(
+            gamepad.analog_inputs.config(Axis::LeftY).response_curve(),
+            2.0
+        );
+
+        settings.axes.clear();
+        settings.apply(&mut gamepad);
+        assert_eq!(
+            gamepad.analog_inputs.config(Axis::LeftX).response_curve(),
+            2.0
+        );
+    }
+}
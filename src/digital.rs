@@ -1,68 +1,833 @@
 //! Generic digital input support.
 
-use std::collections::HashSet;
-use std::hash::Hash;
+use crate::storage::{InputKind, InputMap, InputSet};
+use crate::timestamp::Timestamp;
+use fishsticks_core::digital::InputState;
+use std::time::{Duration, Instant};
 
 /// Container for digital inputs.
 #[derive(Debug)]
-pub struct DigitalInput<T> {
-    activated: HashSet<T>,
-    just_activated: HashSet<T>,
-    just_deactivated: HashSet<T>,
+pub struct DigitalInput<T: InputKind> {
+    // Maps activated inputs to the time they were activated at.
+    activated: InputMap<T, Timestamp>,
+    // Inputs held by the backend and by emulation, and since when. An input is activated while
+    // either holds it, unless it is suppressed.
+    physical: InputMap<T, Instant>,
+    emulated: InputMap<T, Instant>,
+    suppressed: InputSet<T>,
+    // When the backend last reported a press or release of each input.
+    event_times: InputMap<T, Timestamp>,
+    just_activated: InputSet<T>,
+    just_deactivated: InputSet<T>,
+    // Every activation and deactivation since the last update, in the order they happened, so
+    // that taps within one update are not lost.
+    edges: Vec<Edge<T>>,
+    rate_limits: InputMap<T, RateLimit>,
+    turbos: InputMap<T, Turbo>,
+    // Backend inputs that activate another input instead of themselves.
+    remaps: InputMap<T, T>,
+
+    // Accessibility modes, and the inputs they keep activated after release.
+    modes: InputMap<T, PressMode>,
+    toggled: InputSet<T>,
+    // Sticky inputs that are held without another input having been pressed yet.
+    sticky_pending: InputSet<T>,
+    // Sticky inputs kept activated after release, until an input pressed after them is released.
+    stuck: InputSet<T>,
+    // Inputs whose release releases the stuck inputs.
+    unsticking: InputSet<T>,
+
+    // Activation times are kept after release, for gesture recognition.
+    last_activations: InputMap<T, Instant>,
+    previous_activations: InputMap<T, Instant>,
+    press_durations: InputMap<T, Duration>,
+    hold_threshold: Duration,
+
+    // Inputs released during the frame they were activated in, while latching.
+    latched: InputSet<T>,
+    latching: bool,
+
+    // Whether every input is kept deactivated, while still tracking what is held.
+    all_suppressed: bool,
+
+    tick: u64,
+}
+
+/// An activation or deactivation of a digital input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Edge<T> {
+    pub(crate) input: T,
+    pub(crate) activated: bool,
+    // When the backend reported the change, or when it happened for emulated changes.
+    pub(crate) time: Instant,
+}
+
+/// A set of digital inputs that have to be activated together, such as Start and Select.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord<T> {
+    inputs: Vec<T>,
+    window: Duration,
+}
+
+impl<T> Chord<T> {
+    /// Creates a chord from a set of inputs, with a grace window of 100 milliseconds.
+    pub fn new(inputs: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            inputs: inputs.into_iter().collect(),
+            window: DEFAULT_CHORD_WINDOW,
+        }
+    }
+
+    /// Sets how far apart the activations of the chord's inputs may be.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Gets the inputs of the chord.
+    pub fn inputs(&self) -> &[T] {
+        &self.inputs
+    }
+
+    /// Gets the grace window of the chord.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+/// Autofire of a held input.
+#[derive(Debug)]
+struct Turbo {
+    interval: Duration,
+    last_pulse: Instant,
+}
+
+/// How a digital input reacts to being pressed, for players who have trouble holding buttons.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PressMode {
+    /// The input is activated while it is held.
+    #[default]
+    Hold,
+    /// Pressing the input activates it until it is pressed again.
+    Toggle,
+    /// The input stays activated after it is released, until another input that is pressed
+    /// afterwards is released, like sticky keys. Pressing it again releases it.
+    ///
+    /// Holding another input along with it releases it as usual.
+    Sticky,
+}
+
+const DEFAULT_CHORD_WINDOW: Duration = Duration::from_millis(100);
+const DEFAULT_HOLD_THRESHOLD: Duration = Duration::from_millis(250);
+
+/// Minimum time between two accepted activations of an input.
+#[derive(Debug)]
+struct RateLimit {
+    interval: Duration,
+    last_activation: Option<Instant>,
+}
+
+impl RateLimit {
+    /// Records an activation, returning `false` if it came too soon after the previous one.
+    fn try_activate(&mut self) -> bool {
+        let now = Instant::now();
+        match self.last_activation {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last_activation = Some(now);
+                true
+            }
+        }
+    }
 }
 
 impl<T> DigitalInput<T>
 where
-    T: Hash + Eq,
+    T: InputKind,
 {
     /// Checks if a digital input is activated.
     pub fn activated(&self, input: T) -> bool {
-        self.activated.contains(&input)
+        self.activated.contains_key(input)
     }
 
     /// Checks if a digital input has just been activated.
     pub fn just_activated(&self, input: T) -> bool {
-        self.just_activated.contains(&input)
+        self.just_activated.contains(input)
     }
 
     /// Checks if a digital input has just been deactivated.
     pub fn just_deactivated(&self, input: T) -> bool {
-        self.just_deactivated.contains(&input)
+        self.just_deactivated.contains(input)
+    }
+
+    /// Gets when a digital input was activated.
+    ///
+    /// Returns `None` if the input is not activated.
+    pub fn activated_at(&self, input: T) -> Option<Timestamp> {
+        self.activated.get(input).copied()
+    }
+
+    /// Gets when the backend last reported a press or release of a digital input.
+    ///
+    /// This is the time of the operating system event where the backend provides it, so that
+    /// presses within a single update can be ordered and measured. Otherwise, it is when the
+    /// backend read the change. Emulated presses are not included.
+    pub fn last_event_time(&self, input: T) -> Option<Timestamp> {
+        self.event_times.get(input).copied()
+    }
+
+    /// Gets the number of updates since the gamepad connected.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Checks if all inputs of a chord are activated.
+    pub fn chord_activated(&self, chord: &Chord<T>) -> bool {
+        !chord.inputs.is_empty()
+            && chord
+                .inputs
+                .iter()
+                .all(|input| self.activated.contains_key(*input))
+    }
+
+    /// Checks if a chord has just been activated.
+    ///
+    /// This is the case when the last of its inputs has just been activated,
+    /// and all of them were activated within the chord's grace window.
+    pub fn chord_just_activated(&self, chord: &Chord<T>) -> bool {
+        if !self.chord_activated(chord)
+            || !chord
+                .inputs
+                .iter()
+                .any(|input| self.just_activated.contains(*input))
+        {
+            return false;
+        }
+
+        let times = chord
+            .inputs
+            .iter()
+            .filter_map(|&input| Some(self.activated.get(input)?.instant));
+        match (times.clone().min(), times.max()) {
+            (Some(first), Some(last)) => last.duration_since(first) <= chord.window,
+            _ => false,
+        }
+    }
+
+    /// Checks if a digital input has just been activated for the second time within `window`.
+    ///
+    /// A third quick activation counts as another double tap.
+    pub fn just_double_tapped(&self, input: T, window: Duration) -> bool {
+        if !self.just_activated.contains(input) {
+            return false;
+        }
+
+        match (
+            self.previous_activations.get(input),
+            self.last_activations.get(input),
+        ) {
+            (Some(&previous), Some(&last)) => last.duration_since(previous) <= window,
+            _ => false,
+        }
+    }
+
+    /// Checks if a digital input has just been released after a press shorter than the hold
+    /// threshold.
+    pub fn tapped_short(&self, input: T) -> bool {
+        self.just_deactivated.contains(input)
+            && self
+                .press_durations
+                .get(input)
+                .is_some_and(|&duration| duration < self.hold_threshold)
+    }
+
+    /// Checks if a digital input has been activated for at least the hold threshold.
+    pub fn held(&self, input: T) -> bool {
+        self.activated
+            .get(input)
+            .is_some_and(|activated_at| activated_at.instant.elapsed() >= self.hold_threshold)
+    }
+
+    /// Gets the hold threshold, which separates taps from holds.
+    pub fn hold_threshold(&self) -> Duration {
+        self.hold_threshold
+    }
+
+    /// Sets the hold threshold, which separates taps from holds.
+    pub fn set_hold_threshold(&mut self, threshold: Duration) {
+        self.hold_threshold = threshold;
+    }
+
+    /// Latches inputs, so that an input stays activated until the end of the frame it was
+    /// activated in, even if it was released before.
+    ///
+    /// Taps shorter than a frame are otherwise only seen as just deactivated, if at all.
+    /// Latched inputs are released when the next frame starts.
+    pub fn set_latching(&mut self, latching: bool) {
+        self.latching = latching;
+    }
+
+    /// Checks if inputs are latched.
+    pub fn latching(&self) -> bool {
+        self.latching
+    }
+
+    /// Limits how often a digital input can be activated.
+    ///
+    /// Activations that come less than `1.0 / max_per_second` seconds after the previous accepted
+    /// activation are ignored entirely. This guards destructive actions against bouncing buttons
+    /// and turbo controllers. A rate that is not positive removes the limit.
+    pub fn set_rate_limit(&mut self, input: T, max_per_second: f32) {
+        if max_per_second > 0.0 {
+            let interval =
+                Duration::try_from_secs_f32(1.0 / max_per_second).unwrap_or(Duration::MAX);
+            self.rate_limits.insert(
+                input,
+                RateLimit {
+                    interval,
+                    last_activation: None,
+                },
+            );
+        } else {
+            self.rate_limits.remove(input);
+        }
+    }
+
+    /// Gets the rate limit of a digital input, in activations per second.
+    ///
+    /// Returns `None` if the input is not rate limited.
+    pub fn rate_limit(&self, input: T) -> Option<f32> {
+        self.rate_limits
+            .get(input)
+            .map(|rate_limit| 1.0 / rate_limit.interval.as_secs_f32())
+    }
+
+    /// Removes the rate limit of a digital input.
+    pub fn remove_rate_limit(&mut self, input: T) {
+        self.rate_limits.remove(input);
+    }
+
+    /// Makes a digital input fire repeatedly while it is held, being just activated
+    /// `presses_per_second` times per second.
+    ///
+    /// The input stays activated in between, so only [`Self::just_activated`] and press events
+    /// pulse. Pulses are checked once per update, so rates above the update rate are capped by
+    /// it. A rate that is not positive removes turbo.
+    pub fn set_turbo(&mut self, input: T, presses_per_second: f32) {
+        if presses_per_second > 0.0 {
+            let interval =
+                Duration::try_from_secs_f32(1.0 / presses_per_second).unwrap_or(Duration::MAX);
+            let last_pulse = self
+                .activated_at(input)
+                .map_or_else(Instant::now, |at| at.instant);
+            self.turbos.insert(
+                input,
+                Turbo {
+                    interval,
+                    last_pulse,
+                },
+            );
+        } else {
+            self.turbos.remove(input);
+        }
+    }
+
+    /// Gets the turbo rate of a digital input, in presses per second.
+    ///
+    /// Returns `None` if turbo is off for the input.
+    pub fn turbo(&self, input: T) -> Option<f32> {
+        self.turbos
+            .get(input)
+            .map(|turbo| 1.0 / turbo.interval.as_secs_f32())
+    }
+
+    /// Turns off turbo for a digital input.
+    pub fn remove_turbo(&mut self, input: T) {
+        self.turbos.remove(input);
+    }
+
+    /// Sets how a digital input reacts to being pressed.
+    ///
+    /// An input that is kept activated by its previous mode is released, unless it is held.
+    pub fn set_press_mode(&mut self, input: T, mode: PressMode) {
+        let kept = self.toggled.remove(input) | self.stuck.remove(input);
+        self.sticky_pending.remove(input);
+        if kept && self.held_since(input).is_none() {
+            self.release(input, Instant::now());
+        }
+
+        if mode == PressMode::Hold {
+            self.modes.remove(input);
+        } else {
+            self.modes.insert(input, mode);
+        }
+    }
+
+    /// Gets how a digital input reacts to being pressed.
+    pub fn press_mode(&self, input: T) -> PressMode {
+        self.modes.get(input).copied().unwrap_or_default()
     }
 }
 
 impl<T> DigitalInput<T>
 where
-    T: Hash + Copy + Eq,
+    T: InputKind,
 {
     pub(crate) fn activate(&mut self, input: T) {
-        if !self.activated(input) {
-            self.activated.insert(input);
-            self.just_activated.insert(input);
-            self.just_deactivated.remove(&input);
+        self.activate_at(input, Instant::now());
+    }
+
+    /// Activates an input, as reported by the backend at `time`.
+    pub(crate) fn activate_at(&mut self, input: T, time: Instant) {
+        let input = self.remapped(input);
+        if self.physical.contains_key(input) {
+            return;
+        }
+        self.physical.insert(input, time);
+        self.event_times.insert(
+            input,
+            Timestamp {
+                tick: self.tick,
+                instant: time,
+            },
+        );
+
+        match self.press_mode(input) {
+            PressMode::Hold => {
+                // Sticky inputs held along with this one are used up, and stuck ones are
+                // released along with it.
+                self.sticky_pending.clear();
+                if !self.stuck.is_empty() {
+                    self.unsticking.insert(input);
+                }
+                self.press(input, time);
+            }
+            PressMode::Toggle => {
+                if self.toggled.remove(input) {
+                    self.release_unless_emulated(input, time);
+                } else {
+                    self.toggled.insert(input);
+                    self.press(input, time);
+                }
+            }
+            PressMode::Sticky => {
+                if self.stuck.remove(input) {
+                    self.release_unless_emulated(input, time);
+                } else {
+                    self.sticky_pending.insert(input);
+                    self.press(input, time);
+                }
+            }
         }
     }
 
     pub(crate) fn deactivate(&mut self, input: T) {
-        if self.activated(input) {
-            self.activated.remove(&input);
-            self.just_activated.remove(&input);
-            self.just_deactivated.insert(input);
+        self.deactivate_at(input, Instant::now());
+    }
+
+    /// Deactivates an input, as reported by the backend at `time`.
+    pub(crate) fn deactivate_at(&mut self, input: T, time: Instant) {
+        let input = self.remapped(input);
+        if self.physical.remove(input).is_none() {
+            return;
+        }
+        self.event_times.insert(
+            input,
+            Timestamp {
+                tick: self.tick,
+                instant: time,
+            },
+        );
+
+        match self.press_mode(input) {
+            PressMode::Hold => {
+                self.release_unless_emulated(input, time);
+                if self.unsticking.remove(input) {
+                    let mut stuck = std::mem::take(&mut self.stuck);
+                    for input in stuck.iter() {
+                        self.release_unless_emulated(input, time);
+                    }
+                    stuck.clear();
+                    self.stuck = stuck;
+                    self.unsticking.clear();
+                }
+            }
+            PressMode::Toggle => (),
+            PressMode::Sticky => {
+                if self.sticky_pending.remove(input) {
+                    self.stuck.insert(input);
+                } else if !self.stuck.contains(input) {
+                    self.release_unless_emulated(input, time);
+                }
+            }
+        }
+    }
+
+    fn release_unless_emulated(&mut self, input: T, time: Instant) {
+        if !self.emulated.contains_key(input) {
+            self.release(input, time);
         }
     }
 
+    /// Makes the backend's `from` input activate `to` instead, or itself again if both are equal.
+    ///
+    /// The input that `from` activated until now is released, in case it is held.
+    pub(crate) fn set_remap(&mut self, from: T, to: T) {
+        let previous = self.remapped(from);
+        if previous == to {
+            return;
+        }
+
+        if self.physical.contains_key(previous) {
+            self.deactivate(from);
+        }
+        if from == to {
+            self.remaps.remove(from);
+        } else {
+            self.remaps.insert(from, to);
+        }
+    }
+
+    /// Gets the input that the backend's `from` input activates.
+    pub(crate) fn remapped(&self, from: T) -> T {
+        self.remaps.get(from).copied().unwrap_or(from)
+    }
+
+    /// Holds or releases an input on behalf of an emulation layer, independently of the backend.
+    pub(crate) fn set_emulated(&mut self, input: T, active: bool) {
+        if active {
+            if !self.emulated.contains_key(input) {
+                self.emulated.insert(input, Instant::now());
+                self.press(input, Instant::now());
+            }
+        } else if self.emulated.remove(input).is_some() && !self.physical.contains_key(input) {
+            self.release(input, Instant::now());
+        }
+    }
+
+    /// Keeps an input deactivated while it is suppressed, even if it is held.
+    ///
+    /// A held input is activated again once it is no longer suppressed.
+    pub(crate) fn set_suppressed(&mut self, input: T, suppressed: bool) {
+        if suppressed {
+            if self.suppressed.insert(input) {
+                self.release(input, Instant::now());
+            }
+        } else if self.suppressed.remove(input) && self.held_since(input).is_some() {
+            self.press(input, Instant::now());
+        }
+    }
+
+    /// Releases every input, as if the backend and emulation had released them, such as when
+    /// the device can no longer be read. Toggled and stuck inputs are released as well.
+    ///
+    /// Inputs that are still held stay released until they are pressed again.
+    pub(crate) fn release_all(&mut self) {
+        self.physical.clear();
+        self.emulated.clear();
+        self.toggled.clear();
+        self.sticky_pending.clear();
+        self.stuck.clear();
+        self.unsticking.clear();
+        if !self.activated.is_empty() {
+            for input in (0..T::COUNT).map(T::from_index) {
+                self.release(input, Instant::now());
+            }
+        }
+    }
+
+    /// Keeps every input deactivated while suppressed, releasing the activated ones.
+    ///
+    /// Held inputs are still tracked, but stay deactivated once no longer suppressed, until they
+    /// are pressed again. Toggled and stuck inputs are released for good.
+    pub(crate) fn set_all_suppressed(&mut self, suppressed: bool) {
+        if self.all_suppressed == suppressed {
+            return;
+        }
+        self.all_suppressed = suppressed;
+
+        if suppressed {
+            self.toggled.clear();
+            self.sticky_pending.clear();
+            self.stuck.clear();
+            self.unsticking.clear();
+            if !self.activated.is_empty() {
+                for input in (0..T::COUNT).map(T::from_index) {
+                    self.release(input, Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Gets since when the backend holds an input, ignoring emulation.
+    pub(crate) fn physically_held_since(&self, input: T) -> Option<Instant> {
+        self.physical.get(input).copied()
+    }
+
+    /// Gets since when the backend or emulation holds an input, even if it is suppressed.
+    pub(crate) fn held_since(&self, input: T) -> Option<Instant> {
+        match (self.physical.get(input), self.emulated.get(input)) {
+            (Some(&physical), Some(&emulated)) => Some(physical.min(emulated)),
+            (physical, emulated) => physical.or(emulated).copied(),
+        }
+    }
+
+    /// Gets the edge detection state of an input.
+    fn state(&self, input: T) -> InputState {
+        InputState {
+            activated: self.activated.contains_key(input),
+            just_activated: self.just_activated.contains(input),
+            just_deactivated: self.just_deactivated.contains(input),
+            latched: self.latched.contains(input),
+        }
+    }
+
+    /// Stores the edge detection state of an input, apart from whether it is activated, which
+    /// the callers store along with the time.
+    fn set_state(&mut self, input: T, state: InputState) {
+        for (set, contained) in [
+            (&mut self.just_activated, state.just_activated),
+            (&mut self.just_deactivated, state.just_deactivated),
+            (&mut self.latched, state.latched),
+        ] {
+            if contained {
+                set.insert(input);
+            } else {
+                set.remove(input);
+            }
+        }
+    }
+
+    fn press(&mut self, input: T, time: Instant) {
+        if !self.activated(input) {
+            if self.suppressed.contains(input) || self.all_suppressed {
+                return;
+            }
+            if let Some(rate_limit) = self.rate_limits.get_mut(input) {
+                if !rate_limit.try_activate() {
+                    return;
+                }
+            }
+        }
+
+        let mut state = self.state(input);
+        let activated = state.activate();
+        self.set_state(input, state);
+        if activated {
+            let now = Timestamp::now(self.tick);
+            if let Some(previous) = self.last_activations.insert(input, now.instant) {
+                self.previous_activations.insert(input, previous);
+            }
+
+            if let Some(turbo) = self.turbos.get_mut(input) {
+                turbo.last_pulse = now.instant;
+            }
+
+            self.activated.insert(input, now);
+            self.edges.push(Edge {
+                input,
+                activated: true,
+                time,
+            });
+        }
+    }
+
+    fn release(&mut self, input: T, time: Instant) {
+        let mut state = self.state(input);
+        let deactivated = state.deactivate(self.latching);
+        self.set_state(input, state);
+        if deactivated {
+            self.finish_release(input, time);
+        }
+    }
+
+    fn finish_release(&mut self, input: T, time: Instant) {
+        if let Some(activated_at) = self.activated.remove(input) {
+            self.press_durations
+                .insert(input, activated_at.instant.elapsed());
+        }
+        self.edges.push(Edge {
+            input,
+            activated: false,
+            time,
+        });
+    }
+
     pub(crate) fn update(&mut self) {
+        // Inputs that are not latched only forget their edges, so the sets are cleared at once.
         self.just_activated.clear();
         self.just_deactivated.clear();
+        self.edges.clear();
+        self.tick += 1;
+
+        // The set is put back afterwards, so that it stays allocated.
+        let mut latched = std::mem::take(&mut self.latched);
+        for input in latched.iter() {
+            let mut state = InputState {
+                latched: true,
+                ..self.state(input)
+            };
+            let deactivated = state.update();
+            self.set_state(input, state);
+            if deactivated {
+                self.finish_release(input, Instant::now());
+            }
+        }
+        latched.clear();
+        self.latched = latched;
+
+        self.pulse_turbos();
+    }
+
+    /// Activates held turbo inputs again once their interval has passed.
+    fn pulse_turbos(&mut self) {
+        if self.turbos.is_empty() {
+            return;
+        }
+
+        let now = Instant::now();
+        for input in (0..T::COUNT).map(T::from_index) {
+            let Some(turbo) = self.turbos.get_mut(input) else {
+                continue;
+            };
+            if self.activated.contains_key(input) && now - turbo.last_pulse >= turbo.interval {
+                turbo.last_pulse = now;
+                self.just_activated.insert(input);
+                self.edges.push(Edge {
+                    input,
+                    activated: true,
+                    time: now,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn any_activated(&self) -> bool {
+        !self.activated.is_empty()
+    }
+
+    pub(crate) fn activated_inputs(&self) -> impl Iterator<Item = T> + '_ {
+        self.activated.keys()
+    }
+
+    pub(crate) fn just_activated_inputs(&self) -> impl Iterator<Item = T> + '_ {
+        self.just_activated.iter()
+    }
+
+    /// Gets the activations and deactivations since the last update, in the order they happened.
+    pub(crate) fn edges(&self) -> &[Edge<T>] {
+        &self.edges
+    }
+
+    pub(crate) fn just_deactivated_inputs(&self) -> impl Iterator<Item = T> + '_ {
+        self.just_deactivated.iter()
+    }
+
+    /// Gets when an input was last activated, even if it has been released since.
+    pub(crate) fn last_activated_at(&self, input: T) -> Option<Instant> {
+        self.last_activations.get(input).copied()
     }
 }
 
-impl<T> Default for DigitalInput<T> {
+impl<T: InputKind> Default for DigitalInput<T> {
     fn default() -> Self {
         Self {
             activated: Default::default(),
+            physical: Default::default(),
+            emulated: Default::default(),
+            suppressed: Default::default(),
+            event_times: Default::default(),
             just_activated: Default::default(),
             just_deactivated: Default::default(),
+            edges: Vec::new(),
+            rate_limits: Default::default(),
+            turbos: Default::default(),
+            remaps: Default::default(),
+
+            modes: Default::default(),
+            toggled: Default::default(),
+            sticky_pending: Default::default(),
+            stuck: Default::default(),
+            unsticking: Default::default(),
+
+            last_activations: Default::default(),
+            previous_activations: Default::default(),
+            press_durations: Default::default(),
+            hold_threshold: DEFAULT_HOLD_THRESHOLD,
+
+            latched: Default::default(),
+            latching: false,
+
+            all_suppressed: false,
+
+            tick: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fishsticks_core::digital::DigitalState;
+
+    /// Taps, holds and releases, with `None` starting a new update.
+    const STEPS: [Option<(u8, bool)>; 14] = [
+        Some((0, true)),
+        None,
+        Some((0, false)),
+        Some((1, true)),
+        Some((1, false)),
+        None,
+        Some((2, true)),
+        Some((2, false)),
+        Some((2, true)),
+        None,
+        Some((2, false)),
+        None,
+        Some((3, false)),
+        None,
+    ];
+
+    fn assert_in_step(input: &DigitalInput<u8>, state: &DigitalState) {
+        for index in 0..4 {
+            assert_eq!(input.activated(index), state.activated(index.into()));
+            assert_eq!(
+                input.just_activated(index),
+                state.just_activated(index.into())
+            );
+            assert_eq!(
+                input.just_deactivated(index),
+                state.just_deactivated(index.into())
+            );
+        }
+    }
+
+    #[test]
+    fn edges_follow_the_core_crate() {
+        for latching in [false, true] {
+            let mut input = DigitalInput::<u8>::default();
+            let mut state = DigitalState::new();
+            input.set_latching(latching);
+            state.set_latching(latching);
+
+            for step in STEPS {
+                match step {
+                    Some((index, true)) => {
+                        input.activate(index);
+                        state.activate(index.into());
+                    }
+                    Some((index, false)) => {
+                        input.deactivate(index);
+                        state.deactivate(index.into());
+                    }
+                    None => {
+                        input.update();
+                        state.update();
+                    }
+                }
+                assert_in_step(&input, &state);
+            }
         }
     }
 }
@@ -0,0 +1,145 @@
+//! Directions combined from four directional buttons, such as the D-pad.
+
+use crate::digital::DigitalInput;
+use crate::raw::{HatDirection, RawHat, RawInputs};
+use crate::storage::InputKind;
+use crate::Button;
+use std::cmp::Ordering;
+use std::time::Instant;
+
+/// One of eight directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction8 {
+    /// Up.
+    Up,
+    /// Up and right.
+    UpRight,
+    /// Right.
+    Right,
+    /// Down and right.
+    DownRight,
+    /// Down.
+    Down,
+    /// Down and left.
+    DownLeft,
+    /// Left.
+    Left,
+    /// Up and left.
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Gets the direction as `(x, y)`, where each component is `-1`, `0` or `1`.
+    ///
+    /// Like the stick axes, `y` is positive downwards.
+    pub fn to_vector(self) -> (i8, i8) {
+        match self {
+            Direction8::Up => (0, -1),
+            Direction8::UpRight => (1, -1),
+            Direction8::Right => (1, 0),
+            Direction8::DownRight => (1, 1),
+            Direction8::Down => (0, 1),
+            Direction8::DownLeft => (-1, 1),
+            Direction8::Left => (-1, 0),
+            Direction8::UpLeft => (-1, -1),
+        }
+    }
+
+    /// Gets the direction from `(x, y)`, where `y` is positive downwards.
+    ///
+    /// Returns `None` for `(0, 0)`. Only the sign of each component matters.
+    pub fn from_vector(x: i8, y: i8) -> Option<Self> {
+        match (x.signum(), y.signum()) {
+            (0, -1) => Some(Direction8::Up),
+            (1, -1) => Some(Direction8::UpRight),
+            (1, 0) => Some(Direction8::Right),
+            (1, 1) => Some(Direction8::DownRight),
+            (0, 1) => Some(Direction8::Down),
+            (-1, 1) => Some(Direction8::DownLeft),
+            (-1, 0) => Some(Direction8::Left),
+            (-1, -1) => Some(Direction8::UpLeft),
+            _ => None,
+        }
+    }
+}
+
+/// How to resolve opposing directions that are held at the same time (SOCD).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Socd {
+    /// Opposing directions cancel out.
+    #[default]
+    Neutral,
+    /// The direction that was activated last wins.
+    LastWins,
+    /// The direction that was activated first wins.
+    FirstWins,
+}
+
+impl Socd {
+    /// Resolves two opposing inputs, given when each was activated, to `-1`, `0` or `1`.
+    pub(crate) fn resolve(self, negative: Option<Instant>, positive: Option<Instant>) -> i8 {
+        match (negative, positive) {
+            (Some(_), None) => -1,
+            (None, Some(_)) => 1,
+            (Some(negative), Some(positive)) => match self {
+                Socd::Neutral => 0,
+                Socd::LastWins => ordering_sign(positive.cmp(&negative)),
+                Socd::FirstWins => ordering_sign(negative.cmp(&positive)),
+            },
+            (None, None) => 0,
+        }
+    }
+}
+
+fn ordering_sign(ordering: Ordering) -> i8 {
+    match ordering {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// Combines four directional inputs into a direction.
+fn direction<T: InputKind>(
+    inputs: &DigitalInput<T>,
+    [up, down, left, right]: [T; 4],
+    socd: Socd,
+) -> Option<Direction8> {
+    let activated_at = |input| inputs.activated_at(input).map(|at| at.instant);
+    let x = socd.resolve(activated_at(left), activated_at(right));
+    let y = socd.resolve(activated_at(up), activated_at(down));
+    Direction8::from_vector(x, y)
+}
+
+impl DigitalInput<Button> {
+    /// Gets the direction held on the D-pad, resolving opposing directions with `socd`.
+    ///
+    /// Returns `None` if no direction is held, or if opposing directions cancel out.
+    pub fn dpad_direction(&self, socd: Socd) -> Option<Direction8> {
+        let buttons = [
+            Button::DPadUp,
+            Button::DPadDown,
+            Button::DPadLeft,
+            Button::DPadRight,
+        ];
+        direction(self, buttons, socd)
+    }
+}
+
+impl RawInputs {
+    /// Gets the direction held on a hat switch, resolving opposing directions with `socd`.
+    ///
+    /// Returns `None` if no direction is held, or if opposing directions cancel out.
+    pub fn hat_direction(&self, hat: u8, socd: Socd) -> Option<Direction8> {
+        let direction = |direction| RawHat { hat, direction };
+        let hats = [
+            direction(HatDirection::Up),
+            direction(HatDirection::Down),
+            direction(HatDirection::Left),
+            direction(HatDirection::Right),
+        ];
+        self::direction(&self.hats, hats, socd)
+    }
+}
@@ -0,0 +1,156 @@
+//! Detection of sticks that drift, resting off-center, as they do on aging controllers.
+//!
+//! Enable it with [`GamepadContext::set_drift_detection`](crate::GamepadContext::set_drift_detection)
+//! or [`Gamepad::set_drift_detection`](crate::Gamepad::set_drift_detection). Drifting axes are
+//! reported with [`GamepadEvent::DriftDetected`](crate::event::GamepadEvent::DriftDetected).
+
+use crate::analog::AnalogInput;
+use crate::Axis;
+use std::time::{Duration, Instant};
+
+/// The stick axes, which are the ones that drift.
+const STICK_AXES: [Axis; 4] = [Axis::LeftX, Axis::LeftY, Axis::RightX, Axis::RightY];
+
+/// How long an axis rests off-center by default before it is reported.
+const DEFAULT_DURATION: Duration = Duration::from_secs(10);
+
+/// How far off-center an axis has to rest by default to drift.
+const DEFAULT_MIN_OFFSET: f32 = 0.05;
+
+/// How far off-center an axis can be while resting. Further, a player is holding the stick.
+const MAX_OFFSET: f32 = 0.3;
+
+/// How much a resting axis can move, from noise.
+const TOLERANCE: f32 = 0.02;
+
+/// The rest of a single axis.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Rest {
+    since: Option<Instant>,
+    min: f32,
+    max: f32,
+    // The drift that was last reported.
+    offset: Option<f32>,
+}
+
+/// Notices stick axes that rest off-center for a long time, and optionally corrects them.
+///
+/// An axis drifts when it rests at the same spot off-center, within the noise of the stick, for
+/// as long as [`Self::with_duration`]. Players rarely hold a stick that still for that long.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftDetector {
+    duration: Duration,
+    min_offset: f32,
+    correction: bool,
+    rests: [Rest; STICK_AXES.len()],
+}
+
+impl DriftDetector {
+    /// Creates a detector that reports axes resting at least `0.05` off-center for 10 seconds,
+    /// without correcting them.
+    pub fn new() -> Self {
+        Self {
+            duration: DEFAULT_DURATION,
+            min_offset: DEFAULT_MIN_OFFSET,
+            correction: false,
+            rests: Default::default(),
+        }
+    }
+
+    /// Sets how long an axis has to rest off-center before it is reported.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets how far off-center an axis has to rest to drift. Clamped to `[0.0, 0.3]`, since
+    /// axes further off-center are held rather than resting.
+    pub fn with_min_offset(mut self, offset: f32) -> Self {
+        if offset.is_finite() {
+            self.min_offset = offset.clamp(0.0, MAX_OFFSET);
+        }
+        self
+    }
+
+    /// Sets whether drifting axes are centered again, with
+    /// [`AxisConfig::center`](crate::analog::AxisConfig::center), once they are reported.
+    pub fn with_correction(mut self, enabled: bool) -> Self {
+        self.correction = enabled;
+        self
+    }
+
+    /// Checks if drifting axes are centered again.
+    pub fn correction(&self) -> bool {
+        self.correction
+    }
+
+    /// Gets how far off-center an axis drifts, if it was reported as drifting and has not rested
+    /// at the center since.
+    pub fn offset(&self, axis: Axis) -> Option<f32> {
+        let index = STICK_AXES.iter().position(|&other| other == axis)?;
+        self.rests[index].offset
+    }
+
+    /// Reads the raw values of the axes, and gets the axes that were found drifting with how
+    /// far off-center they rest, and the axes that rest at the center again with `None`.
+    pub(crate) fn update(
+        &mut self,
+        analog_inputs: &AnalogInput<Axis>,
+        now: Instant,
+    ) -> Vec<(Axis, Option<f32>)> {
+        let mut changes = Vec::new();
+        for (rest, axis) in self.rests.iter_mut().zip(STICK_AXES) {
+            let value = analog_inputs.raw_value(axis);
+            if value.abs() > MAX_OFFSET {
+                rest.since = None;
+                continue;
+            }
+
+            let min = rest.min.min(value);
+            let max = rest.max.max(value);
+            match rest.since {
+                Some(_) if max - min <= TOLERANCE => {
+                    rest.min = min;
+                    rest.max = max;
+                }
+                // Moving further than noise starts a new rest.
+                _ => {
+                    *rest = Rest {
+                        since: Some(now),
+                        min: value,
+                        max: value,
+                        offset: rest.offset,
+                    };
+                    continue;
+                }
+            }
+
+            if rest
+                .since
+                .is_none_or(|since| now.duration_since(since) < self.duration)
+            {
+                continue;
+            }
+            let offset = (rest.min + rest.max) / 2.0;
+            if offset.abs() >= self.min_offset {
+                if rest
+                    .offset
+                    .is_none_or(|reported| (reported - offset).abs() > TOLERANCE)
+                {
+                    rest.offset = Some(offset);
+                    changes.push((axis, Some(offset)));
+                }
+            } else if rest.offset.take().is_some() {
+                changes.push((axis, None));
+            }
+        }
+
+        changes
+    }
+}
+
+impl Default for DriftDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,400 @@
+//! Structured dumps of the full state of gamepads, for bug reports and tests.
+//!
+//! With the `serde` feature, dumps can be serialized, attached to a bug report, and restored
+//! into a context created with
+//! [`GamepadContext::init_without_devices`](crate::GamepadContext::init_without_devices).
+//! [`Diagnostics`] reports describe the setup of the gamepads instead, for triaging players'
+//! reports of controllers that do not work.
+
+use crate::analog::AnalogInputValue;
+use crate::event::GamepadEvent;
+use crate::glyph::ControllerType;
+use crate::raw::RawHat;
+use crate::sensor::Sensor;
+use crate::touchpad::{Finger, FingerId};
+use crate::{Axis, Button, Gamepad, GamepadContext, GamepadId, Guid, AXES, BUTTONS};
+use std::fmt;
+use std::time::Instant;
+
+/// The state of every gamepad of a context, as returned by [`GamepadContext::dump_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDump {
+    /// The name of the backend, such as `"sdl2"`, or the names of merged backends joined with
+    /// `+`, such as `"sdl2+gilrs"`.
+    pub backend: String,
+    /// The gamepads, ordered by Id.
+    pub gamepads: Vec<GamepadDump>,
+}
+
+/// The state of a single gamepad.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadDump {
+    /// The Id of the gamepad.
+    pub id: GamepadId,
+    /// The GUID of the gamepad.
+    pub guid: Guid,
+    /// The controller type of the gamepad.
+    pub controller_type: ControllerType,
+    /// Whether the gamepad is a raw joystick.
+    pub raw: bool,
+    /// The activated buttons.
+    pub buttons: Vec<Button>,
+    /// The axes that have been read.
+    pub axes: Vec<AxisDump>,
+    /// The activated numbered buttons.
+    pub raw_buttons: Vec<u8>,
+    /// The numbered axes that have been read, with their raw values.
+    pub raw_axes: Vec<(u8, f32)>,
+    /// The activated hat switch directions.
+    pub raw_hats: Vec<RawHat>,
+    /// The fingers touching a touchpad.
+    pub fingers: Vec<(FingerId, Finger)>,
+    /// The latest gyroscope reading.
+    pub gyroscope: Option<[f32; 3]>,
+    /// The latest accelerometer reading.
+    pub accelerometer: Option<[f32; 3]>,
+    /// The analog deadzone shared by all axes.
+    pub deadzone: f32,
+    /// The digital deadzone shared by all axes.
+    pub digital_deadzone: f32,
+}
+
+/// The state of a single axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisDump {
+    /// The axis.
+    pub axis: Axis,
+    /// The value reported by the backend.
+    pub raw: f32,
+    /// The value after configuration and deadzone remapping.
+    pub value: f32,
+}
+
+impl GamepadDump {
+    /// Captures the state of a gamepad.
+    pub fn capture(id: GamepadId, gamepad: &Gamepad) -> Self {
+        let analog_inputs = &gamepad.analog_inputs;
+        let raw_inputs = &gamepad.raw_inputs;
+
+        Self {
+            id,
+            guid: gamepad.guid(),
+            controller_type: gamepad.controller_type(),
+            raw: gamepad.is_raw(),
+            buttons: gamepad.digital_inputs.activated_inputs().collect(),
+            axes: analog_inputs
+                .raw_values()
+                .map(|(axis, raw)| AxisDump {
+                    axis,
+                    raw,
+                    value: analog_inputs.value(axis),
+                })
+                .collect(),
+            raw_buttons: raw_inputs.buttons.activated_inputs().collect(),
+            raw_axes: raw_inputs.axes.raw_values().collect(),
+            raw_hats: raw_inputs.hats.activated_inputs().collect(),
+            fingers: gamepad.touchpad_inputs.fingers().collect(),
+            gyroscope: gamepad.sensors().gyroscope(),
+            accelerometer: gamepad.sensors().accelerometer(),
+            deadzone: analog_inputs.deadzone(),
+            digital_deadzone: analog_inputs.digital_deadzone(),
+        }
+    }
+
+    /// Creates a gamepad without a device, holding the dumped inputs.
+    ///
+    /// Virtual buttons are skipped, since they are computed from the axes.
+    fn restore(&self) -> Gamepad {
+        let mut gamepad = Gamepad::new(None, self.guid);
+        gamepad.raw = self.raw;
+        gamepad.set_deadzone(self.deadzone);
+        gamepad.set_digital_deadzone(self.digital_deadzone);
+
+        for &button in &self.buttons {
+            if !matches!(button, Button::Virtual(_)) {
+                gamepad.digital_inputs.activate(button);
+            }
+        }
+        for axis in &self.axes {
+            gamepad
+                .analog_inputs
+                .set(axis.axis, AnalogInputValue::from(axis.raw));
+        }
+        for &button in &self.raw_buttons {
+            gamepad.raw_inputs.buttons.activate(button);
+        }
+        for &(axis, value) in &self.raw_axes {
+            gamepad
+                .raw_inputs
+                .axes
+                .set(axis, AnalogInputValue::from(value));
+        }
+        for &hat in &self.raw_hats {
+            gamepad.raw_inputs.hats.activate(hat);
+        }
+        for &(id, finger) in &self.fingers {
+            gamepad.touchpad_inputs.set(id, Some(finger));
+        }
+        gamepad.sensors.set(Sensor::Gyroscope, self.gyroscope);
+        gamepad
+            .sensors
+            .set(Sensor::Accelerometer, self.accelerometer);
+
+        gamepad
+    }
+}
+
+impl GamepadContext {
+    /// Captures the state of every gamepad, such as to attach it to a bug report.
+    pub fn dump_state(&self) -> StateDump {
+        let mut gamepads: Vec<_> = self
+            .gamepads()
+            .map(|(id, gamepad)| GamepadDump::capture(id, gamepad))
+            .collect();
+        gamepads.sort_by_key(|gamepad| gamepad.id);
+
+        StateDump {
+            backend: self
+                .backends()
+                .iter()
+                .map(|backend| backend.name())
+                .collect::<Vec<_>>()
+                .join("+"),
+            gamepads,
+        }
+    }
+
+    /// Adds the gamepads of a dump to the context, replacing gamepads with the same Ids.
+    ///
+    /// Restored gamepads have no device, so LEDs, rumble and sensors cannot be controlled.
+    /// Meant for reproducing bug reports in tests, with a context created with
+    /// [`Self::init_without_devices`]. The gamepads are reported as connected on the next update.
+    pub fn restore_state(&mut self, dump: &StateDump) {
+        for gamepad in &dump.gamepads {
+            self.gamepads.insert(gamepad.id, gamepad.restore());
+        }
+    }
+}
+
+/// A report of the setup of every gamepad of a context, as returned by
+/// [`GamepadContext::diagnostics`].
+///
+/// Shown as text, or serialized with any serde format, such as JSON, with the `serde` feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// The version of this crate.
+    pub version: String,
+    /// The name of the backend, like [`StateDump::backend`].
+    pub backend: String,
+    /// The gamepads, ordered by Id.
+    pub gamepads: Vec<GamepadDiagnostics>,
+    /// The gamepads that wait to join, because the maximum number of gamepads is reached.
+    pub waiting: Vec<GamepadId>,
+    /// The last events other than axis moves, from the oldest.
+    pub recent_events: Vec<EventDiagnostics>,
+}
+
+/// The setup of a single gamepad.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadDiagnostics {
+    /// The Id of the gamepad.
+    pub id: GamepadId,
+    /// The GUID of the gamepad.
+    pub guid: Guid,
+    /// The name of the gamepad.
+    pub name: Option<String>,
+    /// The serial number of the gamepad.
+    pub serial: Option<String>,
+    /// The backend that reports the gamepad.
+    pub backend: String,
+    /// The USB vendor ID of the gamepad.
+    pub vendor_id: Option<u16>,
+    /// The USB product ID of the gamepad.
+    pub product_id: Option<u16>,
+    /// The controller type of the gamepad.
+    pub controller_type: ControllerType,
+    /// Whether the gamepad is a raw joystick, without a controller mapping.
+    pub raw: bool,
+    /// Buttons that report as other buttons.
+    pub button_remaps: Vec<(Button, Button)>,
+    /// Axes that report as other axes.
+    pub axis_remaps: Vec<(Axis, Axis)>,
+    /// The analog deadzone shared by all axes.
+    pub deadzone: f32,
+    /// Axes with their own analog deadzone.
+    pub axis_deadzones: Vec<(Axis, f32)>,
+    /// The digital deadzone shared by all axes.
+    pub digital_deadzone: f32,
+    /// Whether the gamepad is suppressed.
+    pub suppressed: bool,
+}
+
+/// An event, as shown in [`Diagnostics`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventDiagnostics {
+    /// How many seconds before the report the event was queued.
+    pub seconds_ago: f32,
+    /// A description of the event.
+    pub event: String,
+}
+
+impl GamepadDiagnostics {
+    fn capture(context: &GamepadContext, id: GamepadId, gamepad: &Gamepad) -> Self {
+        let guid = gamepad.guid();
+        Self {
+            id,
+            guid,
+            name: gamepad.name().map(str::to_owned),
+            serial: gamepad.serial().map(str::to_owned),
+            backend: context
+                .gamepad_backend(id)
+                .map_or_else(|| "unknown".to_owned(), |backend| backend.to_string()),
+            vendor_id: guid.vendor_id(),
+            product_id: guid.product_id(),
+            controller_type: gamepad.controller_type(),
+            raw: gamepad.is_raw(),
+            button_remaps: BUTTONS
+                .into_iter()
+                .map(|button| (button, gamepad.remapped(button)))
+                .filter(|(from, to)| from != to)
+                .collect(),
+            axis_remaps: AXES
+                .into_iter()
+                .map(|axis| (axis, gamepad.remapped_axis(axis)))
+                .filter(|(from, to)| from != to)
+                .collect(),
+            deadzone: gamepad.analog_inputs.deadzone(),
+            axis_deadzones: AXES
+                .into_iter()
+                .filter_map(|axis| Some((axis, gamepad.analog_inputs.deadzone_override(axis)?)))
+                .collect(),
+            digital_deadzone: gamepad.analog_inputs.digital_deadzone(),
+            suppressed: gamepad.is_suppressed(),
+        }
+    }
+}
+
+impl EventDiagnostics {
+    fn capture(event: GamepadEvent, queued: Instant, now: Instant) -> Self {
+        let event = match event {
+            GamepadEvent::Connected(id) => format!("{:?} connected", id),
+            GamepadEvent::Disconnected(id) => format!("{:?} disconnected", id),
+            GamepadEvent::Ignored(id) => format!("{:?} ignored", id),
+            GamepadEvent::Queued(id) => format!("{:?} queued", id),
+            GamepadEvent::Replaced { old, new } => format!("{:?} replaced {:?}", new, old),
+            GamepadEvent::ButtonPressed { id, button, .. } => {
+                format!("{:?} pressed {:?}", id, button)
+            }
+            GamepadEvent::ButtonReleased { id, button, .. } => {
+                format!("{:?} released {:?}", id, button)
+            }
+            GamepadEvent::DriftDetected { id, axis, offset } => {
+                format!("{:?} drifts on {:?} by {}", id, axis, offset)
+            }
+            GamepadEvent::AxisMoved {
+                id, axis, value, ..
+            } => format!("{:?} moved {:?} to {}", id, axis, value),
+        };
+        Self {
+            seconds_ago: now.duration_since(queued).as_secs_f32(),
+            event,
+        }
+    }
+}
+
+impl GamepadContext {
+    /// Reports the setup of every gamepad, such as to attach it to a player's bug report about
+    /// a controller that does not work.
+    ///
+    /// Unlike [`Self::dump_state`], the report covers names, backends, remaps, deadzones and
+    /// the last events rather than the inputs that are held.
+    pub fn diagnostics(&self) -> Diagnostics {
+        let mut gamepads: Vec<_> = self
+            .gamepads()
+            .map(|(id, gamepad)| GamepadDiagnostics::capture(self, id, gamepad))
+            .collect();
+        gamepads.sort_by_key(|gamepad| gamepad.id);
+        let now = Instant::now();
+
+        Diagnostics {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            backend: self.dump_state().backend,
+            gamepads,
+            waiting: self.waiting_gamepads().collect(),
+            recent_events: self
+                .events
+                .recent()
+                .map(|(event, queued)| EventDiagnostics::capture(event, queued, now))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "fishsticks {} ({})", self.version, self.backend)?;
+        for gamepad in &self.gamepads {
+            writeln!(
+                f,
+                "{:?} \"{}\" ({}), GUID {}, {:?}{}",
+                gamepad.id,
+                gamepad.name.as_deref().unwrap_or("unnamed"),
+                gamepad.backend,
+                gamepad.guid,
+                gamepad.controller_type,
+                if gamepad.raw { ", raw" } else { "" }
+            )?;
+            writeln!(
+                f,
+                "  deadzone {}, digital deadzone {}",
+                gamepad.deadzone, gamepad.digital_deadzone
+            )?;
+            for (axis, deadzone) in &gamepad.axis_deadzones {
+                writeln!(f, "  deadzone of {:?}: {}", axis, deadzone)?;
+            }
+            for (from, to) in &gamepad.button_remaps {
+                writeln!(f, "  {:?} reports as {:?}", from, to)?;
+            }
+            for (from, to) in &gamepad.axis_remaps {
+                writeln!(f, "  {:?} reports as {:?}", from, to)?;
+            }
+            if gamepad.suppressed {
+                writeln!(f, "  suppressed")?;
+            }
+        }
+        for id in &self.waiting {
+            writeln!(f, "{:?} waits to join", id)?;
+        }
+        for event in &self.recent_events {
+            writeln!(f, "{:.3}s ago: {}", event.seconds_ago, event.event)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Shows the inputs of the gamepad, like its [`GamepadDump`].
+impl fmt::Debug for Gamepad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dump = GamepadDump::capture(GamepadId(0), self);
+        f.debug_struct("Gamepad")
+            .field("guid", &dump.guid)
+            .field("controller_type", &dump.controller_type)
+            .field("raw", &dump.raw)
+            .field("buttons", &dump.buttons)
+            .field("axes", &dump.axes)
+            .field("raw_buttons", &dump.raw_buttons)
+            .field("raw_axes", &dump.raw_axes)
+            .field("raw_hats", &dump.raw_hats)
+            .field("fingers", &dump.fingers)
+            .field("gyroscope", &dump.gyroscope)
+            .field("accelerometer", &dump.accelerometer)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,394 @@
+//! Gamepad events, as an alternative to polling the state of every input.
+
+use crate::storage::{InputKind, InputSet};
+use crate::{Axis, Button, Gamepad, GamepadId, AXES};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+/// A change to the gamepads of a context.
+///
+/// The changes of a single update are ordered by the time they happened, as reported by the
+/// operating system where the backend provides it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(bevy_ecs::event::Event))]
+pub enum GamepadEvent {
+    /// A gamepad was connected.
+    Connected(GamepadId),
+    /// A gamepad was disconnected.
+    Disconnected(GamepadId),
+    /// A gamepad connected while the maximum number of gamepads was reached, and is ignored.
+    ///
+    /// See [`JoinPolicy::Ignore`](crate::admission::JoinPolicy::Ignore).
+    Ignored(GamepadId),
+    /// A gamepad connected while the maximum number of gamepads was reached, and waits to join.
+    /// It is reported as connected once it joins.
+    ///
+    /// See [`JoinPolicy::Queue`](crate::admission::JoinPolicy::Queue).
+    Queued(GamepadId),
+    /// A gamepad connected while the maximum number of gamepads was reached, and replaced an
+    /// idle gamepad, which now waits to join again.
+    ///
+    /// Followed by the disconnection of `old` and the connection of `new`.
+    /// See [`JoinPolicy::ReplaceIdle`](crate::admission::JoinPolicy::ReplaceIdle).
+    Replaced {
+        /// The idle gamepad that was replaced.
+        old: GamepadId,
+        /// The gamepad that took its place.
+        new: GamepadId,
+    },
+    /// A button was activated.
+    ButtonPressed {
+        /// The gamepad the button belongs to.
+        id: GamepadId,
+        /// The button that was activated.
+        button: Button,
+        /// When the button was activated.
+        time: Instant,
+    },
+    /// A button was deactivated.
+    ButtonReleased {
+        /// The gamepad the button belongs to.
+        id: GamepadId,
+        /// The button that was deactivated.
+        button: Button,
+        /// When the button was deactivated.
+        time: Instant,
+    },
+    /// A stick axis was found resting off-center, as reported by a
+    /// [`DriftDetector`](crate::drift::DriftDetector).
+    DriftDetected {
+        /// The gamepad the axis belongs to.
+        id: GamepadId,
+        /// The axis that drifts.
+        axis: Axis,
+        /// Where the axis rests, before the config.
+        offset: f32,
+    },
+    /// An axis changed value.
+    AxisMoved {
+        /// The gamepad the axis belongs to.
+        id: GamepadId,
+        /// The axis that changed.
+        axis: Axis,
+        /// The new value, after deadzone remapping.
+        value: f32,
+        /// When the axis changed.
+        time: Instant,
+    },
+}
+
+impl GamepadEvent {
+    /// Gets when an input event happened. Connections and other changes to the gamepads
+    /// themselves have no time.
+    ///
+    /// See [`DigitalInput::last_event_time`](crate::DigitalInput::last_event_time).
+    pub fn time(&self) -> Option<Instant> {
+        match *self {
+            Self::Connected(_)
+            | Self::Disconnected(_)
+            | Self::Ignored(_)
+            | Self::Queued(_)
+            | Self::Replaced { .. }
+            | Self::DriftDetected { .. } => None,
+            Self::ButtonPressed { time, .. }
+            | Self::ButtonReleased { time, .. }
+            | Self::AxisMoved { time, .. } => Some(time),
+        }
+    }
+}
+
+/// Whether a button was pressed or released, as passed to the callbacks of
+/// [`GamepadContext::observe`](crate::GamepadContext::observe).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ButtonEdge {
+    /// The button was activated.
+    Pressed,
+    /// The button was deactivated.
+    Released,
+}
+
+/// The number of recent events kept for diagnostics.
+const RECENT_EVENTS: usize = 64;
+
+/// Queue of the events of the last update.
+#[derive(Debug, Default)]
+pub(crate) struct EventQueue {
+    events: VecDeque<GamepadEvent>,
+    // The state of every connected gamepad, as reported by events so far.
+    reported: HashMap<GamepadId, Reported>,
+    // Input events of the update, before they are sorted by time.
+    inputs: Vec<GamepadEvent>,
+    // Events that are not derived from the state of the gamepads, queued before the others.
+    pending: Vec<GamepadEvent>,
+    // The last events other than axis moves, with when they were queued, for diagnostics.
+    recent: VecDeque<(GamepadEvent, Instant)>,
+}
+
+/// The state of a gamepad as reported by events, so that every press gets a release.
+#[derive(Debug, Default)]
+struct Reported {
+    buttons: InputSet<Button>,
+    axes: [f32; Axis::COUNT],
+}
+
+impl EventQueue {
+    /// Replaces the queued events with the changes of the last update.
+    ///
+    /// Buttons are reported as they were pressed and released, so a tap within a single update
+    /// is reported as a press and a release. A release is only reported for a reported press.
+    /// Buttons that are no longer activated are reported as released, and axes that moved as
+    /// moved, even if their state was reset without an edge in this update, such as by
+    /// disconnecting.
+    pub(crate) fn update(&mut self, gamepads: &HashMap<GamepadId, Gamepad>) {
+        self.events.clear();
+        self.events.extend(self.pending.drain(..));
+        let now = Instant::now();
+
+        let disconnected: Vec<_> = self
+            .reported
+            .keys()
+            .copied()
+            .filter(|id| !gamepads.contains_key(id))
+            .collect();
+        for id in disconnected {
+            let Some(reported) = self.reported.remove(&id) else {
+                continue;
+            };
+            for button in reported.buttons.iter() {
+                self.events.push_back(GamepadEvent::ButtonReleased {
+                    id,
+                    button,
+                    time: now,
+                });
+            }
+            for (index, &value) in reported.axes.iter().enumerate() {
+                if value != 0.0 {
+                    self.events.push_back(GamepadEvent::AxisMoved {
+                        id,
+                        axis: Axis::from_index(index),
+                        value: 0.0,
+                        time: now,
+                    });
+                }
+            }
+            self.events.push_back(GamepadEvent::Disconnected(id));
+        }
+
+        for (&id, gamepad) in gamepads {
+            let reported = self.reported.entry(id).or_insert_with(|| {
+                self.events.push_back(GamepadEvent::Connected(id));
+                Reported::default()
+            });
+
+            let digital_inputs = &gamepad.digital_inputs;
+            for edge in digital_inputs.edges() {
+                let (button, time) = (edge.input, edge.time);
+                if edge.activated {
+                    reported.buttons.insert(button);
+                    self.inputs
+                        .push(GamepadEvent::ButtonPressed { id, button, time });
+                } else if reported.buttons.remove(button) {
+                    self.inputs
+                        .push(GamepadEvent::ButtonReleased { id, button, time });
+                }
+            }
+
+            // Buttons reset without an edge, such as by replacing the gamepad.
+            let start = self.inputs.len();
+            for button in reported.buttons.iter() {
+                if !digital_inputs.activated(button) {
+                    self.inputs.push(GamepadEvent::ButtonReleased {
+                        id,
+                        button,
+                        time: now,
+                    });
+                }
+            }
+            for event in &self.inputs[start..] {
+                if let GamepadEvent::ButtonReleased { button, .. } = *event {
+                    reported.buttons.remove(button);
+                }
+            }
+
+            let analog_inputs = &gamepad.analog_inputs;
+            for &axis in AXES.iter() {
+                let value = analog_inputs.value(axis);
+                let changed = analog_inputs
+                    .last_changed(axis)
+                    .is_some_and(|changed| changed.tick == analog_inputs.tick());
+                let reported_value = std::mem::replace(&mut reported.axes[axis.index()], value);
+                if changed || value != reported_value {
+                    let time = analog_inputs
+                        .last_event_time(axis)
+                        .filter(|time| time.tick == analog_inputs.tick())
+                        .map_or(now, |time| time.instant);
+                    self.inputs.push(GamepadEvent::AxisMoved {
+                        id,
+                        axis,
+                        value,
+                        time,
+                    });
+                }
+            }
+        }
+
+        // The sort is stable, so events of the same time keep the order of their inputs.
+        self.inputs.sort_by_key(GamepadEvent::time);
+        self.events.extend(self.inputs.drain(..));
+
+        // Axes move too often to tell what happened.
+        for &event in &self.events {
+            if !matches!(event, GamepadEvent::AxisMoved { .. }) {
+                if self.recent.len() == RECENT_EVENTS {
+                    self.recent.pop_front();
+                }
+                self.recent.push_back((event, now));
+            }
+        }
+    }
+
+    /// Iterates over the last events other than axis moves, from the oldest, with when they
+    /// were queued.
+    pub(crate) fn recent(&self) -> impl Iterator<Item = (GamepadEvent, Instant)> + '_ {
+        self.recent.iter().copied()
+    }
+
+    /// Iterates over the events of the last update that have not been taken yet.
+    pub(crate) fn queued(&self) -> impl Iterator<Item = &GamepadEvent> + '_ {
+        self.events.iter()
+    }
+
+    /// Queues an event along with the next update.
+    pub(crate) fn push(&mut self, event: GamepadEvent) {
+        self.pending.push(event);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<GamepadEvent> {
+        self.events.pop_front()
+    }
+
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = GamepadEvent> + '_ {
+        self.events.drain(..)
+    }
+}
+
+#[cfg(feature = "async")]
+mod stream {
+    use super::GamepadEvent;
+    use crate::GamepadContextHandle;
+    use futures_core::Stream;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    impl GamepadContextHandle {
+        /// Waits for the next gamepad event.
+        ///
+        /// Works with any executor on the thread of the context. The task is woken once an
+        /// update, such as by the game loop, queues events. Only one task should wait at a time.
+        pub async fn next_event(&self) -> GamepadEvent {
+            std::future::poll_fn(|cx| self.poll_event_async(cx)).await
+        }
+
+        /// Gets a stream of gamepad events, as queued by the updates of the context.
+        ///
+        /// See [`Self::next_event`].
+        pub fn event_stream(&self) -> EventStream {
+            EventStream {
+                handle: self.clone(),
+            }
+        }
+
+        fn poll_event_async(&self, cx: &mut Context<'_>) -> Poll<GamepadEvent> {
+            let mut context = self.get_mut();
+            match context.poll_event() {
+                Some(event) => Poll::Ready(event),
+                None => {
+                    context.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+
+    /// A never-ending stream of gamepad events.
+    ///
+    /// See [`GamepadContextHandle::event_stream`].
+    pub struct EventStream {
+        handle: GamepadContextHandle,
+    }
+
+    impl Stream for EventStream {
+        type Item = GamepadEvent;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.handle.poll_event_async(cx).map(Some)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use stream::EventStream;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guid;
+
+    fn buttons(queue: &mut EventQueue) -> Vec<(Button, bool)> {
+        queue
+            .drain()
+            .filter_map(|event| match event {
+                GamepadEvent::ButtonPressed { button, .. } => Some((button, true)),
+                GamepadEvent::ButtonReleased { button, .. } => Some((button, false)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn taps_within_an_update_are_reported() {
+        let mut queue = EventQueue::default();
+        let mut gamepads = HashMap::new();
+        gamepads.insert(GamepadId(0), Gamepad::new(None, Guid::default()));
+        queue.update(&gamepads);
+        queue.drain().for_each(drop);
+
+        let gamepad = gamepads.get_mut(&GamepadId(0)).unwrap();
+        gamepad.update_inputs();
+        gamepad.digital_inputs.activate(Button::A);
+        gamepad.digital_inputs.deactivate(Button::A);
+        gamepad.digital_inputs.activate(Button::B);
+        queue.update(&gamepads);
+        assert_eq!(
+            buttons(&mut queue),
+            [(Button::A, true), (Button::A, false), (Button::B, true)]
+        );
+
+        let gamepad = gamepads.get_mut(&GamepadId(0)).unwrap();
+        gamepad.update_inputs();
+        gamepad.digital_inputs.deactivate(Button::B);
+        gamepad.digital_inputs.activate(Button::X);
+        queue.update(&gamepads);
+        assert_eq!(buttons(&mut queue), [(Button::B, false), (Button::X, true)]);
+    }
+
+    #[test]
+    fn releases_follow_reported_presses() {
+        let mut queue = EventQueue::default();
+        let mut gamepad = Gamepad::new(None, Guid::default());
+        // Held before the queue saw the gamepad, such as while it waited to join.
+        gamepad.digital_inputs.activate(Button::A);
+        gamepad.update_inputs();
+
+        let mut gamepads = HashMap::new();
+        gamepads.insert(GamepadId(0), gamepad);
+        queue.update(&gamepads);
+        assert_eq!(buttons(&mut queue), []);
+
+        let gamepad = gamepads.get_mut(&GamepadId(0)).unwrap();
+        gamepad.update_inputs();
+        gamepad.digital_inputs.deactivate(Button::A);
+        queue.update(&gamepads);
+        assert_eq!(buttons(&mut queue), []);
+    }
+}
@@ -0,0 +1,245 @@
+//! A flat C ABI, for engines and tools that are not written in Rust.
+//!
+//! Enabled by the `ffi` feature. Build the crate as a C library with
+//! `cargo rustc --lib --crate-type cdylib --features ffi`, and generate a header with cbindgen.
+//!
+//! A context is created with [`fishsticks_init`] and passed to every other function. Gamepads are
+//! identified by the ids of [`fishsticks_gamepad_id`], buttons by their index in [`BUTTONS`] and
+//! axes by their index in [`AXES`]. Functions that fail return `false`, a null pointer or `-1`,
+//! and [`fishsticks_last_error`] tells why.
+
+use crate::{Gamepad, GamepadContext, GamepadId, AXES, BUTTONS};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(error: impl Into<Vec<u8>>) {
+    // Errors never contain nul bytes, but nothing is better than a panic if one did.
+    let error = CString::new(error).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = error);
+}
+
+/// Gets a gamepad of a context, setting the last error if there is none.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+unsafe fn gamepad<'a>(context: *const GamepadContext, id: usize) -> Option<&'a Gamepad> {
+    let Some(context) = context.as_ref() else {
+        set_error("The context is null");
+        return None;
+    };
+    let gamepad = context.gamepad(GamepadId(id));
+    if gamepad.is_none() {
+        set_error(format!("No gamepad with id {}", id));
+    }
+    gamepad
+}
+
+/// Creates a context with the first backend that works. Returns null on failure.
+///
+/// Fails while another context runs SDL2 or Steam Input. Free it with [`fishsticks_free`].
+#[no_mangle]
+pub extern "C" fn fishsticks_init() -> *mut GamepadContext {
+    match GamepadContext::init() {
+        Ok(context) => Box::into_raw(Box::new(context)),
+        Err(e) => {
+            set_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a context. Does nothing if `context` is null.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`], and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_free(context: *mut GamepadContext) {
+    if !context.is_null() {
+        drop(Box::from_raw(context));
+    }
+}
+
+/// Gets the message of the last error on this thread, or null if nothing failed yet.
+///
+/// The message stays valid until the next error on this thread.
+#[no_mangle]
+pub extern "C" fn fishsticks_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Updates the gamepads of a context. Call it once per frame.
+///
+/// Returns `false` if a backend failed, in which case the gamepads are still updated.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_update(context: *mut GamepadContext) -> bool {
+    let Some(context) = context.as_mut() else {
+        set_error("The context is null");
+        return false;
+    };
+    match context.update() {
+        Ok(()) => true,
+        Err(e) => {
+            set_error(e);
+            false
+        }
+    }
+}
+
+/// Gets the number of connected gamepads.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_gamepad_count(context: *const GamepadContext) -> usize {
+    context
+        .as_ref()
+        .map_or(0, |context| context.gamepads().count())
+}
+
+/// Gets the id of the connected gamepad at `index`, from `0` to the gamepad count, in the order
+/// of their ids. Returns `-1` if `index` is out of range.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_gamepad_id(
+    context: *const GamepadContext,
+    index: usize,
+) -> isize {
+    let Some(context) = context.as_ref() else {
+        set_error("The context is null");
+        return -1;
+    };
+    let mut ids: Vec<_> = context.gamepads().map(|(id, _)| id.0).collect();
+    ids.sort_unstable();
+    match ids.get(index) {
+        Some(&id) => id as isize,
+        None => {
+            set_error(format!("No gamepad at index {}", index));
+            -1
+        }
+    }
+}
+
+/// Gets the number of buttons. Button indices go from `0` to one less.
+#[no_mangle]
+pub extern "C" fn fishsticks_button_count() -> usize {
+    BUTTONS.len()
+}
+
+/// Gets the number of axes. Axis indices go from `0` to one less.
+#[no_mangle]
+pub extern "C" fn fishsticks_axis_count() -> usize {
+    AXES.len()
+}
+
+/// Checks if a button of a gamepad is held. Returns `false` for unknown gamepads and buttons.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_button(
+    context: *const GamepadContext,
+    gamepad_id: usize,
+    button: usize,
+) -> bool {
+    let (Some(gamepad), Some(&button)) = (gamepad(context, gamepad_id), BUTTONS.get(button)) else {
+        return false;
+    };
+    gamepad.digital_inputs.activated(button)
+}
+
+/// Checks if a button of a gamepad was pressed during the last update.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_button_pressed(
+    context: *const GamepadContext,
+    gamepad_id: usize,
+    button: usize,
+) -> bool {
+    let (Some(gamepad), Some(&button)) = (gamepad(context, gamepad_id), BUTTONS.get(button)) else {
+        return false;
+    };
+    gamepad.digital_inputs.just_activated(button)
+}
+
+/// Checks if a button of a gamepad was released during the last update.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_button_released(
+    context: *const GamepadContext,
+    gamepad_id: usize,
+    button: usize,
+) -> bool {
+    let (Some(gamepad), Some(&button)) = (gamepad(context, gamepad_id), BUTTONS.get(button)) else {
+        return false;
+    };
+    gamepad.digital_inputs.just_deactivated(button)
+}
+
+/// Gets the value of an axis of a gamepad, after deadzones. Returns `0.0` for unknown gamepads
+/// and axes.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_axis(
+    context: *const GamepadContext,
+    gamepad_id: usize,
+    axis: usize,
+) -> f32 {
+    let (Some(gamepad), Some(&axis)) = (gamepad(context, gamepad_id), AXES.get(axis)) else {
+        return 0.0;
+    };
+    gamepad.analog_inputs.value(axis)
+}
+
+/// Sets the strength of the rumble motors of a gamepad, from `0.0` to `1.0`, until it is set
+/// again. Returns `false` if the gamepad cannot rumble.
+///
+/// # Safety
+///
+/// `context` has to be null or come from [`fishsticks_init`].
+#[no_mangle]
+pub unsafe extern "C" fn fishsticks_rumble(
+    context: *const GamepadContext,
+    gamepad_id: usize,
+    low_frequency: f32,
+    high_frequency: f32,
+) -> bool {
+    let Some(gamepad) = gamepad(context, gamepad_id) else {
+        return false;
+    };
+    match gamepad.set_rumble(low_frequency, high_frequency) {
+        Ok(()) => true,
+        Err(e) => {
+            set_error(e);
+            false
+        }
+    }
+}
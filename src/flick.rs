@@ -0,0 +1,116 @@
+//! Flick stick, where flicking a stick turns the camera towards the stick's direction.
+//!
+//! Flicking the stick past a threshold turns by the angle of the stick. Rotating the stick while
+//! it is held past the threshold turns by as much as the stick rotated. This is usually combined
+//! with gyro aiming, which handles up and down.
+
+use crate::analog::AnalogInput;
+use crate::storage::InputKind;
+use std::f32::consts::{PI, TAU};
+
+const DEFAULT_THRESHOLD: f32 = 0.9;
+
+/// How far below the threshold the stick has to fall to end a flick.
+const RELEASE_MARGIN: f32 = 0.1;
+
+/// A turn reported by [`FlickStick::update`]. Angles are in radians, positive clockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlickStickTurn {
+    /// The stick was flicked. Turn by `angle`, where `0.0` is straight up.
+    Flick {
+        /// The angle of the stick.
+        angle: f32,
+    },
+    /// The stick was rotated while held past the threshold. Turn by `delta`.
+    Rotate {
+        /// How much the stick rotated since the last update.
+        delta: f32,
+    },
+}
+
+/// Detects flicks of a stick. Update it once per update, after the context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlickStick {
+    threshold: f32,
+    // The angle of the stick while it is held past the threshold.
+    angle: Option<f32>,
+}
+
+impl FlickStick {
+    /// Creates a flick stick that flicks when the stick is pushed 90% of the way.
+    pub fn new() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            angle: None,
+        }
+    }
+
+    /// Sets how far the stick has to be pushed to flick, from `0.0` to `1.0`.
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(RELEASE_MARGIN, 1.0);
+        self
+    }
+
+    /// Gets how far the stick has to be pushed to flick.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Checks if the stick is held past the threshold after a flick.
+    pub fn is_flicking(&self) -> bool {
+        self.angle.is_some()
+    }
+
+    /// Reads a stick, given its horizontal and vertical axis, and reports how to turn.
+    ///
+    /// A flick is only detected when the stick crosses the threshold during an update, so that
+    /// slowly pushing the stick does not flick.
+    pub fn update<T>(
+        &mut self,
+        analog_inputs: &AnalogInput<T>,
+        x: T,
+        y: T,
+    ) -> Option<FlickStickTurn>
+    where
+        T: InputKind,
+    {
+        let (value_x, value_y) = (analog_inputs.value(x), analog_inputs.value(y));
+        let magnitude = value_x.hypot(value_y);
+        // Vertical axes are positive downwards, so up is negative.
+        let angle = value_x.atan2(-value_y);
+
+        match self.angle {
+            Some(_) if magnitude < self.threshold - RELEASE_MARGIN => {
+                self.angle = None;
+                None
+            }
+            Some(previous) => {
+                self.angle = Some(angle);
+                let delta = wrap_angle(angle - previous);
+                (delta != 0.0).then_some(FlickStickTurn::Rotate { delta })
+            }
+            None if magnitude >= self.threshold => {
+                let previous_x = value_x - analog_inputs.delta(x);
+                let previous_y = value_y - analog_inputs.delta(y);
+                if previous_x.hypot(previous_y) >= self.threshold {
+                    return None;
+                }
+
+                self.angle = Some(angle);
+                Some(FlickStickTurn::Flick { angle })
+            }
+            None => None,
+        }
+    }
+}
+
+impl Default for FlickStick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Wraps an angle to `[-PI, PI]`.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
@@ -0,0 +1,125 @@
+//! Controller identification, for showing button prompts that match the player's controller.
+
+use crate::{Axis, Button, Guid};
+
+/// The family of a controller, which decides how its buttons are labelled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControllerType {
+    /// An Xbox controller, or one that follows its labels.
+    Xbox,
+    /// A PlayStation controller.
+    PlayStation,
+    /// A Nintendo Switch Pro controller, or Joy-Cons.
+    SwitchPro,
+    /// Any other controller.
+    #[default]
+    Generic,
+}
+
+const VENDOR_MICROSOFT: u16 = 0x045e;
+const VENDOR_SONY: u16 = 0x054c;
+const VENDOR_NINTENDO: u16 = 0x057e;
+
+impl ControllerType {
+    /// Guesses the type of a controller from the vendor id in its GUID.
+    ///
+    /// Works for GUIDs in the format SDL2 uses for USB and Bluetooth devices,
+    /// which gilrs and the evdev backend use as well.
+    pub fn from_guid(guid: Guid) -> Self {
+        if guid.0.starts_with(b"xinput") {
+            return ControllerType::Xbox;
+        }
+
+        match u16::from_le_bytes([guid.0[4], guid.0[5]]) {
+            VENDOR_MICROSOFT => ControllerType::Xbox,
+            VENDOR_SONY => ControllerType::PlayStation,
+            VENDOR_NINTENDO => ControllerType::SwitchPro,
+            _ => ControllerType::Generic,
+        }
+    }
+}
+
+impl Button {
+    /// Gets the label of the button on a type of controller, such as `"✕"` for
+    /// [`Button::A`] on a PlayStation controller.
+    ///
+    /// Buttons are named by position, so [`Button::A`] is labelled `"B"` on Nintendo controllers.
+    pub fn glyph_name(self, controller_type: ControllerType) -> &'static str {
+        use ControllerType::*;
+        match (self, controller_type) {
+            (Button::A, PlayStation) => "✕",
+            (Button::B, PlayStation) => "○",
+            (Button::X, PlayStation) => "□",
+            (Button::Y, PlayStation) => "△",
+            (Button::A, SwitchPro) => "B",
+            (Button::B, SwitchPro) => "A",
+            (Button::X, SwitchPro) => "Y",
+            (Button::Y, SwitchPro) => "X",
+            (Button::A, _) => "A",
+            (Button::B, _) => "B",
+            (Button::X, _) => "X",
+            (Button::Y, _) => "Y",
+
+            (Button::Back, Xbox) => "View",
+            (Button::Back, PlayStation) => "Share",
+            (Button::Back, SwitchPro) => "−",
+            (Button::Back, Generic) => "Back",
+            (Button::Guide, Xbox) => "Xbox",
+            (Button::Guide, PlayStation) => "PS",
+            (Button::Guide, SwitchPro) => "Home",
+            (Button::Guide, Generic) => "Guide",
+            (Button::Start, Xbox) => "Menu",
+            (Button::Start, PlayStation) => "Options",
+            (Button::Start, SwitchPro) => "+",
+            (Button::Start, Generic) => "Start",
+
+            (Button::LeftStick, PlayStation) => "L3",
+            (Button::RightStick, PlayStation) => "R3",
+            (Button::LeftStick, _) => "LS",
+            (Button::RightStick, _) => "RS",
+            (Button::LeftShoulder, PlayStation) => "L1",
+            (Button::RightShoulder, PlayStation) => "R1",
+            (Button::LeftShoulder, SwitchPro) => "L",
+            (Button::RightShoulder, SwitchPro) => "R",
+            (Button::LeftShoulder, _) => "LB",
+            (Button::RightShoulder, _) => "RB",
+
+            (Button::DPadUp, _) => "D-pad Up",
+            (Button::DPadDown, _) => "D-pad Down",
+            (Button::DPadLeft, _) => "D-pad Left",
+            (Button::DPadRight, _) => "D-pad Right",
+
+            (Button::Misc1, Xbox) => "Share",
+            (Button::Misc1, PlayStation) => "Mute",
+            (Button::Misc1, SwitchPro) => "Capture",
+            (Button::Misc1, Generic) => "Misc",
+            (Button::Paddle1, _) => "P1",
+            (Button::Paddle2, _) => "P3",
+            (Button::Paddle3, _) => "P2",
+            (Button::Paddle4, _) => "P4",
+            (Button::Touchpad, _) => "Touchpad",
+            (Button::Virtual(_), _) => "Virtual",
+        }
+    }
+}
+
+impl Axis {
+    /// Gets the label of the axis on a type of controller, such as `"R2"` for
+    /// [`Axis::TriggerRight`] on a PlayStation controller.
+    pub fn glyph_name(self, controller_type: ControllerType) -> &'static str {
+        use ControllerType::*;
+        match (self, controller_type) {
+            (Axis::LeftX | Axis::LeftY, PlayStation) => "L",
+            (Axis::RightX | Axis::RightY, PlayStation) => "R",
+            (Axis::LeftX | Axis::LeftY, _) => "LS",
+            (Axis::RightX | Axis::RightY, _) => "RS",
+            (Axis::TriggerLeft, PlayStation) => "L2",
+            (Axis::TriggerRight, PlayStation) => "R2",
+            (Axis::TriggerLeft, SwitchPro) => "ZL",
+            (Axis::TriggerRight, SwitchPro) => "ZR",
+            (Axis::TriggerLeft, _) => "LT",
+            (Axis::TriggerRight, _) => "RT",
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Gyro aiming, which fuses the gyroscope and accelerometer of a gamepad into a stable
+//! orientation and turns its rotation into aim.
+//!
+//! The gyroscope is integrated into an orientation, and the accelerometer slowly pulls the
+//! orientation towards gravity, like a Mahony filter, so that it does not drift up or down.
+//! Enable both sensors with [`Gamepad::set_sensor_enabled`](crate::Gamepad::set_sensor_enabled).
+
+use crate::sensor::SensorInput;
+use std::time::Duration;
+
+const DEFAULT_CORRECTION: f32 = 0.5;
+
+/// Standard gravity, in meters per second squared.
+const GRAVITY: f32 = 9.80665;
+
+/// Fuses the motion sensors of a gamepad. Update it once per update, after the context.
+///
+/// Sensor axes follow SDL2: `x` points to the right, `y` up out of the face of the gamepad and
+/// `z` towards the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GyroAim {
+    // Rotates from the frame of the gamepad to the world, as `[w, x, y, z]`, with `z` up.
+    orientation: [f32; 4],
+    initialized: bool,
+    correction: f32,
+    sensitivity: f32,
+    gravity_compensation: bool,
+    delta: (f32, f32),
+}
+
+impl GyroAim {
+    /// Creates a gyro aim with a sensitivity of `1.0` and gravity compensation.
+    pub fn new() -> Self {
+        Self {
+            orientation: [1.0, 0.0, 0.0, 0.0],
+            initialized: false,
+            correction: DEFAULT_CORRECTION,
+            sensitivity: 1.0,
+            gravity_compensation: true,
+            delta: (0.0, 0.0),
+        }
+    }
+
+    /// Sets how many radians the aim turns per radian the gamepad turns.
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+
+    /// Sets how strongly the accelerometer corrects the drift of the gyroscope. Higher values
+    /// correct faster, but let shaking disturb the orientation. Defaults to `0.5`.
+    pub fn with_correction(mut self, correction: f32) -> Self {
+        self.correction = correction.max(0.0);
+        self
+    }
+
+    /// Sets whether horizontal aim follows turns around gravity, rather than around the `y` axis
+    /// of the gamepad. Enabled by default.
+    ///
+    /// With compensation, turning left and right aims the same however the gamepad is tilted.
+    pub fn with_gravity_compensation(mut self, enabled: bool) -> Self {
+        self.gravity_compensation = enabled;
+        self
+    }
+
+    /// Reads the sensors, given the time since the last update.
+    ///
+    /// Without a gyroscope reading, the aim does not move. Without an accelerometer reading,
+    /// the orientation is not corrected.
+    pub fn update(&mut self, sensors: &SensorInput, delta_time: Duration) {
+        self.delta = (0.0, 0.0);
+        let accelerometer = sensors.accelerometer().and_then(normalize);
+        if !self.initialized {
+            if let Some(up) = accelerometer {
+                self.orientation = rotation_to_up(up);
+                self.initialized = true;
+            }
+        }
+        let Some(gyroscope) = sensors.gyroscope() else {
+            return;
+        };
+
+        let dt = delta_time.as_secs_f32();
+        let up = self.up();
+        let [mut x, mut y, mut z] = gyroscope;
+        let yaw = if self.gravity_compensation {
+            dot(gyroscope, up)
+        } else {
+            y
+        };
+        // Turning left and pitching up are positive, while aim is positive right and down.
+        self.delta = (-yaw * dt * self.sensitivity, -x * dt * self.sensitivity);
+
+        if let Some(measured) = accelerometer {
+            let [ex, ey, ez] = cross(measured, up);
+            x += self.correction * ex;
+            y += self.correction * ey;
+            z += self.correction * ez;
+        }
+        let [w, qx, qy, qz] = self.orientation;
+        let half = 0.5 * dt;
+        self.orientation = normalize4([
+            w + half * (-qx * x - qy * y - qz * z),
+            qx + half * (w * x + qy * z - qz * y),
+            qy + half * (w * y - qx * z + qz * x),
+            qz + half * (w * z + qx * y - qy * x),
+        ]);
+    }
+
+    /// Gets how much to aim during the last update, in radians, with `x` positive to the right
+    /// and `y` positive downwards like the stick axes.
+    pub fn aim_delta(&self) -> (f32, f32) {
+        self.delta
+    }
+
+    /// Gets the orientation of the gamepad, as a quaternion `[w, x, y, z]` that rotates from
+    /// the axes of the gamepad to the world, where `z` points up.
+    ///
+    /// The heading drifts slowly, since nothing corrects it.
+    pub fn orientation(&self) -> [f32; 4] {
+        self.orientation
+    }
+
+    /// Gets the direction of up in the axes of the gamepad, as a unit vector.
+    pub fn up(&self) -> [f32; 3] {
+        let [w, x, y, z] = self.orientation;
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (w * x + y * z),
+            w * w - x * x - y * y + z * z,
+        ]
+    }
+
+    /// Gets the acceleration of the gamepad without gravity, in meters per second squared.
+    ///
+    /// Returns `None` without an accelerometer reading.
+    pub fn linear_acceleration(&self, sensors: &SensorInput) -> Option<[f32; 3]> {
+        let [x, y, z] = sensors.accelerometer()?;
+        let [up_x, up_y, up_z] = self.up();
+        Some([x - up_x * GRAVITY, y - up_y * GRAVITY, z - up_z * GRAVITY])
+    }
+
+    /// Forgets the orientation, so that it starts over from the next accelerometer reading.
+    pub fn reset(&mut self) {
+        self.orientation = [1.0, 0.0, 0.0, 0.0];
+        self.initialized = false;
+        self.delta = (0.0, 0.0);
+    }
+}
+
+impl Default for GyroAim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gets the rotation that turns `up`, in the axes of the gamepad, onto `z`.
+fn rotation_to_up([x, y, z]: [f32; 3]) -> [f32; 4] {
+    if z < -0.9999 {
+        // Upside down, where the axis of rotation is arbitrary.
+        return [0.0, 1.0, 0.0, 0.0];
+    }
+    // The cross product of `up` and `z`, with a half angle from adding the dot product.
+    normalize4([1.0 + z, y, -x, 0.0])
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let length = dot(v, v).sqrt();
+    (length > f32::EPSILON).then(|| v.map(|component| component / length))
+}
+
+fn normalize4(q: [f32; 4]) -> [f32; 4] {
+    let length = q
+        .iter()
+        .map(|component| component * component)
+        .sum::<f32>()
+        .sqrt();
+    q.map(|component| component / length)
+}
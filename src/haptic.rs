@@ -0,0 +1,306 @@
+//! Haptic effects, played through the rumble motors of a gamepad.
+//!
+//! Effects can also play on [`RumbleChannel`]s, so that several gameplay systems can rumble at
+//! once. The channels with the highest priority are added together, and the others are muted
+//! until they end.
+//!
+//! Effects are serializable with the `serde` feature, with durations in seconds, such as to name
+//! them in a [`Config`](crate::config::Config).
+
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+/// A rumble effect with a waveform, a duration and an optional envelope.
+///
+/// Effects are played in software through [`crate::Gamepad::set_rumble`], so they work on every
+/// backend that supports rumble. Their strength is updated on every [`crate::GamepadContext::update`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct HapticEffect {
+    waveform: Waveform,
+    #[cfg_attr(feature = "serde", serde(with = "crate::config::seconds"))]
+    duration: Duration,
+    #[cfg_attr(feature = "serde", serde(default))]
+    envelope: Envelope,
+    #[cfg_attr(feature = "serde", serde(default = "full_strength"))]
+    low_frequency: f32,
+    #[cfg_attr(feature = "serde", serde(default = "full_strength"))]
+    high_frequency: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Waveform {
+    Constant(f32),
+    Sine {
+        strength: f32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::config::seconds"))]
+        period: Duration,
+    },
+    Ramp {
+        from: f32,
+        to: f32,
+    },
+}
+
+#[cfg(feature = "serde")]
+fn full_strength() -> f32 {
+    1.0
+}
+
+/// Shapes the start and end of a haptic effect.
+///
+/// During the attack, the strength goes from `attack_level` to the strength of the waveform.
+/// During the decay, it goes from the strength of the waveform to `decay_level`.
+/// Levels are relative to the waveform, from `0.0` to `1.0`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Envelope {
+    /// Length of the attack, at the start of the effect.
+    #[cfg_attr(feature = "serde", serde(with = "crate::config::seconds"))]
+    pub attack: Duration,
+    /// Relative strength at the start of the effect.
+    pub attack_level: f32,
+    /// Length of the decay, at the end of the effect.
+    #[cfg_attr(feature = "serde", serde(with = "crate::config::seconds"))]
+    pub decay: Duration,
+    /// Relative strength at the end of the effect.
+    pub decay_level: f32,
+}
+
+impl Envelope {
+    fn at(&self, elapsed: Duration, duration: Duration) -> f32 {
+        let remaining = duration.saturating_sub(elapsed);
+        if elapsed < self.attack {
+            let progress = elapsed.as_secs_f32() / self.attack.as_secs_f32();
+            lerp(self.attack_level, 1.0, progress)
+        } else if remaining < self.decay {
+            let progress = remaining.as_secs_f32() / self.decay.as_secs_f32();
+            lerp(self.decay_level, 1.0, progress)
+        } else {
+            1.0
+        }
+    }
+}
+
+impl HapticEffect {
+    /// Creates an effect with a constant strength, from `0.0` to `1.0`.
+    pub fn constant(strength: f32, duration: Duration) -> Self {
+        Self::new(Waveform::Constant(strength), duration)
+    }
+
+    /// Creates an effect whose strength pulses between `0.0` and `strength`, once per `period`.
+    pub fn sine(strength: f32, period: Duration, duration: Duration) -> Self {
+        Self::new(Waveform::Sine { strength, period }, duration)
+    }
+
+    /// Creates an effect whose strength goes linearly from `from` to `to`.
+    pub fn ramp(from: f32, to: f32, duration: Duration) -> Self {
+        Self::new(Waveform::Ramp { from, to }, duration)
+    }
+
+    fn new(waveform: Waveform, duration: Duration) -> Self {
+        Self {
+            waveform,
+            duration,
+            envelope: Envelope::default(),
+            low_frequency: 1.0,
+            high_frequency: 1.0,
+        }
+    }
+
+    /// Sets the envelope of the effect.
+    pub fn with_envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// Sets how strongly each motor plays the effect, from `0.0` to `1.0`. Both default to `1.0`.
+    pub fn with_motors(mut self, low_frequency: f32, high_frequency: f32) -> Self {
+        self.low_frequency = low_frequency;
+        self.high_frequency = high_frequency;
+        self
+    }
+
+    /// Gets the duration of the effect.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Gets the strength of the effect `elapsed` after it started, from `0.0` to `1.0`.
+    pub fn strength_at(&self, elapsed: Duration) -> f32 {
+        if elapsed >= self.duration {
+            return 0.0;
+        }
+
+        let strength = match self.waveform {
+            Waveform::Constant(strength) => strength,
+            Waveform::Sine { strength, period } => {
+                let phase = elapsed.as_secs_f32() / period.as_secs_f32();
+                strength * (0.5 - 0.5 * (phase * TAU).cos())
+            }
+            Waveform::Ramp { from, to } => lerp(
+                from,
+                to,
+                elapsed.as_secs_f32() / self.duration.as_secs_f32(),
+            ),
+        };
+
+        (strength * self.envelope.at(elapsed, self.duration)).clamp(0.0, 1.0)
+    }
+}
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+/// A channel of rumble, such as for one gameplay system. Channels are chosen by the game.
+///
+/// See [`Gamepad::set_channel_rumble`](crate::Gamepad::set_channel_rumble).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RumbleChannel(pub u16);
+
+#[derive(Debug, Clone, PartialEq)]
+enum ChannelSource {
+    Constant(f32, f32),
+    Effect(HapticEffect, Instant),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Channel {
+    priority: i32,
+    source: ChannelSource,
+}
+
+impl Channel {
+    /// Gets the motor strengths of the channel, or `None` once it ended.
+    fn strengths(&self, now: Instant) -> Option<(f32, f32)> {
+        match &self.source {
+            &ChannelSource::Constant(low_frequency, high_frequency) => {
+                Some((low_frequency, high_frequency))
+            }
+            ChannelSource::Effect(effect, started) => {
+                let elapsed = now.duration_since(*started);
+                let strength = effect.strength_at(elapsed);
+                (elapsed < effect.duration).then_some((
+                    strength * effect.low_frequency,
+                    strength * effect.high_frequency,
+                ))
+            }
+        }
+    }
+}
+
+/// Plays queued haptic effects one after another, mixed with the effects of rumble channels.
+///
+/// The queue plays with priority `0`.
+#[derive(Debug, Default)]
+pub(crate) struct HapticQueue {
+    queue: VecDeque<HapticEffect>,
+    current: Option<(HapticEffect, Instant)>,
+    channels: HashMap<RumbleChannel, Channel>,
+    // Whether channels played during the last update, so that the motors stop after them.
+    mixing: bool,
+}
+
+impl HapticQueue {
+    pub(crate) fn push(&mut self, effect: HapticEffect) {
+        self.queue.push_back(effect);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.queue.clear();
+        self.current = None;
+        self.channels.clear();
+        self.mixing = false;
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.current.is_some() || !self.queue.is_empty() || !self.channels.is_empty()
+    }
+
+    pub(crate) fn set_channel(
+        &mut self,
+        channel: RumbleChannel,
+        priority: i32,
+        strengths: (f32, f32),
+    ) {
+        let source = ChannelSource::Constant(strengths.0, strengths.1);
+        self.channels.insert(channel, Channel { priority, source });
+    }
+
+    pub(crate) fn play_on_channel(
+        &mut self,
+        channel: RumbleChannel,
+        priority: i32,
+        effect: HapticEffect,
+    ) {
+        let source = ChannelSource::Effect(effect, Instant::now());
+        self.channels.insert(channel, Channel { priority, source });
+    }
+
+    pub(crate) fn stop_channel(&mut self, channel: RumbleChannel) {
+        self.channels.remove(&channel);
+    }
+
+    /// Advances playback, returning the motor strengths to apply.
+    ///
+    /// Returns `None` when nothing was playing, so idle gamepads are left alone.
+    pub(crate) fn update(&mut self) -> Option<(f32, f32)> {
+        let now = Instant::now();
+        let queued = self.update_queue(now);
+
+        let mut sources: Vec<_> = self
+            .channels
+            .values()
+            .filter_map(|channel| Some((channel.priority, channel.strengths(now)?)))
+            .collect();
+        self.channels
+            .retain(|_, channel| channel.strengths(now).is_some());
+        let mixing = !sources.is_empty();
+        if !mixing && !std::mem::take(&mut self.mixing) {
+            return queued;
+        }
+        self.mixing = mixing;
+
+        if let Some(strengths) = queued.filter(|_| self.current.is_some()) {
+            sources.push((0, strengths));
+        }
+        let priority = sources.iter().map(|&(priority, _)| priority).max();
+        let (low_frequency, high_frequency) = sources
+            .iter()
+            .filter(|&&(other, _)| Some(other) == priority)
+            .fold((0.0, 0.0), |(low, high), &(_, (other_low, other_high))| {
+                (low + other_low, high + other_high)
+            });
+        Some((low_frequency.min(1.0), high_frequency.min(1.0)))
+    }
+
+    /// Advances the queue, returning its motor strengths.
+    fn update_queue(&mut self, now: Instant) -> Option<(f32, f32)> {
+        loop {
+            if self.current.is_none() {
+                let effect = self.queue.pop_front()?;
+                self.current = Some((effect, now));
+            }
+
+            let (effect, started) = self.current.as_ref()?;
+            let elapsed = now.duration_since(*started);
+            if elapsed < effect.duration {
+                let strength = effect.strength_at(elapsed);
+                return Some((
+                    strength * effect.low_frequency,
+                    strength * effect.high_frequency,
+                ));
+            }
+
+            self.current = None;
+            if self.queue.is_empty() {
+                return Some((0.0, 0.0));
+            }
+        }
+    }
+}
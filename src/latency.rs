@@ -0,0 +1,261 @@
+//! Opt-in instrumentation of input latency, for diagnosing reports of laggy controls.
+//!
+//! Enable it with [`GamepadContext::set_latency_tracking`](crate::GamepadContext::set_latency_tracking),
+//! and read the results with
+//! [`GamepadContext::latency_stats`](crate::GamepadContext::latency_stats).
+
+use crate::{Button, Gamepad, GamepadId};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The upper bound of the first bucket of a histogram. Each bucket is twice as wide as the last.
+const FIRST_BUCKET: Duration = Duration::from_micros(250);
+const BUCKETS: usize = 12;
+
+/// How many presses can wait for a query, so that tracking without querying stays bounded.
+const MAX_PENDING: usize = 1024;
+
+/// How many dropped presses are kept, for inspecting the most recent ones.
+const MAX_DROPPED: usize = 16;
+
+/// A histogram of durations, in buckets that double in width, from 250 µs up to 256 ms.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Histogram {
+    /// Adds a duration to the histogram.
+    pub fn record(&mut self, duration: Duration) {
+        let bucket = (0..BUCKETS - 1)
+            .find(|&bucket| duration <= upper_bound(bucket))
+            .unwrap_or(BUCKETS - 1);
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += duration;
+        self.max = self.max.max(duration);
+    }
+
+    /// Gets the number of recorded durations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Gets the mean of the recorded durations.
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.total.div_f64(self.count as f64))
+    }
+
+    /// Gets the longest recorded duration.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Gets the upper bound of the bucket that holds the given percentile, from `0.0` to `1.0`.
+    ///
+    /// Durations past the last bucket are bounded by [`Self::max`].
+    pub fn percentile(&self, percentile: f32) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (percentile.clamp(0.0, 1.0) * self.count as f32)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(upper_bound(bucket).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Iterates over the buckets, as their upper bound and the number of durations in them.
+    ///
+    /// The last bucket has no upper bound, and is given [`Duration::MAX`].
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(bucket, &count)| (upper_bound(bucket), count))
+    }
+
+    /// Removes every recorded duration.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn upper_bound(bucket: usize) -> Duration {
+    if bucket == BUCKETS - 1 {
+        Duration::MAX
+    } else {
+        FIRST_BUCKET * (1 << bucket)
+    }
+}
+
+/// Latency measurements of a context.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LatencyStats {
+    input_latency: Histogram,
+    update_interval: Histogram,
+    dropped_presses: u64,
+    recent_dropped_presses: VecDeque<DroppedPress>,
+}
+
+/// A press that was released within the same update, so the game never saw it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedPress {
+    /// The gamepad that the press came from.
+    pub gamepad: GamepadId,
+    /// The button that was pressed.
+    pub button: Button,
+    /// The time between the update that dropped the press and the one before it.
+    pub update_interval: Duration,
+}
+
+impl LatencyStats {
+    /// Gets the time from receiving button presses from the backend to the game first reading
+    /// the gamepads or events of the context afterwards.
+    pub fn input_latency(&self) -> &Histogram {
+        &self.input_latency
+    }
+
+    /// Gets the time between updates of the context.
+    pub fn update_interval(&self) -> &Histogram {
+        &self.update_interval
+    }
+
+    /// Gets the number of presses that were released within the same update, so the game never
+    /// saw them.
+    ///
+    /// Updating more often, or enabling
+    /// [latching](crate::GamepadContext::set_latching), prevents these.
+    pub fn dropped_presses(&self) -> u64 {
+        self.dropped_presses
+    }
+
+    /// Gets the last few dropped presses, oldest first.
+    ///
+    /// See [`Self::dropped_presses`].
+    pub fn recent_dropped_presses(&self) -> impl Iterator<Item = &DroppedPress> {
+        self.recent_dropped_presses.iter()
+    }
+
+    /// Clears every measurement.
+    pub fn reset(&mut self) {
+        self.input_latency.clear();
+        self.update_interval.clear();
+        self.dropped_presses = 0;
+        self.recent_dropped_presses.clear();
+    }
+}
+
+/// Measures the latency of a context while tracking is enabled.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    pub(crate) stats: LatencyStats,
+    // When presses were received, until the game next reads the context.
+    pending: Vec<Instant>,
+    update_started: Option<Instant>,
+    update_interval: Duration,
+    queried: Cell<Option<Instant>>,
+}
+
+impl LatencyTracker {
+    /// Notes that the game read the context.
+    pub(crate) fn query(&self) {
+        if self.queried.get().is_none() {
+            self.queried.set(Some(Instant::now()));
+        }
+    }
+
+    /// Measures the reads since the last update. Call before polling the backend.
+    pub(crate) fn start_update(&mut self) {
+        let now = Instant::now();
+        if let Some(queried) = self.queried.take() {
+            for received in self.pending.drain(..) {
+                let latency = queried.saturating_duration_since(received);
+                self.stats.input_latency.record(latency);
+            }
+        }
+        if let Some(previous) = self.update_started.replace(now) {
+            self.update_interval = now - previous;
+            self.stats.update_interval.record(self.update_interval);
+        }
+    }
+
+    /// Collects the presses received from the backend. Call right after polling it.
+    pub(crate) fn finish_polling(&mut self, gamepads: &HashMap<GamepadId, Gamepad>) {
+        let Some(started) = self.update_started else {
+            return;
+        };
+
+        for (&id, gamepad) in gamepads {
+            let digital_inputs = &gamepad.digital_inputs;
+            for button in digital_inputs.just_activated_inputs() {
+                let received = digital_inputs.activated_at(button).map(|at| at.instant);
+                if let Some(received) = received.filter(|&received| received >= started) {
+                    if self.pending.len() < MAX_PENDING {
+                        self.pending.push(received);
+                    }
+                }
+            }
+
+            for button in digital_inputs.just_deactivated_inputs() {
+                let pressed = digital_inputs.last_activated_at(button);
+                if pressed.is_some_and(|pressed| pressed >= started) {
+                    let stats = &mut self.stats;
+                    stats.dropped_presses += 1;
+                    if stats.recent_dropped_presses.len() == MAX_DROPPED {
+                        stats.recent_dropped_presses.pop_front();
+                    }
+                    stats.recent_dropped_presses.push_back(DroppedPress {
+                        gamepad: id,
+                        button,
+                        update_interval: self.update_interval,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Forgets reads made by the context itself during the update.
+    pub(crate) fn finish_update(&mut self) {
+        self.queried.set(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Guid;
+
+    #[test]
+    fn dropped_presses_are_recorded() {
+        let mut tracker = LatencyTracker::default();
+        let mut gamepads = HashMap::new();
+        gamepads.insert(GamepadId(0), Gamepad::new(None, Guid::default()));
+        tracker.start_update();
+
+        for _ in 0..MAX_DROPPED + 1 {
+            let gamepad = gamepads.get_mut(&GamepadId(0)).unwrap();
+            gamepad.update_inputs();
+            gamepad.digital_inputs.activate(Button::A);
+            gamepad.digital_inputs.deactivate(Button::A);
+            tracker.finish_polling(&gamepads);
+        }
+
+        let stats = &tracker.stats;
+        assert_eq!(stats.dropped_presses(), MAX_DROPPED as u64 + 1);
+        assert_eq!(stats.recent_dropped_presses().count(), MAX_DROPPED);
+        let dropped = stats.recent_dropped_presses().next().unwrap();
+        assert_eq!((dropped.gamepad, dropped.button), (GamepadId(0), Button::A));
+    }
+}
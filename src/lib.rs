@@ -2,111 +2,2037 @@
 //!
 //! Uses SDL2 as the backend by default.
 //! To use gilrs instead, disable default features and enable the `gilrs` feature.
+//! When several backends are enabled, the first one that works is picked at runtime,
+//! so a build with both `sdl2` and `gilrs` falls back to gilrs on systems without SDL2.
+//! They can also run side by side with [`GamepadContext::init_merged`].
+//! The `steam` feature reads input through Steam Input, respecting players' Steam configurations.
+//! It is preferred over other backends when the game runs under Steam.
+//! On Windows, the `xinput` feature supports Xbox controllers without any system dependencies.
+//! On Linux, the `evdev` feature reads `/dev/input` directly, without any system dependencies.
+//! On `wasm32` targets, disable default features and enable the `wasm` feature
+//! to use the browser Gamepad API.
+//!
+//! Enable the `config` feature to load the input configuration from TOML files,
+//! and the `serde` feature to serialize gamepad snapshots and profiles.
+//! The `bevy` feature adds a Bevy plugin.
+//! The `ui` feature adds menu navigation for UI toolkits.
+//! The `virtual-device` feature creates virtual gamepads that the operating system sees as real.
+//! The `async` feature adds `GamepadContextHandle::next_event` and a `Stream` of gamepad events.
+//! The `ffi` feature exposes a C ABI, for engines that are not written in Rust.
 
 #![warn(missing_docs)]
-#![cfg_attr(not(any(feature = "sdl2", feature = "gilrs")), allow(dead_code))]
+#![cfg_attr(
+    not(any(
+        feature = "sdl2",
+        feature = "gilrs",
+        feature = "steam",
+        feature = "xinput",
+        feature = "evdev",
+        feature = "wasm"
+    )),
+    allow(dead_code)
+)]
 
+pub mod admission;
 pub mod analog;
+#[cfg(feature = "bevy")]
+pub mod bevy;
+pub mod buffer;
+pub mod capabilities;
+pub mod config;
 pub mod digital;
+pub mod direction;
+pub mod drift;
+pub mod dump;
 pub mod error;
+pub mod event;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod flick;
+pub mod glyph;
+pub mod gyro;
+pub mod haptic;
+pub mod latency;
+pub mod lobby;
+pub mod motion;
+pub mod players;
+pub mod prelude;
+pub mod profile;
+pub mod raw;
+pub mod sensor;
+pub mod state;
+pub mod storage;
+pub mod timestamp;
+pub mod touchpad;
+pub mod trigger;
+#[cfg(feature = "ui")]
+pub mod ui;
+pub mod virtual_button;
+#[cfg(feature = "virtual-device")]
+pub mod virtual_device;
 
 mod backend;
+mod builder;
 
-pub use backend::{Axis, Button};
+pub use backend::{Axis, BackendKind, BackendState, Button, Stick, UpdateReport, AXES, BUTTONS};
+pub use builder::GamepadContextBuilder;
 
+use admission::{Admission, DeviceFilter, JoinPolicy};
 use analog::AnalogInput;
-use analog::{AnalogInputValue, Deadzone};
-use backend::Backend;
-use backend::{ImplementationContext, OwnedImplementationGamepad};
-use digital::DigitalInput;
-use std::collections::HashMap;
+use analog::{
+    AnalogInputValue, AxisConfig, Calibration, Deadzone, Quantization, ANALOG_MAX, ANALOG_MIN,
+};
+use backend::{Backend, GamepadDevice};
+use capabilities::Capabilities;
+use config::{Config, Settings};
+use digital::{DigitalInput, PressMode};
+use direction::Socd;
+use drift::DriftDetector;
+use event::{ButtonEdge, EventQueue, GamepadEvent};
+use glyph::ControllerType;
+use haptic::{HapticEffect, HapticQueue, RumbleChannel};
+use latency::{LatencyStats, LatencyTracker};
+use motion::InputHistory;
+use profile::{Input, Profile};
+use raw::RawInputs;
+use sensor::{Sensor, SensorInput};
+use state::{GamepadSnapshot, GamepadState, SharedState};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use storage::InputMap;
+use touchpad::TouchpadInput;
+use trigger::TriggerEffect;
+use virtual_button::VirtualButton;
 
 use error::Result;
 
 /// The instance Id of a gamepad.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct GamepadId(backend::ImplementationId);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadId(usize);
+
+impl From<GamepadId> for usize {
+    fn from(id: GamepadId) -> Self {
+        id.0
+    }
+}
+
+/// Identifies a gamepad model across reconnects and runs.
+///
+/// Several connected gamepads of the same model may share a GUID.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    /// Gets the USB vendor ID of the device, for GUIDs in the layout of SDL2, which every
+    /// backend reporting vendors uses.
+    pub fn vendor_id(self) -> Option<u16> {
+        self.has_ids()
+            .then(|| u16::from_le_bytes([self.0[4], self.0[5]]))
+    }
+
+    /// Gets the USB product ID of the device, like [`Self::vendor_id`].
+    pub fn product_id(self) -> Option<u16> {
+        self.has_ids()
+            .then(|| u16::from_le_bytes([self.0[8], self.0[9]]))
+    }
+
+    // SDL2 pads the vendor and product IDs with zeros, and leaves them out for devices it
+    // cannot identify.
+    fn has_ids(self) -> bool {
+        let guid = self.0;
+        guid[6..8] == [0, 0] && guid[10..12] == [0, 0] && guid[4..6] != [0, 0]
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Guid {
+    type Err = error::Error;
+
+    /// Parses a GUID from 32 hexadecimal digits.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || format!("Invalid GUID \"{}\"", s);
+        if s.len() != 32 || !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut guid = [0; 16];
+        for (i, byte) in guid.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(guid))
+    }
+}
+
+/// Identifies a physical gamepad across reconnects and runs, such as to save settings per
+/// controller.
+///
+/// Made of the GUID and, where the backend reports one, the serial number of the device.
+/// Without a serial number, gamepads of the same model cannot be told apart.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceFingerprint {
+    /// The GUID of the gamepad.
+    pub guid: Guid,
+    /// The serial number of the gamepad, if the backend reports one.
+    pub serial: Option<String>,
+}
+
+impl fmt::Display for DeviceFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.serial {
+            Some(serial) => write!(f, "{}:{}", self.guid, serial),
+            None => write!(f, "{}", self.guid),
+        }
+    }
+}
+
+impl FromStr for DeviceFingerprint {
+    type Err = error::Error;
+
+    /// Parses a fingerprint as shown, a GUID optionally followed by `:` and a serial number.
+    /// An empty serial number is the same as none.
+    fn from_str(s: &str) -> Result<Self> {
+        let (guid, serial) = match s.split_once(':') {
+            Some((guid, serial)) => (guid, Some(serial).filter(|serial| !serial.is_empty())),
+            None => (s, None),
+        };
+        Ok(Self {
+            guid: guid.parse()?,
+            serial: serial.map(str::to_owned),
+        })
+    }
+}
 
 /// Holds the state of a gamepad.
 pub struct Gamepad {
-    // Backends that produce owned gamepads, such as SDL2, need this to keep the gamepads open.
-    // Backends that produce references to gamepads, such as gilrs, do not need this.
-    #[allow(dead_code)]
-    owned_internal_gamepad: Option<OwnedImplementationGamepad>,
+    // Device features such as LEDs, for backends that support them.
+    device: Option<Box<dyn GamepadDevice>>,
+    guid: Guid,
+    name: Option<String>,
+    serial: Option<String>,
+    capabilities: Capabilities,
     /// Analog inputs, such as thumbsticks.
     pub analog_inputs: AnalogInput<Axis>,
     /// Digital inputs, such as buttons.
     pub digital_inputs: DigitalInput<Button>,
+    /// Touchpad inputs.
+    pub touchpad_inputs: TouchpadInput,
+    /// Numbered inputs, for raw joysticks without a controller mapping.
+    pub raw_inputs: RawInputs,
+    // Whether the gamepad is a raw joystick, which only reports raw inputs.
+    raw: bool,
+    sensors: SensorInput,
+    haptics: RefCell<HapticQueue>,
+    stick_to_dpad: bool,
+    dpad_to_stick: bool,
+    socd: Option<Socd>,
+    // The settings last applied from the configuration and profile, to reset the ones that go
+    // away.
+    settings: Settings,
+    config_generation: u64,
+    // The state after the last update, to detect changes.
+    last_snapshot: Option<GamepadSnapshot>,
+    changed: bool,
+    virtual_buttons: InputMap<u8, VirtualButton>,
+    suppressed: bool,
+    // Whether the context is disabled, which suppresses every gamepad.
+    context_disabled: bool,
+    last_input: Instant,
+    history: Option<InputHistory>,
+    drift: Option<DriftDetector>,
 }
 
 impl Gamepad {
-    fn new(owned_internal_gamepad: Option<OwnedImplementationGamepad>) -> Self {
+    fn new(device: Option<Box<dyn GamepadDevice>>, guid: Guid) -> Self {
         Self {
-            owned_internal_gamepad,
+            device,
+            guid,
+            name: None,
+            serial: None,
+            capabilities: Capabilities::standard(),
             analog_inputs: Default::default(),
             digital_inputs: Default::default(),
+            touchpad_inputs: Default::default(),
+            raw_inputs: Default::default(),
+            raw: false,
+            sensors: Default::default(),
+            haptics: Default::default(),
+            stick_to_dpad: false,
+            dpad_to_stick: false,
+            socd: None,
+            settings: Settings::default(),
+            config_generation: 0,
+            last_snapshot: None,
+            changed: false,
+            virtual_buttons: InputMap::new(),
+            suppressed: false,
+            context_disabled: false,
+            last_input: Instant::now(),
+            history: None,
+            drift: None,
+        }
+    }
+
+    /// Gets the GUID of the gamepad.
+    pub fn guid(&self) -> Guid {
+        self.guid
+    }
+
+    /// Gets the name of the gamepad, as reported by the operating system or the backend.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Gets the serial number of the gamepad, for backends and devices that report one.
+    pub fn serial(&self) -> Option<&str> {
+        self.serial.as_deref()
+    }
+
+    /// Gets the buttons, axes and features that the gamepad has, such as to hide prompts for
+    /// the ones it lacks.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Gets the fingerprint of the gamepad, which identifies it across runs.
+    pub fn fingerprint(&self) -> DeviceFingerprint {
+        DeviceFingerprint {
+            guid: self.guid,
+            serial: self.serial.clone(),
+        }
+    }
+
+    /// Checks if the gamepad is a raw joystick without a controller mapping.
+    ///
+    /// Raw joysticks only report [`Self::raw_inputs`].
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    /// Gets the type of the gamepad, for showing matching button prompts.
+    ///
+    /// See [`Button::glyph_name`].
+    pub fn controller_type(&self) -> ControllerType {
+        self.device
+            .as_ref()
+            .and_then(|device| device.controller_type())
+            .unwrap_or_else(|| ControllerType::from_guid(self.guid))
+    }
+
+    /// Gets mutable access to the configuration of an axis, such as its inversion.
+    pub fn axis_config_mut(&mut self, axis: Axis) -> &mut AxisConfig {
+        self.analog_inputs.config_mut(axis)
+    }
+
+    /// Sets the analog deadzone shared by all axes.
+    ///
+    /// Positive values outside the deadzone get remapped to the range `[0.0, ANALOG_MAX]`.\
+    /// Negative values get remapped to `[ANALOG_MIN, 0.0]`.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        let deadzone = Deadzone::from(AnalogInputValue::from(deadzone));
+        self.analog_inputs.set_deadzone(deadzone);
+    }
+
+    /// Sets the digital deadzone shared by all axes.
+    pub fn set_digital_deadzone(&mut self, deadzone: f32) {
+        let deadzone = Deadzone::from(AnalogInputValue::from(deadzone));
+        self.analog_inputs.set_digital_deadzone(deadzone);
+    }
+
+    /// Sets the analog deadzone of a single axis.
+    ///
+    /// See [`AnalogInput::set_deadzone_for`].
+    pub fn set_axis_deadzone(&mut self, axis: Axis, deadzone: f32) {
+        self.analog_inputs.set_deadzone_for(axis, deadzone);
+    }
+
+    /// Applies the deadzones suggested by a calibration.
+    pub fn apply_calibration(&mut self, calibration: &Calibration<Axis>) {
+        calibration.apply(&mut self.analog_inputs);
+    }
+
+    /// Limits how often a button can be activated.
+    ///
+    /// See [`DigitalInput::set_rate_limit`].
+    pub fn set_rate_limit(&mut self, button: Button, max_per_second: f32) {
+        self.digital_inputs.set_rate_limit(button, max_per_second);
+    }
+
+    /// Makes a button fire repeatedly while it is held.
+    ///
+    /// See [`DigitalInput::set_turbo`].
+    pub fn set_turbo(&mut self, button: Button, presses_per_second: f32) {
+        self.digital_inputs.set_turbo(button, presses_per_second);
+    }
+
+    /// Sets how a button reacts to being pressed, such as toggling instead of holding.
+    ///
+    /// See [`DigitalInput::set_press_mode`].
+    pub fn set_press_mode(&mut self, button: Button, mode: PressMode) {
+        self.digital_inputs.set_press_mode(button, mode);
+    }
+
+    /// Makes the physical `from` button activate `to` instead, such as for players with damaged
+    /// or non-standard controllers.
+    ///
+    /// Remapping happens before state is stored, so the game only sees `to`. To swap two
+    /// buttons, remap each to the other. Remapping a button to itself removes its remap.
+    pub fn remap(&mut self, from: Button, to: Button) {
+        self.digital_inputs.set_remap(from, to);
+    }
+
+    /// Gets the button that the physical `from` button activates.
+    pub fn remapped(&self, from: Button) -> Button {
+        self.digital_inputs.remapped(from)
+    }
+
+    /// Makes the physical `from` axis report to `to` instead.
+    ///
+    /// Like [`Self::remap`], this happens before state is stored, and before the axis
+    /// configuration and deadzones of `to` are applied.
+    pub fn remap_axis(&mut self, from: Axis, to: Axis) {
+        self.analog_inputs.set_remap(from, to);
+    }
+
+    /// Gets the axis that the physical `from` axis reports to.
+    pub fn remapped_axis(&self, from: Axis) -> Axis {
+        self.analog_inputs.remapped(from)
+    }
+
+    /// Removes every button and axis remap.
+    pub fn clear_remaps(&mut self) {
+        for button in BUTTONS {
+            self.remap(button, button);
+        }
+        for axis in AXES {
+            self.remap_axis(axis, axis);
+        }
+    }
+
+    /// Releases every button and centers every axis, with the corresponding just deactivated
+    /// edges, such as when the window loses focus and the backend stops reporting releases.
+    ///
+    /// Buttons that are still held stay released until they are pressed again, and axes read
+    /// `0.0` until they move again.
+    pub fn release_all(&mut self) {
+        self.digital_inputs.release_all();
+        self.analog_inputs.release_all();
+        self.touchpad_inputs.release_all();
+        self.raw_inputs.buttons.release_all();
+        self.raw_inputs.axes.release_all();
+        self.raw_inputs.hats.release_all();
+    }
+
+    /// Ignores the input of the gamepad, such as while its player types in a text field.
+    ///
+    /// While suppressed, every button reads as released and every axis as `0.0`, but the state
+    /// reported by the backend is still tracked. Once no longer suppressed, axes read their
+    /// current value, while buttons held since being suppressed stay released until pressed
+    /// again, so that no press or release is made up.
+    pub fn set_suppressed(&mut self, suppressed: bool) {
+        self.suppressed = suppressed;
+        self.apply_suppression();
+    }
+
+    /// Checks if the gamepad is suppressed.
+    ///
+    /// See [`Self::set_suppressed`].
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed
+    }
+
+    fn set_context_disabled(&mut self, disabled: bool) {
+        self.context_disabled = disabled;
+        self.apply_suppression();
+    }
+
+    fn apply_suppression(&mut self) {
+        let ignored = self.suppressed || self.context_disabled;
+        self.digital_inputs.set_all_suppressed(ignored);
+        self.analog_inputs.set_suppressed(ignored);
+        self.raw_inputs.buttons.set_all_suppressed(ignored);
+        self.raw_inputs.axes.set_suppressed(ignored);
+        self.raw_inputs.hats.set_all_suppressed(ignored);
+    }
+
+    /// Mirrors the left stick onto the D-pad buttons, so that menus checking only the D-pad
+    /// also work with the stick.
+    ///
+    /// A direction is pressed while the stick is outside the digital deadzone.
+    pub fn set_stick_to_dpad(&mut self, enabled: bool) {
+        self.stick_to_dpad = enabled;
+    }
+
+    /// Mirrors the D-pad buttons onto the left stick axes, so that movement code reading only
+    /// the stick also works with the D-pad.
+    pub fn set_dpad_to_stick(&mut self, enabled: bool) {
+        self.dpad_to_stick = enabled;
+    }
+
+    /// Checks if the left stick is mirrored onto the D-pad.
+    pub fn stick_to_dpad(&self) -> bool {
+        self.stick_to_dpad
+    }
+
+    /// Checks if the D-pad is mirrored onto the left stick.
+    pub fn dpad_to_stick(&self) -> bool {
+        self.dpad_to_stick
+    }
+
+    /// Cleans simultaneous opposing directions on the D-pad, or stops cleaning them with `None`.
+    ///
+    /// While opposing directions are held, the losing ones are deactivated. This also applies to
+    /// the stick values mirrored from the D-pad, see [`Self::set_dpad_to_stick`], which otherwise
+    /// cancel out.
+    pub fn set_socd(&mut self, socd: Option<Socd>) {
+        self.socd = socd;
+    }
+
+    /// Gets how simultaneous opposing directions on the D-pad are cleaned.
+    pub fn socd(&self) -> Option<Socd> {
+        self.socd
+    }
+
+    /// Records the last `capacity` directions held on the D-pad, to recognize motions such as
+    /// quarter-circles. `None` stops recording. Disabled by default.
+    ///
+    /// Directions are resolved like [`Self::set_socd`]. Enable [`Self::set_stick_to_dpad`] to
+    /// include the left stick.
+    pub fn set_input_history(&mut self, capacity: Option<usize>) {
+        self.history = capacity.map(InputHistory::new);
+    }
+
+    /// Gets the recorded directions, if recording is enabled.
+    ///
+    /// See [`Self::set_input_history`].
+    pub fn input_history(&self) -> Option<&InputHistory> {
+        self.history.as_ref()
+    }
+
+    /// Gets mutable access to the recorded directions, such as to clear them after a motion
+    /// triggered a move.
+    pub fn input_history_mut(&mut self) -> Option<&mut InputHistory> {
+        self.history.as_mut()
+    }
+
+    /// Watches the sticks for axes that rest off-center, reported with
+    /// [`GamepadEvent::DriftDetected`]. `None` stops watching. Disabled by default.
+    pub fn set_drift_detection(&mut self, detector: Option<DriftDetector>) {
+        self.drift = detector;
+    }
+
+    /// Gets the drift detector of the gamepad, such as to read which axes drift.
+    pub fn drift_detection(&self) -> Option<&DriftDetector> {
+        self.drift.as_ref()
+    }
+
+    /// Gets the latest motion sensor readings.
+    pub fn sensors(&self) -> &SensorInput {
+        &self.sensors
+    }
+
+    /// Checks if the gamepad has a motion sensor.
+    pub fn has_sensor(&self, sensor: Sensor) -> bool {
+        self.device
+            .as_ref()
+            .is_some_and(|device| device.has_sensor(sensor))
+    }
+
+    /// Enables or disables a motion sensor.
+    ///
+    /// Sensors are disabled by default, since reporting their data drains controller batteries.
+    pub fn set_sensor_enabled(&self, sensor: Sensor, enabled: bool) -> Result<()> {
+        match &self.device {
+            Some(device) => device.set_sensor_enabled(sensor, enabled),
+            None => Err("Motion sensors are not supported by this backend".into()),
+        }
+    }
+
+    /// Sets the color of the gamepad's lightbar, such as on DualShock 4 and DualSense controllers.
+    pub fn set_led(&self, red: u8, green: u8, blue: u8) -> Result<()> {
+        match &self.device {
+            Some(device) => device.set_led(red, green, blue),
+            None => Err("LEDs are not supported by this backend".into()),
+        }
+    }
+
+    /// Sets the player number shown by the gamepad's player indicator lights.
+    ///
+    /// Player indices start at `0`. `None` turns the indicator off.
+    pub fn set_player_index(&self, index: Option<u8>) -> Result<()> {
+        match &self.device {
+            Some(device) => device.set_player_index(index),
+            None => Err("Player indices are not supported by this backend".into()),
+        }
+    }
+
+    /// Sets the strength of the gamepad's rumble motors, from `0.0` (off) to `1.0`.
+    ///
+    /// The low frequency motor is usually on the left, the high frequency motor on the right.
+    /// Rumble continues until it is set again. Playing effects and rumble channels override it
+    /// on the next update.
+    pub fn set_rumble(&self, low_frequency: f32, high_frequency: f32) -> Result<()> {
+        match &self.device {
+            Some(device) => device.set_rumble(low_frequency, high_frequency),
+            None => Err("Rumble is not supported by this backend".into()),
+        }
+    }
+
+    /// Checks if the gamepad's triggers can play [`TriggerEffect`]s, like on DualSense controllers.
+    pub fn has_adaptive_triggers(&self) -> bool {
+        self.device
+            .as_ref()
+            .is_some_and(|device| device.has_adaptive_triggers())
+    }
+
+    /// Sets the effect played by the motor of a trigger, which lasts until it is set again.
+    ///
+    /// `trigger` is [`Axis::TriggerLeft`] or [`Axis::TriggerRight`]. Does nothing on gamepads
+    /// without adaptive triggers, so effects can be set regardless of the controller. Through
+    /// SDL2, effects need SDL 2.0.16 or newer at runtime, and fail with older versions.
+    pub fn set_trigger_effect(&self, trigger: Axis, effect: TriggerEffect) -> Result<()> {
+        if !matches!(trigger, Axis::TriggerLeft | Axis::TriggerRight) {
+            return Err(format!("{trigger:?} is not a trigger"));
+        }
+
+        match &self.device {
+            Some(device) => device.set_trigger_effect(trigger, effect),
+            None => Ok(()),
+        }
+    }
+
+    /// Queues a haptic effect, to be played once the effects queued before it have finished.
+    pub fn play_effect(&self, effect: HapticEffect) -> Result<()> {
+        if self.device.is_none() {
+            return Err("Rumble is not supported by this backend".into());
+        }
+
+        self.haptics.borrow_mut().push(effect);
+        Ok(())
+    }
+
+    /// Rumbles on a channel until it is stopped, replacing what the channel played before.
+    ///
+    /// Unlike [`Self::set_rumble`], channels are mixed: the channels with the highest priority
+    /// are added together and clamped, and channels with lower priorities are muted while they
+    /// play. Effects played with [`Self::play_effect`] have priority `0`.
+    pub fn set_channel_rumble(
+        &self,
+        channel: RumbleChannel,
+        priority: i32,
+        low_frequency: f32,
+        high_frequency: f32,
+    ) -> Result<()> {
+        if self.device.is_none() {
+            return Err("Rumble is not supported by this backend".into());
+        }
+
+        let strengths = (low_frequency, high_frequency);
+        self.haptics
+            .borrow_mut()
+            .set_channel(channel, priority, strengths);
+        Ok(())
+    }
+
+    /// Plays a haptic effect on a channel, replacing what the channel played before. The channel
+    /// stops once the effect ends.
+    ///
+    /// See [`Self::set_channel_rumble`].
+    pub fn play_channel_effect(
+        &self,
+        channel: RumbleChannel,
+        priority: i32,
+        effect: HapticEffect,
+    ) -> Result<()> {
+        if self.device.is_none() {
+            return Err("Rumble is not supported by this backend".into());
+        }
+
+        self.haptics
+            .borrow_mut()
+            .play_on_channel(channel, priority, effect);
+        Ok(())
+    }
+
+    /// Stops a rumble channel.
+    pub fn stop_channel(&self, channel: RumbleChannel) {
+        self.haptics.borrow_mut().stop_channel(channel);
+    }
+
+    /// Stops the playing haptic effect, discards the queued ones, stops every rumble channel
+    /// and turns off rumble.
+    pub fn stop_effects(&self) -> Result<()> {
+        self.haptics.borrow_mut().clear();
+        self.set_rumble(0.0, 0.0)
+    }
+
+    /// Checks if a haptic effect is playing or queued, or a rumble channel is playing.
+    pub fn is_playing_effect(&self) -> bool {
+        self.haptics.borrow().is_playing()
+    }
+
+    /// Captures the current input state of the gamepad.
+    pub fn state(&self) -> GamepadState {
+        GamepadState::capture(self)
+    }
+
+    /// Captures a compact, fixed-size copy of the current input state of the gamepad.
+    pub fn snapshot(&self) -> GamepadSnapshot {
+        GamepadSnapshot::capture(self)
+    }
+
+    /// Checks if a button or axis changed during the last update, or if the gamepad connected.
+    ///
+    /// Axes are compared after deadzone remapping, so noise within the deadzone is not a change.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Gets how far a stick is pushed, from `0.0` to `1.0`, after deadzones.
+    ///
+    /// Sticks that reach past the circle in their corners are clamped to `1.0`.
+    pub fn stick_magnitude(&self, stick: Stick) -> f32 {
+        let x = self.analog_inputs.value(stick.x());
+        let y = self.analog_inputs.value(stick.y());
+        x.hypot(y).min(1.0)
+    }
+
+    /// Gets the direction a stick is pushed in, in radians, after deadzones. `0.0` is straight
+    /// up, and angles are positive clockwise, from `-PI` to `PI`.
+    ///
+    /// Returns `None` if the stick is within its deadzone.
+    pub fn stick_angle(&self, stick: Stick) -> Option<f32> {
+        let x = self.analog_inputs.value(stick.x());
+        let y = self.analog_inputs.value(stick.y());
+        // Vertical axes are positive downwards, so up is negative.
+        (x != 0.0 || y != 0.0).then(|| x.atan2(-y))
+    }
+
+    /// Gets how long the gamepad has had no input, or how long it has been connected if it has
+    /// had none.
+    ///
+    /// A gamepad has input while a button is activated, and whenever it [changed](Self::changed).
+    pub fn idle_for(&self) -> Duration {
+        self.last_input.elapsed()
+    }
+
+    fn update_changed(&mut self) {
+        let snapshot = self.snapshot();
+        self.changed = self.last_snapshot != Some(snapshot);
+        self.last_snapshot = Some(snapshot);
+        if self.changed || self.digital_inputs.any_activated() {
+            self.last_input = Instant::now();
         }
     }
 
     fn update_inputs(&mut self) {
         self.analog_inputs.update();
         self.digital_inputs.update();
+        self.touchpad_inputs.update();
+        self.raw_inputs.update();
+    }
+
+    fn update_mirroring(&mut self) {
+        // Only physical inputs are mirrored, so that both directions can be enabled at once.
+        let x = self.analog_inputs.physical_digital_value(Axis::LeftX);
+        let y = self.analog_inputs.physical_digital_value(Axis::LeftY);
+        let stick = self.stick_to_dpad;
+        let digital_inputs = &mut self.digital_inputs;
+        digital_inputs.set_emulated(Button::DPadLeft, stick && x < 0.0);
+        digital_inputs.set_emulated(Button::DPadRight, stick && x > 0.0);
+        digital_inputs.set_emulated(Button::DPadUp, stick && y < 0.0);
+        digital_inputs.set_emulated(Button::DPadDown, stick && y > 0.0);
+
+        let socd = self.socd.unwrap_or_default();
+        let dpad_axis = |negative, positive| {
+            let value = socd.resolve(
+                digital_inputs.physically_held_since(negative),
+                digital_inputs.physically_held_since(positive),
+            );
+            match value {
+                -1 => Some(AnalogInputValue::from(ANALOG_MIN)),
+                1 => Some(AnalogInputValue::from(ANALOG_MAX)),
+                _ => None,
+            }
+        };
+        let (x, y) = if self.dpad_to_stick {
+            (
+                dpad_axis(Button::DPadLeft, Button::DPadRight),
+                dpad_axis(Button::DPadUp, Button::DPadDown),
+            )
+        } else {
+            (None, None)
+        };
+        self.analog_inputs.set_override(Axis::LeftX, x);
+        self.analog_inputs.set_override(Axis::LeftY, y);
+    }
+
+    fn update_socd(&mut self) {
+        let pairs = [
+            (Button::DPadLeft, Button::DPadRight),
+            (Button::DPadUp, Button::DPadDown),
+        ];
+        for (negative, positive) in pairs {
+            let negative_since = self.digital_inputs.held_since(negative);
+            let positive_since = self.digital_inputs.held_since(positive);
+            let (suppress_negative, suppress_positive) = match self.socd {
+                Some(socd) if negative_since.is_some() && positive_since.is_some() => {
+                    let value = socd.resolve(negative_since, positive_since);
+                    (value != -1, value != 1)
+                }
+                _ => (false, false),
+            };
+
+            self.digital_inputs
+                .set_suppressed(negative, suppress_negative);
+            self.digital_inputs
+                .set_suppressed(positive, suppress_positive);
+        }
+    }
+
+    fn update_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            let direction = self
+                .digital_inputs
+                .dpad_direction(self.socd.unwrap_or_default());
+            history.update(direction, self.digital_inputs.tick());
+        }
+    }
+
+    /// Checks the sticks for drift, and gets the axes that were found drifting.
+    fn update_drift(&mut self) -> Vec<(Axis, f32)> {
+        let Some(drift) = &mut self.drift else {
+            return Vec::new();
+        };
+
+        let mut detected = Vec::new();
+        for (axis, offset) in drift.update(&self.analog_inputs, Instant::now()) {
+            if drift.correction() {
+                self.analog_inputs
+                    .config_mut(axis)
+                    .center(offset.unwrap_or(0.0));
+            }
+            if let Some(offset) = offset {
+                detected.push((axis, offset));
+            }
+        }
+        detected
+    }
+
+    /// Gets the device of the gamepad, if its backend uses `D`.
+    // Unused when only backends without devices are enabled.
+    #[allow(dead_code)]
+    fn device<D: 'static>(&self) -> Option<&D> {
+        self.device.as_ref()?.as_any().downcast_ref()
+    }
+
+    fn update_haptics(&self) {
+        if let Some((low_frequency, high_frequency)) = self.haptics.borrow_mut().update() {
+            // Effects keep playing if the device briefly fails, like a disconnecting gamepad.
+            let _ = self.set_rumble(low_frequency, high_frequency);
+        }
     }
 }
 
 /// The gamepad system context.
 ///
-/// Only one `GamepadContext` should be alive at any time.
+/// The SDL2 and Steam backends only run once per process, so only one context at a time can
+/// run each of them. Creating another one fails, or falls back to the next backend when several
+/// are tried. Other backends run in as many contexts as needed, such as one per test.
+/// To share a context between several consumers, convert it into a [`GamepadContextHandle`].
 pub struct GamepadContext {
-    gamepad_system: ImplementationContext,
+    gamepad_system: Box<dyn Backend>,
+    // The backends the context runs on, in order of preference.
+    backends: Vec<BackendKind>,
     gamepads: HashMap<GamepadId, Gamepad>,
+    admin: HashSet<DeviceFingerprint>,
+    config: Config,
+    // Incremented whenever the config changes, so gamepads know when to apply it again.
+    config_generation: u64,
+    #[cfg(feature = "config")]
+    config_file: Option<ConfigFile>,
+    profiles: HashMap<GamepadId, Profile>,
+    // Whether edge flags are kept until `end_frame` rather than cleared on every update.
+    manual_frames: bool,
+    // Whether a frame was sampled since the last pump, so the next pump starts a new frame.
+    sampled: bool,
+    latching: bool,
+    quantization: Option<Quantization>,
+    drift: Option<DriftDetector>,
+    enabled: bool,
+    admission: Admission,
+    // The gamepad that last produced input.
+    primary: Option<GamepadId>,
+    created_at: Instant,
+    last_input: Option<Instant>,
+    shared_state: Option<SharedState>,
+    events: EventQueue,
+    update_report: UpdateReport,
+    latency: Option<LatencyTracker>,
+    // Gamepads that connect callbacks have been called for.
+    connected: HashSet<GamepadId>,
+    connect_callbacks: Vec<ConnectCallback>,
+    disconnect_callbacks: Vec<DisconnectCallback>,
+    observers: Vec<(Button, ObserverCallback)>,
+    // The task waiting for events, woken once an update queues some.
+    #[cfg(feature = "async")]
+    waker: Option<std::task::Waker>,
+}
+
+type ConnectCallback = Box<dyn FnMut(GamepadId, &Gamepad)>;
+type DisconnectCallback = Box<dyn FnMut(GamepadId)>;
+type ObserverCallback = Box<dyn FnMut(GamepadId, ButtonEdge)>;
+
+#[cfg(feature = "config")]
+struct ConfigFile {
+    path: std::path::PathBuf,
+    modified: Option<std::time::SystemTime>,
 }
 
 impl GamepadContext {
-    /// Initializes the gamepad context.
+    /// Initializes the gamepad context with the first backend that works.
+    ///
+    /// Backends are tried in the order of [`BackendKind::AVAILABLE`],
+    /// so a build with both SDL2 and gilrs falls back to gilrs when SDL2 is missing.
+    /// Returns an error if no backend could be initialized.
+    ///
+    /// Use [`Self::builder`] to configure the context before gamepads connect.
     pub fn init() -> Result<Self> {
-        let gamepad_system = ImplementationContext::new()?;
-        let gamepads = HashMap::new();
+        Self::builder().build()
+    }
 
-        Ok(Self {
+    /// Creates a builder, which configures the context before it is initialized.
+    pub fn builder() -> GamepadContextBuilder {
+        GamepadContextBuilder::new()
+    }
+
+    /// Initializes the gamepad context with a specific backend.
+    ///
+    /// Returns an error if the backend could not be initialized, such as when another context
+    /// runs SDL2.
+    pub fn init_with(backend: BackendKind) -> Result<Self> {
+        Self::builder().backends(&[backend]).build()
+    }
+
+    /// Initializes the gamepad context with several backends running side by side, such as
+    /// SDL2 and gilrs, so that devices only one of them recognizes still appear.
+    ///
+    /// Backends are given in order of preference. A device that several backends report only
+    /// appears once, from the backend that reported it first, preferring earlier backends when
+    /// they report it within the same update. Devices are matched by the bus, vendor and product
+    /// of their GUID, so that several gamepads of the same model are reported by the same
+    /// backend. Gamepad Ids are assigned by the context rather than by the backends.
+    ///
+    /// Returns an error if any of the backends fails to initialize.
+    pub fn init_merged(backends: &[BackendKind]) -> Result<Self> {
+        Self::builder().backends(backends).merged(true).build()
+    }
+
+    fn with_system(backends: Vec<BackendKind>, gamepad_system: Box<dyn Backend>) -> Self {
+        Self {
             gamepad_system,
-            gamepads,
-        })
+            backends,
+            gamepads: HashMap::new(),
+            admin: HashSet::new(),
+            config: Config::default(),
+            // Gamepads start at generation 0, so that the config is applied to them.
+            config_generation: 1,
+            #[cfg(feature = "config")]
+            config_file: None,
+            profiles: HashMap::new(),
+            manual_frames: false,
+            sampled: false,
+            latching: false,
+            quantization: None,
+            drift: None,
+            enabled: true,
+            admission: Admission::default(),
+            primary: None,
+            created_at: Instant::now(),
+            last_input: None,
+            shared_state: None,
+            events: EventQueue::default(),
+            update_report: UpdateReport::default(),
+            latency: None,
+            connected: HashSet::new(),
+            connect_callbacks: Vec::new(),
+            disconnect_callbacks: Vec::new(),
+            observers: Vec::new(),
+            #[cfg(feature = "async")]
+            waker: None,
+        }
     }
 
-    /// Gets a reference to a specific gamepad.
+    /// Initializes the gamepad context without opening any physical gamepads.
     ///
-    /// Returns `None` if the gamepad is not found.
-    pub fn gamepad(&self, id: GamepadId) -> Option<&Gamepad> {
-        self.gamepads.get(&id)
+    /// The backend runs as usual, but behaves as if no gamepads were ever connected.
+    /// This keeps integration tests independent of whatever controllers happen to be plugged in.
+    pub fn init_without_devices() -> Result<Self> {
+        Self::builder().without_devices().build()
     }
 
-    /// Gets an iterator over all gamepads.
-    pub fn gamepads(&self) -> impl Iterator<Item = (GamepadId, &Gamepad)> {
-        self.gamepads.iter().map(|(&id, gamepad)| (id, gamepad))
+    /// Initializes the gamepad context on top of an existing SDL2 context.
+    ///
+    /// The context will not poll SDL2 events on its own.
+    /// Forward controller events from the application's event pump with [`Self::feed_event`].
+    #[cfg(feature = "sdl2")]
+    pub fn from_sdl(sdl_context: &sdl2::Sdl) -> Result<Self> {
+        let gamepad_system = backend::sdl2::ImplementationContext::from_sdl(sdl_context)?;
+
+        Ok(Self::with_system(
+            vec![BackendKind::Sdl2],
+            Box::new(gamepad_system),
+        ))
     }
 
-    /// Updates the state of all gamepads.
-    pub fn update(&mut self) -> Result<()> {
-        self.gamepad_system.update(&mut self.gamepads)
+    /// Opens joysticks without a controller mapping as raw gamepads, such as flight sticks,
+    /// arcade sticks and DIY pads.
+    ///
+    /// Raw gamepads only report numbered inputs, see [`Gamepad::raw_inputs`].
+    /// Supported by the SDL2 and web backends. On the web, gamepads without the standard mapping
+    /// are raw. Takes effect on the next [`Self::update`].
+    pub fn set_raw_joysticks(&mut self, enabled: bool) -> Result<()> {
+        self.gamepad_system.set_raw_joysticks(enabled)
     }
 
-    /// Sets the analog deadzone for all analog inputs.
+    /// Selects the Steam Input action set that gamepads read, such as to switch between menu
+    /// and gameplay controls. The default action set is `"fishsticks"`.
     ///
-    /// Positive values outside the deadzone get remapped to the range `[0.0, ANALOG_MAX]`.\
-    /// Negative values get remapped to `[ANALOG_MIN, 0.0]`.
-    pub fn set_deadzone(&mut self, deadzone: f32) {
-        for (_, gamepad) in self.gamepads.iter_mut() {
-            let deadzone = Deadzone::from(AnalogInputValue::from(deadzone));
-            gamepad.analog_inputs.set_deadzone(deadzone);
-        }
+    /// The set needs the same actions as the default one, see [`BackendKind::Steam`]. Only
+    /// supported by the Steam backend. Takes effect on the next [`Self::update`].
+    #[cfg(feature = "steam")]
+    pub fn set_steam_action_set(&mut self, name: &str) -> Result<()> {
+        self.gamepad_system.set_steam_action_set(name)
     }
 
-    /// Sets the digital deadzone for all analog inputs.
-    pub fn set_digital_deadzone(&mut self, deadzone: f32) {
-        for (_, gamepad) in self.gamepads.iter_mut() {
-            let deadzone = Deadzone::from(AnalogInputValue::from(deadzone));
-            gamepad.analog_inputs.set_digital_deadzone(deadzone);
+    /// Gets the backend the context runs on, or the preferred one when it runs on several.
+    ///
+    /// Until lazy backends started, gets the first backend they try.
+    pub fn backend(&self) -> BackendKind {
+        self.backends()[0]
+    }
+
+    /// Gets the backends the context runs on, in order of preference.
+    ///
+    /// See [`Self::init_merged`]. Until lazy backends started, gets the backends they try.
+    pub fn backends(&self) -> &[BackendKind] {
+        self.gamepad_system.kinds().unwrap_or(&self.backends)
+    }
+
+    /// Gets whether the backend runs. Always [`BackendState::Ready`] unless the context was
+    /// built with [`GamepadContextBuilder::lazy`].
+    pub fn backend_state(&self) -> BackendState {
+        self.gamepad_system.state()
+    }
+
+    /// Gets the backend that reports a gamepad.
+    pub fn gamepad_backend(&self, id: GamepadId) -> Option<BackendKind> {
+        self.gamepads
+            .contains_key(&id)
+            .then(|| self.gamepad_system.backend_of(id).unwrap_or(self.backend()))
+    }
+
+    /// Finds the connected gamepad with a fingerprint, such as one saved in a previous run to
+    /// reattach the settings of a controller.
+    ///
+    /// A fingerprint with a serial number only matches the gamepad with the same serial number.
+    /// One without matches the first gamepad with the same GUID, by Id.
+    pub fn find_by_fingerprint(&self, fingerprint: &DeviceFingerprint) -> Option<GamepadId> {
+        self.gamepads
+            .iter()
+            .filter(|(_, gamepad)| {
+                gamepad.guid == fingerprint.guid
+                    && (fingerprint.serial.is_none() || gamepad.serial == fingerprint.serial)
+            })
+            .map(|(&id, _)| id)
+            .min()
+    }
+
+    /// Converts the context into a handle that can be cloned and shared between consumers.
+    pub fn into_handle(self) -> GamepadContextHandle {
+        GamepadContextHandle(Rc::new(RefCell::new(self)))
+    }
+
+    /// Forwards an SDL2 event from the application's event pump.
+    ///
+    /// Non-controller events are ignored. Forwarded events are applied on the next [`Self::update`].
+    #[cfg(feature = "sdl2")]
+    pub fn feed_event(&mut self, event: &sdl2::event::Event) {
+        self.gamepad_system.feed_event(event);
+    }
+
+    /// Takes the non-controller SDL2 events of the last [`Self::update`], such as keyboard,
+    /// mouse, window and quit events.
+    ///
+    /// The SDL2 backend owns the only event pump, so this is how applications receive them.
+    /// Events that are not taken before the next update are discarded. Always empty when the
+    /// context was created with [`Self::from_sdl`], or runs on another backend.
+    #[cfg(feature = "sdl2")]
+    pub fn other_events(&mut self) -> impl Iterator<Item = sdl2::event::Event> + '_ {
+        self.gamepad_system
+            .other_events()
+            .into_iter()
+            .flat_map(|events| events.drain(..))
+    }
+
+    /// Gets a reference to a specific gamepad.
+    ///
+    /// Returns `None` if the gamepad is not found.
+    pub fn gamepad(&self, id: GamepadId) -> Option<&Gamepad> {
+        self.note_query();
+        self.gamepads.get(&id)
+    }
+
+    /// Gets a mutable reference to a specific gamepad, such as to configure it.
+    ///
+    /// Returns `None` if the gamepad is not found.
+    pub fn gamepad_mut(&mut self, id: GamepadId) -> Option<&mut Gamepad> {
+        self.note_query();
+        self.gamepads.get_mut(&id)
+    }
+
+    /// Gets an iterator over all gamepads.
+    pub fn gamepads(&self) -> impl Iterator<Item = (GamepadId, &Gamepad)> {
+        self.note_query();
+        self.gamepads.iter().map(|(&id, gamepad)| (id, gamepad))
+    }
+
+    /// Gets a mutable iterator over all gamepads.
+    pub fn gamepads_mut(&mut self) -> impl Iterator<Item = (GamepadId, &mut Gamepad)> {
+        self.note_query();
+        self.gamepads.iter_mut().map(|(&id, gamepad)| (id, gamepad))
+    }
+
+    /// Iterates over the gamepads whose buttons or axes changed during the last update.
+    ///
+    /// See [`Gamepad::changed`].
+    pub fn changed_gamepads(&self) -> impl Iterator<Item = (GamepadId, &Gamepad)> {
+        self.gamepads().filter(|(_, gamepad)| gamepad.changed())
+    }
+
+    /// Gets the number of connected gamepads.
+    pub fn len(&self) -> usize {
+        self.gamepads.len()
+    }
+
+    /// Checks if no gamepads are connected.
+    pub fn is_empty(&self) -> bool {
+        self.gamepads.is_empty()
+    }
+
+    /// Gets the connected gamepad with the lowest Id, which is usually the one that
+    /// connected first.
+    pub fn first(&self) -> Option<(GamepadId, &Gamepad)> {
+        self.gamepads().min_by_key(|&(id, _)| id)
+    }
+
+    /// Gets a button that has just been activated on any gamepad, such as for
+    /// "press any button to join" screens.
+    ///
+    /// If several buttons have just been activated, the one on the gamepad with the lowest Id
+    /// is returned.
+    pub fn any_button_just_activated(&self) -> Option<(GamepadId, Button)> {
+        self.gamepads()
+            .filter_map(|(id, gamepad)| {
+                BUTTONS
+                    .iter()
+                    .find(|&&button| gamepad.digital_inputs.just_activated(button))
+                    .map(|&button| (id, button))
+            })
+            .min_by_key(|&(id, _)| id)
+    }
+
+    /// Gets the gamepad that last produced input, for single-player games that accept
+    /// input from any gamepad.
+    ///
+    /// Until a gamepad produces input, or after the primary gamepad disconnects,
+    /// this is the gamepad returned by [`Self::first`].
+    pub fn primary(&self) -> Option<(GamepadId, &Gamepad)> {
+        self.note_query();
+        self.primary
+            .and_then(|id| Some((id, self.gamepads.get(&id)?)))
+            .or_else(|| self.first())
+    }
+
+    /// Gets mutable access to the gamepad that last produced input.
+    ///
+    /// See [`Self::primary`].
+    pub fn primary_mut(&mut self) -> Option<&mut Gamepad> {
+        let (id, _) = self.primary()?;
+        self.gamepads.get_mut(&id)
+    }
+
+    fn update_primary(&mut self) {
+        if self
+            .primary
+            .is_some_and(|id| self.gamepads.get(&id).is_some_and(has_new_input))
+        {
+            return;
+        }
+
+        let active = self
+            .gamepads()
+            .filter(|(_, gamepad)| has_new_input(gamepad))
+            .map(|(id, _)| id)
+            .min();
+        if active.is_some() {
+            self.primary = active;
+        }
+    }
+
+    /// Gets when any gamepad last had input, for attract modes, screensavers and auto-pause.
+    ///
+    /// A gamepad has input during an update if its buttons or axes changed, or if any of its
+    /// buttons is held. Returns `None` until the first input.
+    pub fn last_input_instant(&self) -> Option<Instant> {
+        self.last_input
+    }
+
+    /// Gets how long no gamepad has had input, or how long the context has existed if none has.
+    ///
+    /// See [`Self::last_input_instant`].
+    pub fn idle_for(&self) -> Duration {
+        self.last_input.unwrap_or(self.created_at).elapsed()
+    }
+
+    fn update_idle(&mut self) {
+        let active = self
+            .gamepads
+            .values()
+            .any(|gamepad| gamepad.changed() || gamepad.digital_inputs.any_activated());
+        if active {
+            self.last_input = Some(Instant::now());
+        }
+    }
+
+    /// Gets an iterator over all gamepads that are not admin gamepads.
+    ///
+    /// Use this instead of [`Self::gamepads`] when assigning gamepads to players.
+    pub fn player_gamepads(&self) -> impl Iterator<Item = (GamepadId, &Gamepad)> {
+        self.gamepads()
+            .filter(|(_, gamepad)| !self.is_admin_gamepad(gamepad))
+    }
+
+    /// Marks the gamepad with the given fingerprint as an admin gamepad, or unmarks it.
+    ///
+    /// Admin gamepads, such as a tournament organizer's pad, stay fully readable,
+    /// but are left out of [`Self::player_gamepads`] so they never take a player slot.
+    ///
+    /// Fingerprints have to match exactly. Gamepads that report no serial number have a
+    /// fingerprint of only their GUID, so marking one marks every gamepad of that model without
+    /// a serial number.
+    pub fn set_admin(&mut self, fingerprint: DeviceFingerprint, admin: bool) {
+        if admin {
+            self.admin.insert(fingerprint);
+        } else {
+            self.admin.remove(&fingerprint);
+        }
+    }
+
+    /// Checks if a gamepad is an admin gamepad.
+    pub fn is_admin(&self, id: GamepadId) -> bool {
+        self.gamepads
+            .get(&id)
+            .is_some_and(|gamepad| self.is_admin_gamepad(gamepad))
+    }
+
+    fn is_admin_gamepad(&self, gamepad: &Gamepad) -> bool {
+        let fingerprint = gamepad.fingerprint();
+        self.admin.contains(&fingerprint) || self.config.admin.contains(&fingerprint)
+    }
+
+    /// Gets the current configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Replaces the configuration.
+    ///
+    /// The configuration is applied to all gamepads, including ones that connect later.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+        self.config_generation += 1;
+        self.apply_config();
+    }
+
+    /// Loads the configuration from a TOML file and applies it.
+    ///
+    /// The file can be reloaded later with [`Self::reload_config`].
+    #[cfg(feature = "config")]
+    pub fn load_config(&mut self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let config = Config::load(&path)?;
+
+        self.config_file = Some(ConfigFile { path, modified });
+        self.set_config(config);
+
+        Ok(())
+    }
+
+    /// Reloads the configuration file if it changed since it was last loaded.
+    ///
+    /// Returns `true` if the configuration was reloaded. Call this periodically to hot-reload.
+    #[cfg(feature = "config")]
+    pub fn reload_config(&mut self) -> Result<bool> {
+        let config_file = match &mut self.config_file {
+            Some(config_file) => config_file,
+            None => return Ok(false),
+        };
+
+        let modified = std::fs::metadata(&config_file.path)
+            .and_then(|m| m.modified())
+            .ok();
+        if modified.is_some() && modified == config_file.modified {
+            return Ok(false);
+        }
+
+        let config = Config::load(&config_file.path)?;
+        config_file.modified = modified;
+        self.set_config(config);
+
+        Ok(true)
+    }
+
+    /// Assigns a player's profile to a gamepad, replacing the profile assigned to it before.
+    ///
+    /// The profile's settings are applied on top of the configuration. Settings it leaves unset
+    /// fall back to the configuration. The assignment ends when the gamepad disconnects.
+    pub fn assign_profile(&mut self, id: GamepadId, profile: Profile) {
+        self.profiles.insert(id, profile);
+        if let Some(gamepad) = self.gamepads.get_mut(&id) {
+            configure(&self.config, self.profiles.get(&id), gamepad);
+        }
+    }
+
+    /// Gets the profile assigned to a gamepad.
+    pub fn profile(&self, id: GamepadId) -> Option<&Profile> {
+        self.profiles.get(&id)
+    }
+
+    /// Gets the inputs bound to an action on a gamepad, from the profile assigned to it or else
+    /// from the configuration.
+    pub fn binding(&self, id: GamepadId, action: &str) -> &[Input] {
+        let profile = self.profiles.get(&id).map(|profile| &profile.bindings);
+        profile
+            .and_then(|bindings| bindings.get(action))
+            .or_else(|| self.config.bindings.get(action))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks if any input bound to an action is activated on a gamepad.
+    ///
+    /// See [`Self::binding`].
+    pub fn action_activated(&self, id: GamepadId, action: &str) -> bool {
+        self.gamepads.get(&id).is_some_and(|gamepad| {
+            self.binding(id, action)
+                .iter()
+                .any(|input| input.activated(gamepad))
+        })
+    }
+
+    /// Checks if any input bound to an action has just been activated on a gamepad.
+    ///
+    /// See [`Self::binding`].
+    pub fn action_just_activated(&self, id: GamepadId, action: &str) -> bool {
+        self.gamepads.get(&id).is_some_and(|gamepad| {
+            self.binding(id, action)
+                .iter()
+                .any(|input| input.just_activated(gamepad))
+        })
+    }
+
+    /// Checks if any input bound to an action has just been deactivated on a gamepad.
+    ///
+    /// See [`Self::binding`].
+    pub fn action_just_deactivated(&self, id: GamepadId, action: &str) -> bool {
+        self.gamepads.get(&id).is_some_and(|gamepad| {
+            self.binding(id, action)
+                .iter()
+                .any(|input| input.just_deactivated(gamepad))
+        })
+    }
+
+    /// Queues the haptic effect that the configuration names `cue` on a gamepad.
+    ///
+    /// See [`Gamepad::play_effect`].
+    pub fn play_cue(&self, id: GamepadId, cue: &str) -> Result<()> {
+        let effect = self
+            .config
+            .haptics
+            .get(cue)
+            .ok_or_else(|| format!("No haptic cue named \"{}\"", cue))?;
+        let gamepad = self
+            .gamepads
+            .get(&id)
+            .ok_or_else(|| format!("No gamepad with id {}", id.0))?;
+        gamepad.play_effect(effect.clone())
+    }
+
+    fn apply_config(&mut self) {
+        for (id, gamepad) in self.gamepads.iter_mut() {
+            if gamepad.config_generation != self.config_generation {
+                configure(&self.config, self.profiles.get(id), gamepad);
+                gamepad.config_generation = self.config_generation;
+            }
+        }
+    }
+
+    /// Updates the state of all gamepads, like [`Self::pump`] followed by [`Self::sample`].
+    ///
+    /// Just activated and just deactivated inputs are reset first, unless frames are ended
+    /// manually. See [`Self::set_manual_frames`].
+    ///
+    /// The cost of an update grows with the number of backend events and gamepads. Buffers are
+    /// reused between updates, so once every gamepad has reported its inputs, updates only
+    /// allocate when gamepads connect or disconnect, the config changes, or the backend rescans
+    /// its devices.
+    ///
+    /// Returns an error if a backend failed, like [`Self::pump`]. The gamepads of other backends
+    /// are updated anyway.
+    pub fn update(&mut self) -> Result<()> {
+        if !self.manual_frames {
+            self.sampled = true;
+        }
+        let result = self.pump();
+        if !self.manual_frames {
+            self.sample();
+        }
+
+        result
+    }
+
+    /// Reads the pending input of the backend, without ending a frame. Call it as often as
+    /// needed, such as from every iteration of a winit or SDL2 event loop.
+    ///
+    /// Gamepads reflect the input right away, but just activated and just deactivated inputs and
+    /// events accumulate over pumps until the next [`Self::sample`]. The first pump after a
+    /// sample starts a new frame, resetting them.
+    ///
+    /// Returns an error if a backend failed to read its gamepads, after updating every other
+    /// gamepad. Gamepads that fail on their own are only reported by [`Self::update_report`].
+    pub fn pump(&mut self) -> Result<()> {
+        if std::mem::take(&mut self.sampled) {
+            for gamepad in self.gamepads.values_mut() {
+                gamepad.update_inputs();
+            }
+        }
+        // Gamepads that wait to join have nobody to end their frames.
+        for gamepad in self.admission.parked_mut() {
+            gamepad.update_inputs();
+        }
+
+        if let Some(latency) = &mut self.latency {
+            latency.start_update();
+        }
+        self.admission.unpark(&mut self.gamepads);
+        self.update_report = self.gamepad_system.update(&mut self.gamepads);
+        self.admission.update(&mut self.gamepads);
+        for event in self.admission.take_events() {
+            self.events.push(event);
+        }
+        if let Some(latency) = &mut self.latency {
+            latency.finish_polling(&self.gamepads);
+        }
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.digital_inputs.set_latching(self.latching);
+            // Without a grid for all gamepads, gamepads may have grids of their own.
+            if self.quantization.is_some() {
+                gamepad.analog_inputs.set_quantization(self.quantization);
+            }
+            if gamepad.drift.is_none() {
+                gamepad.drift.clone_from(&self.drift);
+            }
+            gamepad.set_context_disabled(!self.enabled);
+        }
+        self.profiles.retain(|id, _| self.gamepads.contains_key(id));
+        self.update_primary();
+        self.apply_config();
+        for (&id, gamepad) in &mut self.gamepads {
+            gamepad.analog_inputs.smooth();
+            gamepad.raw_inputs.axes.smooth();
+            gamepad.update_mirroring();
+            gamepad.update_virtual_buttons();
+            gamepad.update_socd();
+            gamepad.update_history();
+            for (axis, offset) in gamepad.update_drift() {
+                self.events
+                    .push(GamepadEvent::DriftDetected { id, axis, offset });
+            }
+            gamepad.update_haptics();
+            gamepad.update_changed();
+        }
+        self.update_idle();
+        self.run_hotplug_callbacks();
+
+        if let Some(shared_state) = &self.shared_state {
+            shared_state.publish(&self.gamepads);
+        }
+        if let Some(latency) = &mut self.latency {
+            latency.finish_update();
+        }
+
+        match &self.update_report.backend_errors[..] {
+            [] => Ok(()),
+            errors => Err(errors.join(", ")),
+        }
+    }
+
+    /// Gets what the backends did during the last [`Self::pump`], including the errors of
+    /// single gamepads that did not keep the others from updating.
+    pub fn update_report(&self) -> &UpdateReport {
+        &self.update_report
+    }
+
+    /// Commits the input pumped since the last sample as a frame, once per tick of the game
+    /// logic: queues the events of the frame, and keeps just activated and just deactivated inputs
+    /// until the next [`Self::pump`].
+    ///
+    /// See [`Self::pump`].
+    pub fn sample(&mut self) {
+        self.queue_events();
+        self.sampled = true;
+    }
+
+    /// Queues the events of the frame, passes them to the observers, and wakes the task waiting
+    /// for them.
+    fn queue_events(&mut self) {
+        self.events.update(&self.gamepads);
+        self.run_observers();
+        #[cfg(feature = "async")]
+        if self.events.queued().next().is_some() {
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
         }
     }
+
+    /// Enables or disables latency tracking, which measures how long button presses take to
+    /// reach the game, and how many presses the game never sees.
+    ///
+    /// A press counts as read once the game reads the gamepads or events of the context after it
+    /// was received. Enabling tracking clears previous measurements.
+    pub fn set_latency_tracking(&mut self, enabled: bool) {
+        self.latency = enabled.then(LatencyTracker::default);
+    }
+
+    /// Gets the latency measurements, if tracking is enabled.
+    ///
+    /// See [`Self::set_latency_tracking`].
+    pub fn latency_stats(&self) -> Option<&LatencyStats> {
+        self.latency.as_ref().map(|latency| &latency.stats)
+    }
+
+    /// Gets mutable access to the latency measurements, such as to reset them.
+    pub fn latency_stats_mut(&mut self) -> Option<&mut LatencyStats> {
+        self.latency.as_mut().map(|latency| &mut latency.stats)
+    }
+
+    fn note_query(&self) {
+        if let Some(latency) = &self.latency {
+            latency.query();
+        }
+    }
+
+    /// Calls `callback` with every gamepad that connects, during [`Self::update`].
+    ///
+    /// The gamepad already has the config and its profile applied. Gamepads that were reported by
+    /// an earlier update are not reported again, so add callbacks before the first update.
+    pub fn on_connect(&mut self, callback: impl FnMut(GamepadId, &Gamepad) + 'static) {
+        self.connect_callbacks.push(Box::new(callback));
+    }
+
+    /// Calls `callback` with every gamepad that disconnects, during [`Self::update`].
+    pub fn on_disconnect(&mut self, callback: impl FnMut(GamepadId) + 'static) {
+        self.disconnect_callbacks.push(Box::new(callback));
+    }
+
+    fn run_hotplug_callbacks(&mut self) {
+        let gamepads = &self.gamepads;
+        let disconnected: Vec<_> = self
+            .connected
+            .iter()
+            .copied()
+            .filter(|id| !gamepads.contains_key(id))
+            .collect();
+        for id in disconnected {
+            self.connected.remove(&id);
+            for callback in &mut self.disconnect_callbacks {
+                callback(id);
+            }
+        }
+
+        let mut connected: Vec<_> = gamepads
+            .keys()
+            .copied()
+            .filter(|id| !self.connected.contains(id))
+            .collect();
+        connected.sort();
+        for id in connected {
+            self.connected.insert(id);
+            for callback in &mut self.connect_callbacks {
+                callback(id, &gamepads[&id]);
+            }
+        }
+    }
+
+    /// Calls `callback` whenever a button of any gamepad is pressed or released, during
+    /// [`Self::update`], [`Self::sample`] or [`Self::end_frame`], such as for achievements or
+    /// analytics.
+    ///
+    /// Calls follow the presses and releases that [`Self::poll_event`] reports, in the same
+    /// order, whether or not the events are taken. A tap within a single update is a press
+    /// followed by a release.
+    pub fn observe(
+        &mut self,
+        button: Button,
+        callback: impl FnMut(GamepadId, ButtonEdge) + 'static,
+    ) {
+        self.observers.push((button, Box::new(callback)));
+    }
+
+    fn run_observers(&mut self) {
+        if self.observers.is_empty() {
+            return;
+        }
+
+        for event in self.events.queued() {
+            let (id, button, edge) = match *event {
+                GamepadEvent::ButtonPressed { id, button, .. } => (id, button, ButtonEdge::Pressed),
+                GamepadEvent::ButtonReleased { id, button, .. } => {
+                    (id, button, ButtonEdge::Released)
+                }
+                _ => continue,
+            };
+            for (observed, callback) in &mut self.observers {
+                if *observed == button {
+                    callback(id, edge);
+                }
+            }
+        }
+    }
+
+    /// Decouples the end of a frame from [`Self::update`], for engines that poll input more often
+    /// than their game logic runs.
+    ///
+    /// While enabled, just activated and just deactivated inputs accumulate over any number of
+    /// updates, until [`Self::end_frame`] is called. Events and ticks also advance per frame.
+    pub fn set_manual_frames(&mut self, enabled: bool) {
+        self.manual_frames = enabled;
+    }
+
+    /// Checks if frames are ended manually.
+    pub fn manual_frames(&self) -> bool {
+        self.manual_frames
+    }
+
+    /// Latches the buttons of all gamepads, so that a press is seen by at least one frame
+    /// even if the button was released before the frame ended.
+    ///
+    /// Useful with fixed timesteps, where fast taps could otherwise fall between two ticks.
+    /// See [`DigitalInput::set_latching`].
+    pub fn set_latching(&mut self, enabled: bool) {
+        self.latching = enabled;
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.digital_inputs.set_latching(enabled);
+        }
+    }
+
+    /// Rounds the axes of all gamepads to a fixed grid, so that every platform and backend
+    /// reads bit-identical values, such as for lockstep or rollback netcode.
+    ///
+    /// See [`AnalogInput::set_quantization`].
+    pub fn set_quantization(&mut self, quantization: Option<Quantization>) {
+        self.quantization = quantization;
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.analog_inputs.set_quantization(quantization);
+        }
+    }
+
+    /// Watches the sticks of all gamepads for axes that rest off-center, including gamepads
+    /// that connect later. `None` stops watching.
+    ///
+    /// See [`Gamepad::set_drift_detection`].
+    pub fn set_drift_detection(&mut self, detector: Option<DriftDetector>) {
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.drift.clone_from(&detector);
+        }
+        self.drift = detector;
+    }
+
+    /// Gets the grid that the axes of all gamepads are rounded to.
+    pub fn quantization(&self) -> Option<Quantization> {
+        self.quantization
+    }
+
+    /// Limits how many gamepads the context reports, such as to the number of players of the
+    /// game. Unlimited by default.
+    ///
+    /// Gamepads that connect past the maximum are handled as set with
+    /// [`Self::set_join_policy`]. Lowering the maximum does not remove gamepads that already
+    /// joined.
+    pub fn set_max_gamepads(&mut self, max: Option<usize>) {
+        self.admission.set_max(max);
+    }
+
+    /// Gets the maximum number of gamepads the context reports.
+    pub fn max_gamepads(&self) -> Option<usize> {
+        self.admission.max()
+    }
+
+    /// Sets what happens to gamepads that connect past the maximum number of gamepads.
+    /// Defaults to [`JoinPolicy::Queue`].
+    ///
+    /// The game is informed with a [`GamepadEvent::Ignored`], [`GamepadEvent::Queued`] or
+    /// [`GamepadEvent::Replaced`]. Takes effect for gamepads that connect afterwards.
+    pub fn set_join_policy(&mut self, policy: JoinPolicy) {
+        self.admission.set_policy(policy);
+    }
+
+    /// Gets what happens to gamepads that connect past the maximum number of gamepads.
+    pub fn join_policy(&self) -> JoinPolicy {
+        self.admission.policy()
+    }
+
+    /// Sets which devices the context reports, such as to ignore HID devices that register as
+    /// gamepads but are not meant to be played with. Reports every device by default.
+    ///
+    /// Denied gamepads are still read, but are not reported and do not count towards the maximum
+    /// number of gamepads. Gamepads that become denied are reported as disconnected, and gamepads
+    /// that become allowed as connected.
+    pub fn set_device_filter(&mut self, filter: DeviceFilter) {
+        self.admission.set_filter(filter);
+    }
+
+    /// Gets the filter of the devices the context reports.
+    pub fn device_filter(&self) -> &DeviceFilter {
+        self.admission.filter()
+    }
+
+    /// Iterates over the gamepads that wait to join, in the order they will join in.
+    pub fn waiting_gamepads(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.admission.waiting()
+    }
+
+    /// Releases every button and centers every axis of every gamepad, such as when the window
+    /// loses focus and the backend stops reporting releases.
+    ///
+    /// See [`Gamepad::release_all`]. Disconnected gamepads are released automatically: events
+    /// report their held buttons as released before the disconnection.
+    pub fn release_all(&mut self) {
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.release_all();
+        }
+    }
+
+    /// Enables or disables the input of every gamepad, such as while a text field or an overlay
+    /// has focus. Enabled by default.
+    ///
+    /// Disabling the context suppresses every gamepad, including ones that connect afterwards,
+    /// as described in [`Gamepad::set_suppressed`]. Gamepads are still updated, and device
+    /// features such as rumble keep working.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.set_context_disabled(!enabled);
+        }
+    }
+
+    /// Checks if the input of the gamepads is enabled.
+    ///
+    /// See [`Self::set_enabled`].
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Ends a frame when frames are ended manually, queuing the events of the frame and
+    /// resetting just activated and just deactivated inputs.
+    ///
+    /// Call this once the game logic has consumed the input of the frame.
+    /// Does nothing unless enabled with [`Self::set_manual_frames`].
+    pub fn end_frame(&mut self) {
+        if self.manual_frames {
+            self.queue_events();
+            for gamepad in self.gamepads.values_mut() {
+                gamepad.update_inputs();
+            }
+        }
+    }
+
+    /// Takes the next event of the last [`Self::update`] or [`Self::sample`], or of the last
+    /// frame when frames are ended manually.
+    ///
+    /// Events that are not taken before the next ones are queued are discarded.
+    pub fn poll_event(&mut self) -> Option<GamepadEvent> {
+        self.note_query();
+        self.events.pop()
+    }
+
+    /// Takes all events of the last [`Self::update`], like calling [`Self::poll_event`] until it
+    /// returns `None`.
+    ///
+    /// The queue keeps its allocation, so draining it every frame does not allocate.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GamepadEvent> + '_ {
+        self.note_query();
+        self.events.drain()
+    }
+
+    /// Gets a thread-safe view of the state of all gamepads, refreshed on every [`Self::update`].
+    ///
+    /// The context itself is not `Send` with every backend. Keep it on a dedicated input thread,
+    /// and send clones of the view to the threads that read gamepad state.
+    pub fn shared_state(&mut self) -> SharedState {
+        self.shared_state
+            .get_or_insert_with(|| {
+                let shared_state = SharedState::default();
+                shared_state.publish(&self.gamepads);
+                shared_state
+            })
+            .clone()
+    }
+
+    /// Sets the analog deadzone for all analog inputs, of every gamepad, including ones that
+    /// connect later.
+    ///
+    /// Positive values outside the deadzone get remapped to the range `[0.0, ANALOG_MAX]`.\
+    /// Negative values get remapped to `[ANALOG_MIN, 0.0]`.
+    ///
+    /// The deadzone is stored in the defaults of the config, so [`Self::set_config`] replaces
+    /// it.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.config.defaults.deadzone = Some(deadzone);
+        for (_, gamepad) in self.gamepads.iter_mut() {
+            gamepad.set_deadzone(deadzone);
+        }
+    }
+
+    /// Sets the analog deadzone of a single axis on a specific gamepad.
+    ///
+    /// See [`AnalogInput::set_deadzone_for`].
+    pub fn set_axis_deadzone(&mut self, id: GamepadId, axis: Axis, deadzone: f32) {
+        if let Some(gamepad) = self.gamepads.get_mut(&id) {
+            gamepad.set_axis_deadzone(axis, deadzone);
+        }
+    }
+
+    /// Gets mutable access to the configuration of an axis on a specific gamepad.
+    ///
+    /// Returns `None` if the gamepad is not found.
+    pub fn axis_config_mut(&mut self, id: GamepadId, axis: Axis) -> Option<&mut AxisConfig> {
+        self.gamepads
+            .get_mut(&id)
+            .map(|gamepad| gamepad.axis_config_mut(axis))
+    }
+
+    /// Applies the deadzones suggested by a calibration to a specific gamepad.
+    pub fn apply_calibration(&mut self, id: GamepadId, calibration: &Calibration<Axis>) {
+        if let Some(gamepad) = self.gamepads.get_mut(&id) {
+            gamepad.apply_calibration(calibration);
+        }
+    }
+
+    /// Sets the digital deadzone for all analog inputs, of every gamepad, including ones that
+    /// connect later.
+    ///
+    /// Stored in the config like [`Self::set_deadzone`].
+    pub fn set_digital_deadzone(&mut self, deadzone: f32) {
+        self.config.defaults.digital_deadzone = Some(deadzone);
+        for (_, gamepad) in self.gamepads.iter_mut() {
+            gamepad.set_digital_deadzone(deadzone);
+        }
+    }
+
+    /// Limits how often a button can be activated on all gamepads, including ones that connect
+    /// later.
+    ///
+    /// Stored in the config like [`Self::set_deadzone`]. See [`DigitalInput::set_rate_limit`].
+    pub fn set_rate_limit(&mut self, button: Button, max_per_second: f32) {
+        let settings = self.config.defaults.buttons.entry(button).or_default();
+        settings.rate_limit = Some(max_per_second);
+        for (_, gamepad) in self.gamepads.iter_mut() {
+            gamepad.set_rate_limit(button, max_per_second);
+        }
+    }
+}
+
+/// Applies the configuration to a gamepad, with the settings of its profile on top.
+fn configure(config: &Config, profile: Option<&Profile>, gamepad: &mut Gamepad) {
+    let mut settings = config.settings(gamepad.guid());
+    if let Some(profile) = profile {
+        settings.layer(&profile.settings);
+    }
+    settings.apply(gamepad);
+}
+
+/// Checks if any input of a gamepad has just been activated.
+fn has_new_input(gamepad: &Gamepad) -> bool {
+    BUTTONS
+        .iter()
+        .copied()
+        .chain(gamepad.virtual_buttons().map(Button::Virtual))
+        .any(|button| gamepad.digital_inputs.just_activated(button))
+        || AXES
+            .iter()
+            .any(|&axis| gamepad.analog_inputs.just_activated(axis).is_some())
+}
+
+/// A cloneable, shared handle to a [`GamepadContext`].
+///
+/// Useful when several parts of an application, such as an editor and a game, need gamepad input.
+/// Only one of them should call [`GamepadContext::update`] each frame,
+/// otherwise inputs that have just been activated are missed by the others.
+///
+/// The handle is not `Send`, since backends such as SDL2 have to stay on the thread that created
+/// them. Other threads read gamepads through [`GamepadContext::shared_state`] instead.
+#[derive(Clone)]
+pub struct GamepadContextHandle(Rc<RefCell<GamepadContext>>);
+
+impl GamepadContextHandle {
+    /// Borrows the context.
+    ///
+    /// Panics if the context is currently borrowed mutably.
+    pub fn get(&self) -> Ref<'_, GamepadContext> {
+        self.0.borrow()
+    }
+
+    /// Borrows the context mutably.
+    ///
+    /// Panics if the context is currently borrowed.
+    pub fn get_mut(&self) -> RefMut<'_, GamepadContext> {
+        self.0.borrow_mut()
+    }
+}
+
+impl From<GamepadContext> for GamepadContextHandle {
+    fn from(context: GamepadContext) -> Self {
+        context.into_handle()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    #[cfg(feature = "gilrs")]
+    fn several_contexts_are_alive_at_once() {
+        let gilrs = || {
+            GamepadContext::builder()
+                .backends(&[BackendKind::Gilrs])
+                .build()
+        };
+        let first = gilrs().unwrap();
+        let second = gilrs().unwrap();
+        assert_eq!(first.backends(), second.backends());
+    }
+
+    #[test]
+    fn observers_see_taps_within_an_update() {
+        let mut context = GamepadContext::builder()
+            .lazy(Duration::from_secs(100))
+            .build()
+            .unwrap();
+        let edges = Rc::new(RefCell::new(Vec::new()));
+        let observed = Rc::clone(&edges);
+        context.observe(Button::A, move |_, edge| observed.borrow_mut().push(edge));
+        context
+            .gamepads
+            .insert(GamepadId(0), Gamepad::new(None, Guid::default()));
+        context.sample();
+
+        let gamepad = context.gamepads.get_mut(&GamepadId(0)).unwrap();
+        gamepad.digital_inputs.activate(Button::A);
+        gamepad.digital_inputs.deactivate(Button::A);
+        context.sample();
+        assert_eq!(*edges.borrow(), [ButtonEdge::Pressed, ButtonEdge::Released]);
+    }
+
+    #[test]
+    fn context_settings_apply_to_new_gamepads() {
+        let mut context = GamepadContext::builder()
+            .lazy(Duration::from_secs(100))
+            .build()
+            .unwrap();
+        context.set_deadzone(0.3);
+        context.set_digital_deadzone(0.6);
+        context.set_rate_limit(Button::A, 5.0);
+        context
+            .gamepads
+            .insert(GamepadId(0), Gamepad::new(None, Guid::default()));
+        context.apply_config();
+
+        let gamepad = &context.gamepads[&GamepadId(0)];
+        assert_eq!(gamepad.analog_inputs.deadzone(), 0.3);
+        assert_eq!(gamepad.analog_inputs.digital_deadzone(), 0.6);
+        assert_eq!(gamepad.digital_inputs.rate_limit(Button::A), Some(5.0));
+    }
+
+    #[test]
+    fn profiles_layer_over_the_config() {
+        let mut context = GamepadContext::builder()
+            .lazy(Duration::from_secs(100))
+            .build()
+            .unwrap();
+        let mut config = Config::default();
+        config.defaults.deadzone = Some(0.3);
+        config.defaults.stick_to_dpad = Some(true);
+        config
+            .bindings
+            .insert("jump".into(), vec![Button::A.into()]);
+        config
+            .bindings
+            .insert("duck".into(), vec![Button::B.into()]);
+        context.set_config(config);
+        context
+            .gamepads
+            .insert(GamepadId(0), Gamepad::new(None, Guid::default()));
+        context.apply_config();
+
+        let mut profile = Profile::new("left-handed");
+        profile.settings.deadzone = Some(0.2);
+        profile.bind("jump", [Button::X]);
+        context.assign_profile(GamepadId(0), profile);
+
+        let gamepad = &context.gamepads[&GamepadId(0)];
+        assert_eq!(gamepad.analog_inputs.deadzone(), 0.2);
+        assert!(gamepad.stick_to_dpad());
+        assert_eq!(context.binding(GamepadId(0), "jump"), [Button::X.into()]);
+        assert_eq!(context.binding(GamepadId(0), "duck"), [Button::B.into()]);
+
+        context.set_config(Config::default());
+        context.apply_config();
+        let gamepad = &context.gamepads[&GamepadId(0)];
+        assert_eq!(gamepad.analog_inputs.deadzone(), 0.2);
+        assert!(!gamepad.stick_to_dpad());
+        assert!(context.binding(GamepadId(0), "duck").is_empty());
+        assert!(context.play_cue(GamepadId(0), "hit").is_err());
+    }
+
+    #[test]
+    fn fingerprints_round_trip() {
+        let guid: Guid = "030000005e0400008e02000010010000".parse().unwrap();
+        for serial in [None, Some("3032363030".to_owned())] {
+            let fingerprint = DeviceFingerprint { guid, serial };
+            let parsed: DeviceFingerprint = fingerprint.to_string().parse().unwrap();
+            assert_eq!(parsed, fingerprint);
+        }
+
+        let parsed: DeviceFingerprint = format!("{}:", guid).parse().unwrap();
+        assert_eq!(parsed.serial, None);
+    }
 }
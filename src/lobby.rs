@@ -0,0 +1,227 @@
+//! Player lobbies for local multiplayer, where players join with their gamepads.
+//!
+//! In a [`Lobby`], pressing [`Button::A`] joins a free slot and then marks the player as ready.
+//! [`Button::B`] unmarks a ready player, or leaves the lobby. Once every player is ready, any
+//! of them can press [`Button::Start`] to start the game.
+
+use crate::{Button, GamepadContext, GamepadId};
+
+/// A change to a lobby, returned by [`Lobby::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LobbyEvent {
+    /// A gamepad joined the lobby and took a slot.
+    Joined {
+        /// The index of the slot.
+        slot: usize,
+        /// The gamepad of the player.
+        gamepad: GamepadId,
+    },
+    /// A gamepad left the lobby, or disconnected, freeing its slot.
+    Left {
+        /// The index of the slot.
+        slot: usize,
+        /// The gamepad of the player.
+        gamepad: GamepadId,
+    },
+    /// The player of a slot is ready.
+    Ready {
+        /// The index of the slot.
+        slot: usize,
+    },
+    /// The player of a slot is no longer ready.
+    Unready {
+        /// The index of the slot.
+        slot: usize,
+    },
+    /// Every player is ready and one of them pressed start.
+    Started,
+}
+
+/// A player in a lobby slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LobbySlot {
+    /// The gamepad of the player.
+    pub gamepad: GamepadId,
+    /// Whether the player is ready.
+    pub ready: bool,
+}
+
+/// Assigns gamepads to player slots. Update it once per update, after the context.
+///
+/// Admin gamepads, as set with [`GamepadContext::set_admin`], cannot join. Once the lobby has
+/// started, it stops reading input and keeps its slots, until it is [reset](Self::reset).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lobby {
+    slots: Vec<Option<LobbySlot>>,
+    min_players: usize,
+    started: bool,
+    // Reused between updates to avoid allocating.
+    gamepads: Vec<GamepadId>,
+    events: Vec<LobbyEvent>,
+}
+
+impl Lobby {
+    /// Creates an empty lobby with `max_players` slots, which can start with a single player.
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            slots: vec![None; max_players],
+            min_players: 1,
+            started: false,
+            gamepads: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Sets how many players have to join before the lobby can start.
+    pub fn with_min_players(mut self, min_players: usize) -> Self {
+        self.min_players = min_players.max(1);
+        self
+    }
+
+    /// Gets the number of slots.
+    pub fn max_players(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Gets how many players have to join before the lobby can start.
+    pub fn min_players(&self) -> usize {
+        self.min_players
+    }
+
+    /// Gets the player in a slot.
+    pub fn slot(&self, slot: usize) -> Option<LobbySlot> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Gets the slot of a gamepad.
+    pub fn slot_of(&self, gamepad: GamepadId) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_some_and(|slot| slot.gamepad == gamepad))
+    }
+
+    /// Iterates over the taken slots and their players.
+    pub fn players(&self) -> impl Iterator<Item = (usize, LobbySlot)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| Some((index, (*slot)?)))
+    }
+
+    /// Gets the number of players in the lobby.
+    pub fn len(&self) -> usize {
+        self.players().count()
+    }
+
+    /// Checks if no player has joined.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if enough players have joined and all of them are ready.
+    pub fn all_ready(&self) -> bool {
+        self.len() >= self.min_players && self.players().all(|(_, slot)| slot.ready)
+    }
+
+    /// Checks if the lobby has started.
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Empties the lobby, so that players can join again.
+    pub fn reset(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.started = false;
+    }
+
+    /// Reads the input of the context's player gamepads and returns the changes to the lobby.
+    ///
+    /// Gamepads are handled in order of Id, so when two press A at once, the lowest Id joins
+    /// first.
+    pub fn update(&mut self, context: &GamepadContext) -> impl Iterator<Item = LobbyEvent> + '_ {
+        if !self.started {
+            self.read(context);
+        }
+        self.events.drain(..)
+    }
+
+    fn read(&mut self, context: &GamepadContext) {
+        for index in 0..self.slots.len() {
+            if let Some(slot) = self.slots[index] {
+                if context.gamepad(slot.gamepad).is_none() {
+                    self.leave(index);
+                }
+            }
+        }
+
+        let mut gamepads = std::mem::take(&mut self.gamepads);
+        gamepads.clear();
+        gamepads.extend(context.player_gamepads().map(|(id, _)| id));
+        gamepads.sort();
+
+        for &id in &gamepads {
+            let Some(buttons) = context.gamepad(id).map(|gamepad| &gamepad.digital_inputs) else {
+                continue;
+            };
+
+            match self.slot_of(id) {
+                None => {
+                    if buttons.just_activated(Button::A) {
+                        self.join(id);
+                    }
+                }
+                Some(index) => {
+                    let ready = self.slots[index].is_some_and(|slot| slot.ready);
+                    if buttons.just_activated(Button::A) && !ready {
+                        self.set_ready(index, true);
+                    } else if buttons.just_activated(Button::B) {
+                        if ready {
+                            self.set_ready(index, false);
+                        } else {
+                            self.leave(index);
+                        }
+                    } else if buttons.just_activated(Button::Start) && self.all_ready() {
+                        self.started = true;
+                        self.events.push(LobbyEvent::Started);
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.gamepads = gamepads;
+    }
+
+    fn join(&mut self, gamepad: GamepadId) {
+        if let Some(index) = self.slots.iter().position(Option::is_none) {
+            self.slots[index] = Some(LobbySlot {
+                gamepad,
+                ready: false,
+            });
+            self.events.push(LobbyEvent::Joined {
+                slot: index,
+                gamepad,
+            });
+        }
+    }
+
+    fn leave(&mut self, index: usize) {
+        if let Some(slot) = self.slots[index].take() {
+            self.events.push(LobbyEvent::Left {
+                slot: index,
+                gamepad: slot.gamepad,
+            });
+        }
+    }
+
+    fn set_ready(&mut self, index: usize, ready: bool) {
+        if let Some(slot) = &mut self.slots[index] {
+            slot.ready = ready;
+            self.events.push(if ready {
+                LobbyEvent::Ready { slot: index }
+            } else {
+                LobbyEvent::Unready { slot: index }
+            });
+        }
+    }
+}
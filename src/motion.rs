@@ -0,0 +1,283 @@
+//! Motion inputs, such as the quarter-circles and charge moves of fighting games.
+//!
+//! Enable the history of a gamepad with
+//! [`Gamepad::set_input_history`](crate::Gamepad::set_input_history), and check it against a
+//! [`Motion`] when the button of a special move is just activated.
+
+use crate::direction::Direction8;
+use std::collections::VecDeque;
+
+/// The number of updates a motion has to be entered within by default.
+const DEFAULT_WINDOW: u64 = 15;
+
+/// The number of updates a direction has to be held for a charge by default.
+const DEFAULT_CHARGE: u64 = 40;
+
+/// A direction entered at some update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    direction: Option<Direction8>,
+    tick: u64,
+}
+
+/// Remembers the last directions entered on a gamepad, in the order they were entered.
+///
+/// Only changes of direction are kept, so the history covers as many updates as it takes to
+/// enter `capacity` directions.
+#[derive(Debug, Clone)]
+pub struct InputHistory {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl InputHistory {
+    /// Creates an empty history that keeps the last `capacity` directions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            tick: 0,
+        }
+    }
+
+    /// Records the direction held at an update. `tick` counts the updates, such as
+    /// [`DigitalInput::tick`](crate::digital::DigitalInput::tick).
+    pub fn update(&mut self, direction: Option<Direction8>, tick: u64) {
+        self.tick = tick;
+        if self.direction() == direction && !self.entries.is_empty() {
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry { direction, tick });
+    }
+
+    /// Gets the direction that is held, as of the last update.
+    pub fn direction(&self) -> Option<Direction8> {
+        self.entries.back().and_then(|entry| entry.direction)
+    }
+
+    /// Iterates over the entered directions with the update they were entered at, from the
+    /// oldest to the newest. `None` is the neutral position.
+    pub fn iter(&self) -> impl Iterator<Item = (Option<Direction8>, u64)> + '_ {
+        self.entries
+            .iter()
+            .map(|entry| (entry.direction, entry.tick))
+    }
+
+    /// Checks if a motion was entered and has not been cleared since.
+    pub fn matches(&self, motion: &Motion) -> bool {
+        // Steps are matched from the last one backwards, each against the newest entry before
+        // the entry of the step after it, so other directions in between are allowed.
+        let mut end = self.entries.len();
+        let mut start_tick = self.tick;
+        for step in motion.steps.iter().rev() {
+            let Some(index) = self
+                .entries
+                .range(..end)
+                .rposition(|entry| step.matches(entry))
+            else {
+                return false;
+            };
+
+            // The step ends where the next direction was entered.
+            let step_end = self
+                .entries
+                .get(index + 1)
+                .map_or(self.tick, |entry| entry.tick);
+            if step.hold > 0 {
+                // Charges may move between the directions of the step, such as down-back and
+                // back, without starting over.
+                let first = self
+                    .entries
+                    .range(..index)
+                    .rposition(|entry| !step.matches(entry))
+                    .map_or(0, |other| other + 1);
+                if step_end - self.entries[first].tick < step.hold {
+                    return false;
+                }
+                // Charging may start long before the rest of the motion.
+                start_tick = step_end;
+                end = first;
+            } else {
+                start_tick = self.entries[index].tick;
+                end = index;
+            }
+        }
+
+        self.tick - start_tick <= motion.window
+    }
+
+    /// Forgets all directions but the one that is held, such as after a motion triggered a move
+    /// so that it does not trigger it again.
+    pub fn clear(&mut self) {
+        let direction = self.direction();
+        self.entries.clear();
+        self.entries.push_back(Entry {
+            direction,
+            tick: self.tick,
+        });
+    }
+}
+
+/// A set of directions that a step of a motion accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    // One bit per `Direction8`.
+    directions: u8,
+    hold: u64,
+}
+
+impl Step {
+    fn new(directions: impl IntoIterator<Item = Direction8>, hold: u64) -> Self {
+        let directions = directions
+            .into_iter()
+            .fold(0, |directions, direction| directions | bit(direction));
+        Self { directions, hold }
+    }
+
+    fn matches(&self, entry: &Entry) -> bool {
+        entry
+            .direction
+            .is_some_and(|direction| self.directions & bit(direction) != 0)
+    }
+}
+
+fn bit(direction: Direction8) -> u8 {
+    1 << direction as u8
+}
+
+fn mirror(direction: Direction8) -> Direction8 {
+    let (x, y) = direction.to_vector();
+    Direction8::from_vector(-x, y).unwrap_or(direction)
+}
+
+/// A sequence of directions that has to be entered in order, such as a quarter-circle.
+///
+/// Presets face right. Use [`Self::mirrored`] for characters that face left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Motion {
+    steps: Vec<Step>,
+    window: u64,
+}
+
+impl Motion {
+    /// Creates an empty motion, which has to be entered within 15 updates.
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Sets how many updates the motion has to be entered within, up to the last update.
+    /// Charges do not count towards it.
+    pub fn with_window(mut self, window: u64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Adds a direction to enter.
+    pub fn then(self, direction: Direction8) -> Self {
+        self.then_any([direction])
+    }
+
+    /// Adds a step that accepts any of several directions, such as to be lenient with diagonals.
+    pub fn then_any(mut self, directions: impl IntoIterator<Item = Direction8>) -> Self {
+        self.steps.push(Step::new(directions, 0));
+        self
+    }
+
+    /// Adds a step where any of several directions has to be held for `updates` updates, such as
+    /// the charge of a charge move.
+    pub fn hold(mut self, directions: impl IntoIterator<Item = Direction8>, updates: u64) -> Self {
+        self.steps.push(Step::new(directions, updates.max(1)));
+        self
+    }
+
+    /// Down, down-forward, forward.
+    pub fn quarter_circle_forward() -> Self {
+        Self::new()
+            .then(Direction8::Down)
+            .then(Direction8::DownRight)
+            .then(Direction8::Right)
+    }
+
+    /// Down, down-back, back.
+    pub fn quarter_circle_back() -> Self {
+        Self::quarter_circle_forward().mirrored()
+    }
+
+    /// Forward, down, down-forward, also known as a dragon punch.
+    pub fn dragon_punch() -> Self {
+        Self::new()
+            .then(Direction8::Right)
+            .then(Direction8::Down)
+            .then(Direction8::DownRight)
+    }
+
+    /// Back held for 40 updates, then forward.
+    pub fn charge_back_forward() -> Self {
+        Self::new()
+            .hold(
+                [Direction8::Left, Direction8::UpLeft, Direction8::DownLeft],
+                DEFAULT_CHARGE,
+            )
+            .then(Direction8::Right)
+    }
+
+    /// Down held for 40 updates, then up.
+    pub fn charge_down_up() -> Self {
+        Self::new()
+            .hold(
+                [
+                    Direction8::Down,
+                    Direction8::DownLeft,
+                    Direction8::DownRight,
+                ],
+                DEFAULT_CHARGE,
+            )
+            .then_any([Direction8::Up, Direction8::UpLeft, Direction8::UpRight])
+    }
+
+    /// Swaps left and right, for characters that face left.
+    pub fn mirrored(&self) -> Self {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| Step {
+                directions: DIRECTIONS
+                    .into_iter()
+                    .filter(|&direction| step.directions & bit(direction) != 0)
+                    .fold(0, |directions, direction| {
+                        directions | bit(mirror(direction))
+                    }),
+                hold: step.hold,
+            })
+            .collect();
+        Self {
+            steps,
+            window: self.window,
+        }
+    }
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const DIRECTIONS: [Direction8; 8] = [
+    Direction8::Up,
+    Direction8::UpRight,
+    Direction8::Right,
+    Direction8::DownRight,
+    Direction8::Down,
+    Direction8::DownLeft,
+    Direction8::Left,
+    Direction8::UpLeft,
+];
@@ -0,0 +1,298 @@
+//! Players that are decoupled from the devices they play with.
+//!
+//! A [`Players`] registry maps the game's own player handles to input sources: gamepads of the
+//! context, or virtual pads driven by code, such as from the keyboard or a replay. Game code
+//! reads the input of a player without caring where it comes from, and gamepads that reconnect
+//! are given back to their players.
+
+use crate::analog::AnalogInputValue;
+use crate::state::GamepadSnapshot;
+use crate::{Axis, Button, Gamepad, GamepadContext, GamepadId, Guid, AXES, BUTTONS};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Where the input of a player comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    /// A gamepad of the context.
+    Gamepad(GamepadId),
+    /// A virtual pad, driven with [`Players::virtual_pad_mut`].
+    Virtual,
+}
+
+/// A change to the players, returned by [`Players::update`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayerEvent<P> {
+    /// The gamepad of a player disconnected. The player keeps waiting for it.
+    Disconnected(P),
+    /// A gamepad of the same GUID as the one that disconnected was given to the player.
+    Reconnected {
+        /// The player.
+        player: P,
+        /// The gamepad that was given to the player.
+        gamepad: GamepadId,
+    },
+}
+
+/// A gamepad driven by code, such as from the keyboard, a bot or a replay.
+///
+/// Changes are seen as just activated or just deactivated until the next [`Players::update`].
+pub struct VirtualPad {
+    gamepad: Gamepad,
+}
+
+impl VirtualPad {
+    fn new() -> Self {
+        Self {
+            gamepad: Gamepad::new(None, Guid::default()),
+        }
+    }
+
+    /// Presses or releases a button.
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.gamepad.digital_inputs.activate(button);
+        } else {
+            self.gamepad.digital_inputs.deactivate(button);
+        }
+    }
+
+    /// Moves an axis, from `-1.0` to `1.0` for sticks, or `0.0` to `1.0` for triggers.
+    pub fn set_axis(&mut self, axis: Axis, value: f32) {
+        self.gamepad
+            .analog_inputs
+            .set(axis, AnalogInputValue::from(value));
+    }
+
+    /// Sets every button and axis from a snapshot, such as one frame of a replay.
+    pub fn apply_snapshot(&mut self, snapshot: &GamepadSnapshot) {
+        for button in BUTTONS {
+            self.set_button(button, snapshot.activated(button));
+        }
+        for axis in AXES {
+            self.set_axis(axis, snapshot.value(axis));
+        }
+    }
+
+    /// Gets the gamepad state of the pad.
+    pub fn gamepad(&self) -> &Gamepad {
+        &self.gamepad
+    }
+}
+
+struct Player {
+    source: InputSource,
+    // Whether the gamepad of the player is connected.
+    connected: bool,
+    // The GUID of the gamepad of the player, to give it back when it reconnects.
+    guid: Option<Guid>,
+    pad: Option<VirtualPad>,
+}
+
+/// The input of a player, as returned by [`Players::input`].
+///
+/// Players without a connected source read as if nothing was held.
+#[derive(Clone, Copy)]
+pub struct PlayerInput<'a> {
+    gamepad: Option<&'a Gamepad>,
+}
+
+impl<'a> PlayerInput<'a> {
+    /// Gets the gamepad state of the player, if its source is connected.
+    pub fn gamepad(&self) -> Option<&'a Gamepad> {
+        self.gamepad
+    }
+
+    /// Checks if a button is activated.
+    pub fn activated(&self, button: Button) -> bool {
+        self.gamepad
+            .is_some_and(|gamepad| gamepad.digital_inputs.activated(button))
+    }
+
+    /// Checks if a button was just activated.
+    pub fn just_activated(&self, button: Button) -> bool {
+        self.gamepad
+            .is_some_and(|gamepad| gamepad.digital_inputs.just_activated(button))
+    }
+
+    /// Checks if a button was just deactivated.
+    pub fn just_deactivated(&self, button: Button) -> bool {
+        self.gamepad
+            .is_some_and(|gamepad| gamepad.digital_inputs.just_deactivated(button))
+    }
+
+    /// Gets the value of an axis, after deadzones.
+    pub fn value(&self, axis: Axis) -> f32 {
+        self.gamepad
+            .map_or(0.0, |gamepad| gamepad.analog_inputs.value(axis))
+    }
+}
+
+/// Maps player handles, such as player numbers or entities, to where their input comes from.
+/// Update it once per update, after the context.
+pub struct Players<P> {
+    players: HashMap<P, Player>,
+    events: Vec<PlayerEvent<P>>,
+}
+
+impl<P> Players<P>
+where
+    P: Clone + Eq + Hash,
+{
+    /// Creates a registry without players.
+    pub fn new() -> Self {
+        Self {
+            players: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Gives a gamepad to a player, replacing its previous source.
+    ///
+    /// The gamepad is taken from any other player that had it, which is left without a
+    /// connected source.
+    pub fn assign_gamepad(&mut self, player: P, gamepad: GamepadId) {
+        for other in self.players.values_mut() {
+            if other.source == InputSource::Gamepad(gamepad) {
+                other.connected = false;
+                other.guid = None;
+            }
+        }
+        self.players.insert(
+            player,
+            Player {
+                source: InputSource::Gamepad(gamepad),
+                connected: true,
+                guid: None,
+                pad: None,
+            },
+        );
+    }
+
+    /// Gives a new virtual pad to a player, replacing its previous source.
+    pub fn assign_virtual(&mut self, player: P) -> &mut VirtualPad {
+        let new = Player {
+            source: InputSource::Virtual,
+            connected: true,
+            guid: None,
+            pad: None,
+        };
+        let player = match self.players.entry(player) {
+            Entry::Occupied(mut entry) => {
+                entry.insert(new);
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => entry.insert(new),
+        };
+        player.pad.insert(VirtualPad::new())
+    }
+
+    /// Removes a player. Returns `true` if the player was registered.
+    pub fn remove(&mut self, player: &P) -> bool {
+        self.players.remove(player).is_some()
+    }
+
+    /// Gets where the input of a player comes from, even if its gamepad is disconnected.
+    pub fn source(&self, player: &P) -> Option<InputSource> {
+        self.players.get(player).map(|player| player.source)
+    }
+
+    /// Checks if the source of a player is connected.
+    pub fn is_connected(&self, player: &P) -> bool {
+        self.players
+            .get(player)
+            .is_some_and(|player| player.connected)
+    }
+
+    /// Gets the player that a gamepad is assigned to.
+    pub fn player_of(&self, gamepad: GamepadId) -> Option<&P> {
+        self.players
+            .iter()
+            .find(|(_, player)| player.connected && player.source == InputSource::Gamepad(gamepad))
+            .map(|(handle, _)| handle)
+    }
+
+    /// Iterates over the players and their sources.
+    pub fn iter(&self) -> impl Iterator<Item = (&P, InputSource)> + '_ {
+        self.players
+            .iter()
+            .map(|(handle, player)| (handle, player.source))
+    }
+
+    /// Gets the virtual pad of a player, to drive it.
+    pub fn virtual_pad_mut(&mut self, player: &P) -> Option<&mut VirtualPad> {
+        self.players.get_mut(player)?.pad.as_mut()
+    }
+
+    /// Gets the input of a player.
+    pub fn input<'a>(&'a self, context: &'a GamepadContext, player: &P) -> PlayerInput<'a> {
+        let gamepad = self
+            .players
+            .get(player)
+            .filter(|player| player.connected)
+            .and_then(|player| match player.source {
+                InputSource::Gamepad(id) => context.gamepad(id),
+                InputSource::Virtual => player.pad.as_ref().map(VirtualPad::gamepad),
+            });
+        PlayerInput { gamepad }
+    }
+
+    /// Ends the frame of the virtual pads, and follows gamepads that disconnect and reconnect.
+    ///
+    /// A disconnected gamepad is given back to its player when a gamepad with the same GUID
+    /// connects that no other player has.
+    pub fn update(
+        &mut self,
+        context: &GamepadContext,
+    ) -> impl Iterator<Item = PlayerEvent<P>> + '_ {
+        for (handle, player) in &mut self.players {
+            match player.source {
+                InputSource::Virtual => {
+                    if let Some(pad) = &mut player.pad {
+                        pad.gamepad.update_inputs();
+                    }
+                }
+                InputSource::Gamepad(id) if player.connected => match context.gamepad(id) {
+                    Some(gamepad) => player.guid = Some(gamepad.guid()),
+                    None => {
+                        player.connected = false;
+                        self.events.push(PlayerEvent::Disconnected(handle.clone()));
+                    }
+                },
+                InputSource::Gamepad(_) => {}
+            }
+        }
+
+        let mut gamepads: Vec<_> = context.gamepads().map(|(id, _)| id).collect();
+        gamepads.sort();
+        for id in gamepads {
+            if self.player_of(id).is_some() {
+                continue;
+            }
+            let guid = context.gamepad(id).map(Gamepad::guid);
+            let waiting = self.players.iter_mut().find(|(_, player)| {
+                !player.connected && player.guid.is_some() && player.guid == guid
+            });
+            if let Some((handle, player)) = waiting {
+                player.source = InputSource::Gamepad(id);
+                player.connected = true;
+                self.events.push(PlayerEvent::Reconnected {
+                    player: handle.clone(),
+                    gamepad: id,
+                });
+            }
+        }
+
+        self.events.drain(..)
+    }
+}
+
+impl<P> Default for Players<P>
+where
+    P: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
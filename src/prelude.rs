@@ -0,0 +1,42 @@
+//! Commonly used types, for glob importing.
+//!
+//! ```no_run
+//! use fishsticks::prelude::*;
+//! ```
+
+pub use crate::admission::{DeviceFilter, DeviceMatch, JoinPolicy};
+pub use crate::analog::{
+    AnalogInput, Quantization, ANALOG_MAX, ANALOG_MIN, DEFAULT_ANALOG_DEADZONE,
+    DEFAULT_DIGITAL_DEADZONE,
+};
+pub use crate::buffer::InputBuffer;
+pub use crate::capabilities::Capabilities;
+pub use crate::digital::{Chord, DigitalInput, PressMode};
+pub use crate::direction::{Direction8, Socd};
+pub use crate::drift::DriftDetector;
+pub use crate::dump::{Diagnostics, GamepadDump, StateDump};
+pub use crate::event::{ButtonEdge, GamepadEvent};
+pub use crate::flick::{FlickStick, FlickStickTurn};
+pub use crate::glyph::ControllerType;
+pub use crate::gyro::GyroAim;
+pub use crate::haptic::{Envelope, HapticEffect, RumbleChannel};
+pub use crate::latency::{DroppedPress, Histogram, LatencyStats};
+pub use crate::lobby::{Lobby, LobbyEvent, LobbySlot};
+pub use crate::motion::{InputHistory, Motion};
+pub use crate::players::{InputSource, PlayerEvent, PlayerInput, Players, VirtualPad};
+pub use crate::profile::{Input, Profile};
+pub use crate::raw::{HatDirection, RawHat};
+pub use crate::sensor::Sensor;
+pub use crate::state::{GamepadSnapshot, GamepadState, SharedState};
+pub use crate::storage::InputKind;
+pub use crate::touchpad::{FingerId, Gesture, TouchGesture, TouchpadInput};
+pub use crate::trigger::TriggerEffect;
+#[cfg(feature = "ui")]
+pub use crate::ui::{UiAction, UiNavigator};
+pub use crate::virtual_button::VirtualButton;
+#[cfg(feature = "virtual-device")]
+pub use crate::virtual_device::VirtualGamepad;
+pub use crate::{
+    Axis, BackendKind, BackendState, Button, DeviceFingerprint, Gamepad, GamepadContext,
+    GamepadContextBuilder, GamepadContextHandle, GamepadId, Guid, Stick, UpdateReport,
+};
@@ -0,0 +1,133 @@
+//! Per-player input settings that travel with the player rather than the device.
+//!
+//! In local multiplayer, each player brings their own deadzones, response curves, inverted axes
+//! and bindings. Assign a player's [`Profile`] to whichever gamepad they pick up with
+//! [`GamepadContext::assign_profile`](crate::GamepadContext::assign_profile).
+//!
+//! Serializable with the `serde` feature.
+
+use crate::config::Settings;
+use crate::raw::RawHat;
+use crate::touchpad::TouchGesture;
+use crate::{Button, Gamepad};
+use std::collections::HashMap;
+
+/// An input that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Input {
+    /// A button of a gamepad with a controller mapping.
+    Button(Button),
+    /// A numbered button of a raw joystick.
+    RawButton(u8),
+    /// A hat switch direction of a raw joystick.
+    RawHat(RawHat),
+    /// A gesture on a touchpad.
+    Gesture(TouchGesture),
+}
+
+impl Input {
+    pub(crate) fn activated(self, gamepad: &Gamepad) -> bool {
+        match self {
+            Input::Button(button) => gamepad.digital_inputs.activated(button),
+            Input::RawButton(button) => gamepad.raw_inputs.buttons.activated(button),
+            Input::RawHat(hat) => gamepad.raw_inputs.hats.activated(hat),
+            Input::Gesture(gesture) => gamepad.touchpad_inputs.gestures().activated(gesture),
+        }
+    }
+
+    pub(crate) fn just_activated(self, gamepad: &Gamepad) -> bool {
+        match self {
+            Input::Button(button) => gamepad.digital_inputs.just_activated(button),
+            Input::RawButton(button) => gamepad.raw_inputs.buttons.just_activated(button),
+            Input::RawHat(hat) => gamepad.raw_inputs.hats.just_activated(hat),
+            Input::Gesture(gesture) => gamepad.touchpad_inputs.gestures().just_activated(gesture),
+        }
+    }
+
+    pub(crate) fn just_deactivated(self, gamepad: &Gamepad) -> bool {
+        match self {
+            Input::Button(button) => gamepad.digital_inputs.just_deactivated(button),
+            Input::RawButton(button) => gamepad.raw_inputs.buttons.just_deactivated(button),
+            Input::RawHat(hat) => gamepad.raw_inputs.hats.just_deactivated(hat),
+            Input::Gesture(gesture) => gamepad.touchpad_inputs.gestures().just_deactivated(gesture),
+        }
+    }
+}
+
+impl From<Button> for Input {
+    fn from(button: Button) -> Self {
+        Input::Button(button)
+    }
+}
+
+impl From<RawHat> for Input {
+    fn from(hat: RawHat) -> Self {
+        Input::RawHat(hat)
+    }
+}
+
+impl From<TouchGesture> for Input {
+    fn from(gesture: TouchGesture) -> Self {
+        Input::Gesture(gesture)
+    }
+}
+
+/// The input settings of a single player.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Profile {
+    /// The name of the player or profile.
+    pub name: String,
+    /// Settings applied on top of the configuration to the gamepad the profile is assigned to.
+    pub settings: Settings,
+    /// Maps the game's actions to the inputs that trigger them.
+    pub bindings: HashMap<String, Vec<Input>>,
+}
+
+impl Profile {
+    /// Creates an empty profile.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Binds an action to a set of inputs, replacing its previous binding.
+    pub fn bind<I: Into<Input>>(
+        &mut self,
+        action: impl Into<String>,
+        inputs: impl IntoIterator<Item = I>,
+    ) {
+        let inputs = inputs.into_iter().map(Into::into).collect();
+        self.bindings.insert(action.into(), inputs);
+    }
+
+    /// Gets the inputs bound to an action.
+    pub fn binding(&self, action: &str) -> &[Input] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks if any input bound to an action is activated on a gamepad.
+    pub fn action_activated(&self, action: &str, gamepad: &Gamepad) -> bool {
+        self.binding(action)
+            .iter()
+            .any(|input| input.activated(gamepad))
+    }
+
+    /// Checks if any input bound to an action has just been activated on a gamepad.
+    pub fn action_just_activated(&self, action: &str, gamepad: &Gamepad) -> bool {
+        self.binding(action)
+            .iter()
+            .any(|input| input.just_activated(gamepad))
+    }
+
+    /// Checks if any input bound to an action has just been deactivated on a gamepad.
+    pub fn action_just_deactivated(&self, action: &str, gamepad: &Gamepad) -> bool {
+        self.binding(action)
+            .iter()
+            .any(|input| input.just_deactivated(gamepad))
+    }
+}
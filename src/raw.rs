@@ -0,0 +1,83 @@
+//! Numbered inputs of joysticks without a controller mapping, such as flight sticks,
+//! arcade sticks and DIY pads.
+//!
+//! Raw joysticks are opt-in, see
+//! [`GamepadContext::set_raw_joysticks`](crate::GamepadContext::set_raw_joysticks).
+//! Their inputs can be bound to actions through [`Profile`](crate::profile::Profile) bindings.
+
+use crate::analog::AnalogInput;
+use crate::digital::DigitalInput;
+use crate::storage::InputKind;
+
+/// A direction of a hat switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HatDirection {
+    /// Up.
+    Up,
+    /// Down.
+    Down,
+    /// Left.
+    Left,
+    /// Right.
+    Right,
+}
+
+/// A direction of a numbered hat switch, which is activated like a button.
+///
+/// Diagonals activate two directions at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawHat {
+    /// The number of the hat switch.
+    pub hat: u8,
+    /// The direction of the hat switch.
+    pub direction: HatDirection,
+}
+
+impl InputKind for RawHat {
+    const COUNT: usize = u8::COUNT * DIRECTIONS.len();
+
+    fn index(self) -> usize {
+        let direction = DIRECTIONS
+            .iter()
+            .position(|&direction| direction == self.direction)
+            .unwrap_or_default();
+        self.hat.index() * DIRECTIONS.len() + direction
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self {
+            hat: u8::from_index(index / DIRECTIONS.len()),
+            direction: DIRECTIONS[index % DIRECTIONS.len()],
+        }
+    }
+}
+
+const DIRECTIONS: [HatDirection; 4] = [
+    HatDirection::Up,
+    HatDirection::Down,
+    HatDirection::Left,
+    HatDirection::Right,
+];
+
+/// Holds the numbered inputs of a raw joystick.
+///
+/// Empty for gamepads with a controller mapping.
+#[derive(Debug, Default)]
+pub struct RawInputs {
+    /// Numbered buttons.
+    pub buttons: DigitalInput<u8>,
+    /// Numbered axes.
+    pub axes: AnalogInput<u8>,
+    /// Directions of numbered hat switches.
+    pub hats: DigitalInput<RawHat>,
+}
+
+impl RawInputs {
+    pub(crate) fn update(&mut self) {
+        self.buttons.update();
+        self.axes.update();
+        self.hats.update();
+    }
+}
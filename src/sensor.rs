@@ -0,0 +1,51 @@
+//! Motion sensor support, for controllers with gyroscopes and accelerometers.
+//!
+//! Only the SDL2 backend reports sensor data. Sensors are disabled by default and have to be
+//! enabled with [`Gamepad::set_sensor_enabled`](crate::Gamepad::set_sensor_enabled).
+
+/// A motion sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sensor {
+    /// Reports angular velocity around the X, Y and Z axes, in radians per second.
+    Gyroscope,
+    /// Reports acceleration along the X, Y and Z axes, in meters per second squared.
+    ///
+    /// Includes the acceleration due to gravity.
+    Accelerometer,
+}
+
+/// Container for motion sensor readings.
+#[derive(Debug, Default)]
+pub struct SensorInput {
+    gyroscope: Option<[f32; 3]>,
+    accelerometer: Option<[f32; 3]>,
+}
+
+impl SensorInput {
+    /// Gets the latest reading of a sensor.
+    ///
+    /// Returns `None` if the sensor is missing or disabled.
+    pub fn reading(&self, sensor: Sensor) -> Option<[f32; 3]> {
+        match sensor {
+            Sensor::Gyroscope => self.gyroscope,
+            Sensor::Accelerometer => self.accelerometer,
+        }
+    }
+
+    /// Gets the latest gyroscope reading.
+    pub fn gyroscope(&self) -> Option<[f32; 3]> {
+        self.gyroscope
+    }
+
+    /// Gets the latest accelerometer reading.
+    pub fn accelerometer(&self) -> Option<[f32; 3]> {
+        self.accelerometer
+    }
+
+    pub(crate) fn set(&mut self, sensor: Sensor, reading: Option<[f32; 3]>) {
+        match sensor {
+            Sensor::Gyroscope => self.gyroscope = reading,
+            Sensor::Accelerometer => self.accelerometer = reading,
+        }
+    }
+}
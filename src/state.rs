@@ -0,0 +1,249 @@
+//! Point-in-time gamepad state and human-readable diffs between states.
+
+use crate::{Axis, Button, Gamepad, GamepadId, AXES, BUTTONS};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A copy of the input state of a gamepad at a single point in time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GamepadState {
+    buttons: HashSet<Button>,
+    axes: HashMap<Axis, f32>,
+}
+
+impl GamepadState {
+    /// Captures the current state of a gamepad.
+    ///
+    /// Analog values are captured after deadzone remapping.
+    pub fn capture(gamepad: &Gamepad) -> Self {
+        let buttons = gamepad.digital_inputs.activated_inputs().collect();
+        let axes = gamepad
+            .analog_inputs
+            .inputs()
+            .map(|axis| (axis, gamepad.analog_inputs.value(axis)))
+            .collect();
+
+        Self { buttons, axes }
+    }
+
+    /// Checks if a button was activated.
+    pub fn activated(&self, button: Button) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// Gets the value of an axis.
+    ///
+    /// Returns `0.0` if the axis had not been read yet.
+    pub fn value(&self, axis: Axis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or_default()
+    }
+
+    /// Lists the changes needed to get from `self` to `other`.
+    pub fn diff(&self, other: &GamepadState) -> StateDiff {
+        let mut buttons: Vec<_> = self
+            .buttons
+            .symmetric_difference(&other.buttons)
+            .map(|&button| {
+                if other.activated(button) {
+                    StateChange::Pressed(button)
+                } else {
+                    StateChange::Released(button)
+                }
+            })
+            .collect();
+
+        let mut axes: Vec<_> = self
+            .axes
+            .keys()
+            .chain(other.axes.keys())
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|axis| {
+                let from = self.value(axis);
+                let to = other.value(axis);
+                (from != to).then_some(StateChange::Moved { axis, from, to })
+            })
+            .collect();
+
+        // Sort by name so that diffs of equal states always print the same way.
+        buttons.sort_by_cached_key(|change| change.to_string());
+        axes.sort_by_cached_key(|change| change.to_string());
+        buttons.append(&mut axes);
+
+        StateDiff { changes: buttons }
+    }
+}
+
+/// A compact, fixed-size copy of the input state of a gamepad, such as for rollback netcode.
+///
+/// Serializable with the `serde` feature.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamepadSnapshot {
+    // Bit `i` is set if `BUTTONS[i]` is activated.
+    buttons: u32,
+    // Value `i` is the value of `AXES[i]`.
+    axes: [f32; AXES.len()],
+}
+
+impl GamepadSnapshot {
+    /// Captures the current state of a gamepad.
+    ///
+    /// Analog values are captured after deadzone remapping.
+    pub fn capture(gamepad: &Gamepad) -> Self {
+        let mut snapshot = Self::default();
+
+        for (i, &button) in BUTTONS.iter().enumerate() {
+            if gamepad.digital_inputs.activated(button) {
+                snapshot.buttons |= 1 << i;
+            }
+        }
+        for (value, &axis) in snapshot.axes.iter_mut().zip(AXES.iter()) {
+            *value = gamepad.analog_inputs.value(axis);
+        }
+
+        snapshot
+    }
+
+    /// Checks if a button was activated.
+    pub fn activated(&self, button: Button) -> bool {
+        BUTTONS
+            .iter()
+            .position(|&other| other == button)
+            .is_some_and(|i| self.buttons & (1 << i) != 0)
+    }
+
+    /// Gets the value of an axis.
+    pub fn value(&self, axis: Axis) -> f32 {
+        AXES.iter()
+            .position(|&other| other == axis)
+            .map_or(0.0, |i| self.axes[i])
+    }
+
+    /// Lists the changes needed to get from `self` to `other`.
+    pub fn diff(&self, other: &GamepadSnapshot) -> StateDiff {
+        GamepadState::from(*self).diff(&GamepadState::from(*other))
+    }
+}
+
+impl From<GamepadSnapshot> for GamepadState {
+    fn from(snapshot: GamepadSnapshot) -> Self {
+        let buttons = BUTTONS
+            .iter()
+            .copied()
+            .filter(|&button| snapshot.activated(button))
+            .collect();
+        let axes = AXES.iter().copied().zip(snapshot.axes).collect();
+
+        Self { buttons, axes }
+    }
+}
+
+/// A thread-safe view of the state of all gamepads.
+///
+/// Obtained from [`GamepadContext::shared_state`](crate::GamepadContext::shared_state),
+/// and refreshed on every [`GamepadContext::update`](crate::GamepadContext::update).
+/// This lets a dedicated input thread own the context while other threads read gamepad state.
+#[derive(Debug, Clone, Default)]
+pub struct SharedState(Arc<RwLock<HashMap<GamepadId, GamepadSnapshot>>>);
+
+impl SharedState {
+    /// Gets the state of a specific gamepad, as of the last update.
+    ///
+    /// Returns `None` if the gamepad is not found.
+    pub fn gamepad(&self, id: GamepadId) -> Option<GamepadSnapshot> {
+        self.read().get(&id).copied()
+    }
+
+    /// Gets the state of all gamepads, as of the last update.
+    pub fn gamepads(&self) -> Vec<(GamepadId, GamepadSnapshot)> {
+        self.read()
+            .iter()
+            .map(|(&id, &snapshot)| (id, snapshot))
+            .collect()
+    }
+
+    pub(crate) fn publish(&self, gamepads: &HashMap<GamepadId, Gamepad>) {
+        let mut snapshots = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        snapshots.clear();
+        snapshots.extend(
+            gamepads
+                .iter()
+                .map(|(&id, gamepad)| (id, gamepad.snapshot())),
+        );
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<GamepadId, GamepadSnapshot>> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Every button needs a bit in `GamepadSnapshot::buttons`.
+const _: () = assert!(BUTTONS.len() <= u32::BITS as usize);
+
+/// A single difference between two gamepad states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateChange {
+    /// A button was pressed.
+    Pressed(Button),
+    /// A button was released.
+    Released(Button),
+    /// An axis changed value.
+    Moved {
+        /// The axis that changed.
+        axis: Axis,
+        /// The old value.
+        from: f32,
+        /// The new value.
+        to: f32,
+    },
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pressed(button) => write!(f, "{:?} pressed", button),
+            Self::Released(button) => write!(f, "{:?} released", button),
+            Self::Moved { axis, from, to } => write!(f, "{:?} {:.2}→{:.2}", axis, from, to),
+        }
+    }
+}
+
+/// The differences between two gamepad states.
+///
+/// Formats as a comma-separated list, e.g. `A pressed, LeftX 0.20→0.85`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    changes: Vec<StateChange>,
+}
+
+impl StateDiff {
+    /// Checks if the states were identical.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Gets the individual changes, button changes first.
+    pub fn changes(&self) -> &[StateChange] {
+        &self.changes
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "no changes");
+        }
+
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
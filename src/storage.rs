@@ -0,0 +1,337 @@
+//! Storage for per-input state, indexed by input rather than hashed.
+//!
+//! Inputs are small enums, so state is kept in a slot per possible input. Updating it neither
+//! hashes nor allocates, which matters at the event rates of high polling rate controllers.
+
+use crate::{Axis, Button, AXES, BUTTONS};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A kind of input with a small, fixed number of values, such as [`Button`].
+pub trait InputKind: Copy + Eq {
+    /// The number of distinct inputs.
+    const COUNT: usize;
+
+    /// Gets the index of the input, below [`Self::COUNT`].
+    fn index(self) -> usize;
+
+    /// Gets the input at an index below [`Self::COUNT`].
+    fn from_index(index: usize) -> Self;
+}
+
+impl InputKind for Axis {
+    const COUNT: usize = AXES.len();
+
+    fn index(self) -> usize {
+        match self {
+            Axis::LeftX => 0,
+            Axis::LeftY => 1,
+            Axis::RightX => 2,
+            Axis::RightY => 3,
+            Axis::TriggerLeft => 4,
+            Axis::TriggerRight => 5,
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        AXES[index]
+    }
+}
+
+impl InputKind for Button {
+    // Virtual buttons come after the physical ones.
+    const COUNT: usize = BUTTONS.len() + u8::COUNT;
+
+    fn index(self) -> usize {
+        match self {
+            Button::A => 0,
+            Button::B => 1,
+            Button::X => 2,
+            Button::Y => 3,
+            Button::Back => 4,
+            Button::Guide => 5,
+            Button::Start => 6,
+            Button::LeftStick => 7,
+            Button::RightStick => 8,
+            Button::LeftShoulder => 9,
+            Button::RightShoulder => 10,
+            Button::DPadUp => 11,
+            Button::DPadDown => 12,
+            Button::DPadLeft => 13,
+            Button::DPadRight => 14,
+            Button::Misc1 => 15,
+            Button::Paddle1 => 16,
+            Button::Paddle2 => 17,
+            Button::Paddle3 => 18,
+            Button::Paddle4 => 19,
+            Button::Touchpad => 20,
+            Button::Virtual(id) => BUTTONS.len() + id.index(),
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        match BUTTONS.get(index) {
+            Some(&button) => button,
+            None => Button::Virtual(u8::from_index(index - BUTTONS.len())),
+        }
+    }
+}
+
+impl InputKind for u8 {
+    const COUNT: usize = u8::MAX as usize + 1;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn from_index(index: usize) -> Self {
+        index as u8
+    }
+}
+
+/// A map from inputs to values, with a slot per input.
+///
+/// Slots are only allocated on the first insertion, so unused maps take no memory.
+/// Iterates in the order of input indices.
+#[derive(Clone)]
+pub struct InputMap<T, V> {
+    slots: Vec<Option<V>>,
+    len: usize,
+    kind: PhantomData<T>,
+}
+
+impl<T, V> InputMap<T, V>
+where
+    T: InputKind,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the number of inputs in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks if an input is in the map.
+    pub fn contains_key(&self, input: T) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Gets the value of an input.
+    pub fn get(&self, input: T) -> Option<&V> {
+        self.slots.get(input.index())?.as_ref()
+    }
+
+    /// Gets mutable access to the value of an input.
+    pub fn get_mut(&mut self, input: T) -> Option<&mut V> {
+        self.slots.get_mut(input.index())?.as_mut()
+    }
+
+    /// Sets the value of an input, returning its previous value.
+    pub fn insert(&mut self, input: T, value: V) -> Option<V> {
+        let previous = Self::allocate(&mut self.slots, input).replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Removes an input from the map, returning its value.
+    pub fn remove(&mut self, input: T) -> Option<V> {
+        let previous = self.slots.get_mut(input.index())?.take();
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    /// Gets mutable access to the value of an input, inserting one made by `default` if there
+    /// is none.
+    pub fn get_or_insert_with(&mut self, input: T, default: impl FnOnce() -> V) -> &mut V {
+        let len = &mut self.len;
+        let slot = Self::allocate(&mut self.slots, input);
+        slot.get_or_insert_with(|| {
+            *len += 1;
+            default()
+        })
+    }
+
+    /// Removes all inputs, keeping the slots allocated.
+    pub fn clear(&mut self) {
+        if self.len > 0 {
+            self.slots.iter_mut().for_each(|slot| *slot = None);
+            self.len = 0;
+        }
+    }
+
+    /// Iterates over the inputs in the map and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (T, &V)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| Some((T::from_index(index), slot.as_ref()?)))
+    }
+
+    /// Iterates over the inputs in the map.
+    pub fn keys(&self) -> impl Iterator<Item = T> + '_ {
+        self.iter().map(|(input, _)| input)
+    }
+
+    fn allocate(slots: &mut Vec<Option<V>>, input: T) -> &mut Option<V> {
+        if slots.is_empty() {
+            slots.resize_with(T::COUNT, || None);
+        }
+        &mut slots[input.index()]
+    }
+}
+
+impl<T, V> Default for InputMap<T, V> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T, V> fmt::Debug for InputMap<T, V>
+where
+    T: InputKind + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A set of inputs, as a bitset.
+///
+/// The bitset is only allocated on the first insertion, so unused sets take no memory.
+/// Iterates in the order of input indices.
+#[derive(Clone)]
+pub struct InputSet<T> {
+    words: Vec<u64>,
+    len: usize,
+    kind: PhantomData<T>,
+}
+
+impl<T> InputSet<T>
+where
+    T: InputKind,
+{
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the number of inputs in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks if an input is in the set.
+    pub fn contains(&self, input: T) -> bool {
+        let (word, bit) = position(input);
+        self.words.get(word).is_some_and(|word| word & bit != 0)
+    }
+
+    /// Adds an input to the set, returning `false` if it already was in it.
+    pub fn insert(&mut self, input: T) -> bool {
+        if self.words.is_empty() {
+            self.words.resize(T::COUNT.div_ceil(u64::BITS as usize), 0);
+        }
+
+        let (word, bit) = position(input);
+        let inserted = self.words[word] & bit == 0;
+        if inserted {
+            self.words[word] |= bit;
+            self.len += 1;
+        }
+        inserted
+    }
+
+    /// Removes an input from the set, returning `false` if it was not in it.
+    pub fn remove(&mut self, input: T) -> bool {
+        let (word, bit) = position(input);
+        let removed = self.words.get(word).is_some_and(|word| word & bit != 0);
+        if removed {
+            self.words[word] &= !bit;
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Removes all inputs, keeping the bitset allocated.
+    pub fn clear(&mut self) {
+        if self.len > 0 {
+            self.words.iter_mut().for_each(|word| *word = 0);
+            self.len = 0;
+        }
+    }
+
+    /// Iterates over the inputs in the set.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, &word)| word != 0)
+            .flat_map(|(index, &word)| {
+                let start = index * u64::BITS as usize;
+                (0..u64::BITS as usize)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| T::from_index(start + bit))
+            })
+    }
+}
+
+/// Gets the word and bit of an input in a bitset.
+fn position<T: InputKind>(input: T) -> (usize, u64) {
+    let index = input.index();
+    let bits = u64::BITS as usize;
+    (index / bits, 1 << (index % bits))
+}
+
+impl<T> Default for InputSet<T> {
+    fn default() -> Self {
+        Self {
+            words: Vec::new(),
+            len: 0,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for InputSet<T>
+where
+    T: InputKind + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for InputSet<T>
+where
+    T: InputKind,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for input in iter {
+            set.insert(input);
+        }
+        set
+    }
+}
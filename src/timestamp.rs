@@ -0,0 +1,30 @@
+//! Timestamps of input changes.
+
+use std::time::{Instant, SystemTime};
+
+/// When an input changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// The number of updates since the gamepad connected, at the time of the change.
+    pub tick: u64,
+    /// The time at which the change happened.
+    pub instant: Instant,
+}
+
+impl Timestamp {
+    pub(crate) fn now(tick: u64) -> Self {
+        Self {
+            tick,
+            instant: Instant::now(),
+        }
+    }
+}
+
+/// Converts the wall-clock time of an operating system event to an instant, assuming the clock
+/// did not jump since the event. Times in the future are clamped to now.
+#[cfg_attr(not(any(feature = "gilrs", feature = "evdev")), allow(dead_code))]
+pub(crate) fn instant_from_system_time(time: SystemTime) -> Instant {
+    let now = Instant::now();
+    let age = SystemTime::now().duration_since(time).unwrap_or_default();
+    now.checked_sub(age).unwrap_or(now)
+}
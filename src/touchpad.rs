@@ -0,0 +1,266 @@
+//! Touchpad support, for controllers such as the DualShock 4 and DualSense.
+//!
+//! Only the SDL2 backend reports touchpad input. Other backends never report any fingers.
+//!
+//! Taps and swipes are recognized as [`TouchGesture`]s, which are activated like buttons for a
+//! single update, and can be bound to actions in a [`Profile`](crate::profile::Profile).
+
+use crate::digital::DigitalInput;
+use crate::storage::{InputKind, InputSet};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Identifies a finger slot on one of a gamepad's touchpads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FingerId {
+    /// The index of the touchpad.
+    pub touchpad: usize,
+    /// The index of the finger on the touchpad.
+    pub finger: usize,
+}
+
+/// The state of a finger touching a touchpad.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Finger {
+    /// Horizontal position, from `0.0` (left) to `1.0` (right).
+    pub x: f32,
+    /// Vertical position, from `0.0` (top) to `1.0` (bottom).
+    pub y: f32,
+    /// Pressure, from `0.0` to `1.0`.
+    pub pressure: f32,
+}
+
+/// A gesture recognized on a touchpad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gesture {
+    /// A short touch of a single finger.
+    Tap,
+    /// A short touch of two or more fingers.
+    TwoFingerTap,
+    /// A quick stroke upwards.
+    SwipeUp,
+    /// A quick stroke downwards.
+    SwipeDown,
+    /// A quick stroke to the left.
+    SwipeLeft,
+    /// A quick stroke to the right.
+    SwipeRight,
+}
+
+const GESTURES: [Gesture; 6] = [
+    Gesture::Tap,
+    Gesture::TwoFingerTap,
+    Gesture::SwipeUp,
+    Gesture::SwipeDown,
+    Gesture::SwipeLeft,
+    Gesture::SwipeRight,
+];
+
+/// A gesture on one of a gamepad's touchpads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TouchGesture {
+    /// The index of the touchpad.
+    pub touchpad: u8,
+    /// The gesture.
+    pub gesture: Gesture,
+}
+
+impl InputKind for TouchGesture {
+    const COUNT: usize = u8::COUNT * GESTURES.len();
+
+    fn index(self) -> usize {
+        let gesture = GESTURES
+            .iter()
+            .position(|&gesture| gesture == self.gesture)
+            .unwrap_or_default();
+        self.touchpad.index() * GESTURES.len() + gesture
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self {
+            touchpad: u8::from_index(index / GESTURES.len()),
+            gesture: GESTURES[index % GESTURES.len()],
+        }
+    }
+}
+
+/// The longest touch that counts as a tap.
+const TAP_DURATION: Duration = Duration::from_millis(250);
+/// How far a finger may move during a tap.
+const TAP_DISTANCE: f32 = 0.05;
+/// The longest touch that counts as a swipe.
+const SWIPE_DURATION: Duration = Duration::from_millis(500);
+/// How far a finger has to move to swipe, as a fraction of the touchpad.
+const SWIPE_DISTANCE: f32 = 0.3;
+
+/// A touch on a touchpad, from the first finger down to the last finger up.
+#[derive(Debug)]
+struct Touch {
+    started: Instant,
+    // The finger that started the touch, which decides the direction of swipes.
+    lead: usize,
+    start: (f32, f32),
+    position: (f32, f32),
+    fingers: usize,
+    max_fingers: usize,
+}
+
+impl Touch {
+    fn gesture(&self) -> Option<Gesture> {
+        let duration = self.started.elapsed();
+        let (dx, dy) = (
+            self.position.0 - self.start.0,
+            self.position.1 - self.start.1,
+        );
+        let distance = dx.hypot(dy);
+
+        if duration <= TAP_DURATION && distance < TAP_DISTANCE {
+            if self.max_fingers >= 2 {
+                Some(Gesture::TwoFingerTap)
+            } else {
+                Some(Gesture::Tap)
+            }
+        } else if duration <= SWIPE_DURATION && distance >= SWIPE_DISTANCE {
+            // Vertical positions grow downwards.
+            let gesture = match (dx.abs() > dy.abs(), dx < 0.0, dy < 0.0) {
+                (true, true, _) => Gesture::SwipeLeft,
+                (true, false, _) => Gesture::SwipeRight,
+                (false, _, true) => Gesture::SwipeUp,
+                (false, _, false) => Gesture::SwipeDown,
+            };
+            Some(gesture)
+        } else {
+            None
+        }
+    }
+}
+
+/// Container for touchpad inputs.
+#[derive(Debug, Default)]
+pub struct TouchpadInput {
+    fingers: HashMap<FingerId, Finger>,
+    just_touched: HashSet<FingerId>,
+    just_released: HashSet<FingerId>,
+    touches: HashMap<usize, Touch>,
+    gestures: DigitalInput<TouchGesture>,
+    // Gestures recognized during the last update, which are released on the next one.
+    recognized: InputSet<TouchGesture>,
+}
+
+impl TouchpadInput {
+    /// Gets the state of a finger.
+    ///
+    /// Returns `None` if the finger is not touching the touchpad.
+    pub fn finger(&self, id: FingerId) -> Option<Finger> {
+        self.fingers.get(&id).copied()
+    }
+
+    /// Gets an iterator over all fingers touching any touchpad.
+    pub fn fingers(&self) -> impl Iterator<Item = (FingerId, Finger)> + '_ {
+        self.fingers.iter().map(|(&id, &finger)| (id, finger))
+    }
+
+    /// Checks if a finger has just started touching a touchpad.
+    pub fn just_touched(&self, id: FingerId) -> bool {
+        self.just_touched.contains(&id)
+    }
+
+    /// Checks if a finger has just stopped touching a touchpad.
+    pub fn just_released(&self, id: FingerId) -> bool {
+        self.just_released.contains(&id)
+    }
+
+    /// Gets the recognized gestures.
+    ///
+    /// A gesture is activated, and just activated, for the update in which its last finger was
+    /// lifted.
+    pub fn gestures(&self) -> &DigitalInput<TouchGesture> {
+        &self.gestures
+    }
+
+    pub(crate) fn set(&mut self, id: FingerId, finger: Option<Finger>) {
+        match finger {
+            Some(finger) => {
+                if self.fingers.insert(id, finger).is_none() {
+                    self.just_touched.insert(id);
+                    self.just_released.remove(&id);
+                    self.touch_down(id, finger);
+                } else {
+                    self.touch_move(id, finger);
+                }
+            }
+            None => {
+                if self.fingers.remove(&id).is_some() {
+                    self.just_touched.remove(&id);
+                    self.just_released.insert(id);
+                    self.touch_up(id);
+                }
+            }
+        }
+    }
+
+    /// Lifts every finger without recognizing gestures, such as when the device can no longer
+    /// be read.
+    pub(crate) fn release_all(&mut self) {
+        for (id, _) in self.fingers.drain() {
+            self.just_touched.remove(&id);
+            self.just_released.insert(id);
+        }
+        self.touches.clear();
+    }
+
+    fn touch_down(&mut self, id: FingerId, finger: Finger) {
+        let touch = self.touches.entry(id.touchpad).or_insert_with(|| Touch {
+            started: Instant::now(),
+            lead: id.finger,
+            start: (finger.x, finger.y),
+            position: (finger.x, finger.y),
+            fingers: 0,
+            max_fingers: 0,
+        });
+        touch.fingers += 1;
+        touch.max_fingers = touch.max_fingers.max(touch.fingers);
+    }
+
+    fn touch_move(&mut self, id: FingerId, finger: Finger) {
+        if let Some(touch) = self.touches.get_mut(&id.touchpad) {
+            if touch.lead == id.finger {
+                touch.position = (finger.x, finger.y);
+            }
+        }
+    }
+
+    fn touch_up(&mut self, id: FingerId) {
+        let touch = match self.touches.get_mut(&id.touchpad) {
+            Some(touch) => touch,
+            None => return,
+        };
+        touch.fingers = touch.fingers.saturating_sub(1);
+        if touch.fingers > 0 {
+            return;
+        }
+
+        let gesture = touch.gesture();
+        self.touches.remove(&id.touchpad);
+        if let (Some(gesture), Ok(touchpad)) = (gesture, u8::try_from(id.touchpad)) {
+            let gesture = TouchGesture { touchpad, gesture };
+            self.gestures.activate(gesture);
+            self.recognized.insert(gesture);
+        }
+    }
+
+    pub(crate) fn update(&mut self) {
+        self.just_touched.clear();
+        self.just_released.clear();
+
+        self.gestures.update();
+        for gesture in self.recognized.iter() {
+            self.gestures.deactivate(gesture);
+        }
+        self.recognized.clear();
+    }
+}
@@ -0,0 +1,71 @@
+//! Adaptive trigger effects, such as those of the DualSense.
+
+/// An effect played by the motor of an adaptive trigger.
+///
+/// Positions and strengths go from `0.0` to `1.0`, where a position of `0.0` is the released
+/// trigger. Effects last until another effect is set on the same trigger.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TriggerEffect {
+    /// No effect, the trigger moves freely.
+    #[default]
+    Off,
+    /// Constant resistance from `start` to the end of the trigger's travel.
+    Resistance {
+        /// Position at which the resistance starts.
+        start: f32,
+        /// Strength of the resistance.
+        strength: f32,
+    },
+    /// Resistance between `start` and `end` that gives way past `end`, like a gun's trigger.
+    Weapon {
+        /// Position at which the resistance starts.
+        start: f32,
+        /// Position at which the resistance gives way.
+        end: f32,
+        /// Strength of the resistance.
+        strength: f32,
+    },
+    /// Vibration from `start` to the end of the trigger's travel.
+    Vibration {
+        /// Position at which the vibration starts.
+        start: f32,
+        /// Strength of the vibration.
+        amplitude: f32,
+        /// Frequency of the vibration, in hertz.
+        frequency: u8,
+    },
+}
+
+impl TriggerEffect {
+    /// Encodes the effect as the trigger parameters of a DualSense output report.
+    #[cfg_attr(not(feature = "sdl2"), allow(dead_code))]
+    pub(crate) fn to_dualsense(self) -> [u8; 11] {
+        let mut data = [0; 11];
+        match self {
+            TriggerEffect::Off => data[0] = 0x05,
+            TriggerEffect::Resistance { start, strength } => {
+                data[..3].copy_from_slice(&[0x01, to_byte(start), to_byte(strength)]);
+            }
+            TriggerEffect::Weapon {
+                start,
+                end,
+                strength,
+            } => {
+                data[..4].copy_from_slice(&[0x02, to_byte(start), to_byte(end), to_byte(strength)]);
+            }
+            TriggerEffect::Vibration {
+                start,
+                amplitude,
+                frequency,
+            } => {
+                data[..4].copy_from_slice(&[0x06, frequency, to_byte(amplitude), to_byte(start)]);
+            }
+        }
+        data
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * u8::MAX as f32) as u8
+}
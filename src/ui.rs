@@ -0,0 +1,206 @@
+//! Menu navigation, translating gamepad input into generic UI actions.
+//!
+//! [`UiNavigator`] turns the D-pad, the left stick, [`Button::A`] and [`Button::B`] into
+//! [`UiAction`]s, repeating directions while they are held like a keyboard does. UI toolkits such
+//! as egui and iced can then treat the actions as the equivalent key presses, given by
+//! [`UiAction::key_name`].
+
+use crate::direction::Socd;
+use crate::{Axis, Button, Gamepad, GamepadContext, GamepadId};
+use std::time::{Duration, Instant};
+
+const DEFAULT_REPEAT_DELAY: Duration = Duration::from_millis(400);
+const DEFAULT_REPEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A UI navigation action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UiAction {
+    /// Move the focus up.
+    Up,
+    /// Move the focus down.
+    Down,
+    /// Move the focus left.
+    Left,
+    /// Move the focus right.
+    Right,
+    /// Activate the focused element.
+    Accept,
+    /// Go back, or close the focused menu.
+    Cancel,
+}
+
+impl UiAction {
+    /// Gets the name of the equivalent keyboard key, as used by the web `KeyboardEvent.key`,
+    /// such as `"ArrowUp"` or `"Enter"`.
+    pub fn key_name(self) -> &'static str {
+        match self {
+            UiAction::Up => "ArrowUp",
+            UiAction::Down => "ArrowDown",
+            UiAction::Left => "ArrowLeft",
+            UiAction::Right => "ArrowRight",
+            UiAction::Accept => "Enter",
+            UiAction::Cancel => "Escape",
+        }
+    }
+}
+
+/// A direction that is held, and when it was last reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Held {
+    action: UiAction,
+    since: Instant,
+    reported: Instant,
+}
+
+/// Emits [`UiAction`]s from gamepad input. Update it once per update, after the context.
+///
+/// A direction is reported when it is first held, then again after the repeat delay, and then
+/// once per repeat interval for as long as it is held. Diagonals resolve to the axis that is
+/// pushed the furthest, preferring vertical movement on ties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiNavigator {
+    repeat_delay: Duration,
+    repeat_interval: Duration,
+    held: Option<Held>,
+    actions: Vec<UiAction>,
+}
+
+impl UiNavigator {
+    /// Creates a navigator that repeats directions after 400 ms, every 100 ms.
+    pub fn new() -> Self {
+        Self {
+            repeat_delay: DEFAULT_REPEAT_DELAY,
+            repeat_interval: DEFAULT_REPEAT_INTERVAL,
+            held: None,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Sets how long a direction has to be held before it repeats, and how often it repeats.
+    pub fn with_repeat(mut self, delay: Duration, interval: Duration) -> Self {
+        self.repeat_delay = delay;
+        self.repeat_interval = interval;
+        self
+    }
+
+    /// Gets how long a direction has to be held before it repeats.
+    pub fn repeat_delay(&self) -> Duration {
+        self.repeat_delay
+    }
+
+    /// Gets how often a held direction repeats.
+    pub fn repeat_interval(&self) -> Duration {
+        self.repeat_interval
+    }
+
+    /// Reads every gamepad of the context and returns the actions of this update.
+    ///
+    /// When several gamepads hold a direction, the one with the lowest Id wins.
+    pub fn update(&mut self, context: &GamepadContext) -> impl Iterator<Item = UiAction> + '_ {
+        self.read(context.gamepads());
+        self.actions.drain(..)
+    }
+
+    /// Reads a single gamepad and returns the actions of this update.
+    pub fn update_gamepad(&mut self, gamepad: &Gamepad) -> impl Iterator<Item = UiAction> + '_ {
+        self.read(std::iter::once((GamepadId(0), gamepad)));
+        self.actions.drain(..)
+    }
+
+    /// Forgets the held direction, so that it is not repeated, such as when a menu closes.
+    pub fn reset(&mut self) {
+        self.held = None;
+    }
+
+    fn read<'a>(&mut self, gamepads: impl Iterator<Item = (GamepadId, &'a Gamepad)>) {
+        let now = Instant::now();
+        let mut direction: Option<(GamepadId, UiAction)> = None;
+
+        for (id, gamepad) in gamepads {
+            let buttons = &gamepad.digital_inputs;
+            if buttons.just_activated(Button::A) {
+                self.actions.push(UiAction::Accept);
+            }
+            if buttons.just_activated(Button::B) {
+                self.actions.push(UiAction::Cancel);
+            }
+            if direction.is_none_or(|(held_by, _)| id < held_by) {
+                if let Some(action) = held_direction(gamepad) {
+                    direction = Some((id, action));
+                }
+            }
+        }
+
+        match (direction.map(|(_, action)| action), self.held) {
+            (Some(action), Some(held)) if held.action == action => {
+                let held_for = now - held.since;
+                let since_reported = now - held.reported;
+                let repeat = if held.since == held.reported {
+                    held_for >= self.repeat_delay
+                } else {
+                    since_reported >= self.repeat_interval
+                };
+                if repeat {
+                    self.actions.push(action);
+                    self.held = Some(Held {
+                        reported: now,
+                        ..held
+                    });
+                }
+            }
+            (Some(action), _) => {
+                self.actions.push(action);
+                self.held = Some(Held {
+                    action,
+                    since: now,
+                    reported: now,
+                });
+            }
+            (None, _) => self.held = None,
+        }
+    }
+}
+
+impl Default for UiNavigator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gets the direction held on the D-pad, or else on the left stick.
+fn held_direction(gamepad: &Gamepad) -> Option<UiAction> {
+    let (x, y) = match gamepad.digital_inputs.dpad_direction(Socd::Neutral) {
+        Some(direction) => {
+            let (x, y) = direction.to_vector();
+            (f32::from(x), f32::from(y))
+        }
+        None => {
+            let analog_inputs = &gamepad.analog_inputs;
+            let axis = |axis| {
+                if analog_inputs.digital_value(axis) == 0.0 {
+                    0.0
+                } else {
+                    analog_inputs.value(axis)
+                }
+            };
+            (axis(Axis::LeftX), axis(Axis::LeftY))
+        }
+    };
+
+    if x == 0.0 && y == 0.0 {
+        None
+    } else if y.abs() >= x.abs() {
+        Some(if y < 0.0 {
+            UiAction::Up
+        } else {
+            UiAction::Down
+        })
+    } else {
+        Some(if x < 0.0 {
+            UiAction::Left
+        } else {
+            UiAction::Right
+        })
+    }
+}
@@ -0,0 +1,75 @@
+//! Buttons that are activated by an axis crossing a threshold.
+
+use crate::{Axis, Button, Gamepad};
+
+/// A button activated by an axis crossing a threshold, such as a trigger pulled a third of the way.
+///
+/// It appears in [`Gamepad::digital_inputs`] as [`Button::Virtual`], with the same just activated
+/// and just deactivated semantics as physical buttons.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VirtualButton {
+    axis: Axis,
+    threshold: f32,
+}
+
+impl VirtualButton {
+    /// Creates a button that is activated while the value of `axis` is past `threshold`.
+    ///
+    /// A positive threshold activates the button at or above it, and a negative threshold at or
+    /// below it. Values are compared after deadzone remapping.
+    pub fn from_axis(axis: Axis, threshold: f32) -> Self {
+        Self { axis, threshold }
+    }
+
+    /// Gets the axis that activates the button.
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    /// Gets the threshold the axis has to cross.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Checks if an axis value activates the button.
+    pub fn activated_by(&self, value: f32) -> bool {
+        if self.threshold < 0.0 {
+            value <= self.threshold
+        } else {
+            value >= self.threshold
+        }
+    }
+}
+
+impl Gamepad {
+    /// Registers a virtual button with the given Id, replacing any previous one.
+    pub fn set_virtual_button(&mut self, id: u8, button: VirtualButton) {
+        self.virtual_buttons.insert(id, button);
+    }
+
+    /// Removes the virtual button with the given Id, releasing it.
+    pub fn remove_virtual_button(&mut self, id: u8) {
+        if self.virtual_buttons.remove(id).is_some() {
+            self.digital_inputs.set_emulated(Button::Virtual(id), false);
+        }
+    }
+
+    /// Gets the virtual button with the given Id.
+    pub fn virtual_button(&self, id: u8) -> Option<VirtualButton> {
+        self.virtual_buttons.get(id).copied()
+    }
+
+    /// Iterates over the Ids of the registered virtual buttons.
+    pub fn virtual_buttons(&self) -> impl Iterator<Item = u8> + '_ {
+        self.virtual_buttons.keys()
+    }
+
+    pub(crate) fn update_virtual_buttons(&mut self) {
+        for (id, button) in self.virtual_buttons.iter() {
+            let active = button.activated_by(self.analog_inputs.value(button.axis));
+            self.digital_inputs
+                .set_emulated(Button::Virtual(id), active);
+        }
+    }
+}
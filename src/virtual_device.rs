@@ -0,0 +1,117 @@
+//! Virtual gamepads that the operating system sees as real ones, driven from code.
+//!
+//! Enabled by the `virtual-device` feature. On Linux, devices are created through uinput, which
+//! needs write access to `/dev/uinput`. On Windows, they are created through the ViGEmBus driver,
+//! which has to be installed, and the `ViGEmClient` library, which has to be linked.
+//! Virtual gamepads appear as Xbox 360 controllers, and are picked up by every backend,
+//! including this crate's own, which makes them useful for bots, accessibility remappers and
+//! end-to-end tests.
+
+use crate::storage::{InputKind, InputSet};
+use crate::{Axis, Button, Gamepad, BUTTONS};
+
+use crate::Result;
+
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(windows)]
+mod vigem;
+
+#[cfg(target_os = "linux")]
+use uinput::Device;
+#[cfg(windows)]
+use vigem::Device;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+struct Device;
+
+#[cfg(not(any(target_os = "linux", windows)))]
+impl Device {
+    fn create(_: &str) -> Result<Self> {
+        Err("Virtual gamepads are not supported on this platform".into())
+    }
+
+    fn send(&mut self, _: &Report) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The inputs sent to the operating system on every [`VirtualGamepad::sync`].
+#[derive(Debug, Default, Clone)]
+struct Report {
+    buttons: InputSet<Button>,
+    // Sticks from `-1.0` to `1.0` with `y` positive downwards, triggers from `0.0` to `1.0`.
+    axes: [f32; Axis::COUNT],
+}
+
+impl Report {
+    fn axis(&self, axis: Axis) -> f32 {
+        self.axes[axis.index()]
+    }
+}
+
+/// A virtual gamepad, removed from the system when dropped.
+///
+/// Changes to its inputs are buffered until [`Self::sync`] sends them all at once.
+pub struct VirtualGamepad {
+    device: Device,
+    report: Report,
+}
+
+impl VirtualGamepad {
+    /// Creates a virtual gamepad. `name` is shown to applications where the platform allows.
+    pub fn create(name: &str) -> Result<Self> {
+        Ok(Self {
+            device: Device::create(name)?,
+            report: Report::default(),
+        })
+    }
+
+    /// Presses or releases a button.
+    ///
+    /// Buttons that an Xbox 360 controller lacks, such as [`Button::Touchpad`], are not sent.
+    /// On Linux, [`Button::Misc1`] and the paddles are sent like the evdev backend reads them.
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.report.buttons.insert(button);
+        } else {
+            self.report.buttons.remove(button);
+        }
+    }
+
+    /// Moves an axis, from `-1.0` to `1.0` for sticks, or `0.0` to `1.0` for triggers.
+    ///
+    /// Like [`Gamepad::analog_inputs`], the vertical stick axes are positive downwards.
+    pub fn set_axis(&mut self, axis: Axis, value: f32) {
+        let value = match axis {
+            Axis::TriggerLeft | Axis::TriggerRight => value.clamp(0.0, 1.0),
+            _ => value.clamp(-1.0, 1.0),
+        };
+        self.report.axes[axis.index()] = value;
+    }
+
+    /// Copies the activated buttons and axis values of a gamepad, such as to forward a remapped
+    /// or replayed gamepad.
+    ///
+    /// Axes are copied as the backend reported them, since whatever reads the virtual gamepad
+    /// applies deadzones of its own. See
+    /// [`AnalogInput::raw_value`](crate::analog::AnalogInput::raw_value).
+    pub fn copy_from(&mut self, gamepad: &Gamepad) {
+        for button in BUTTONS {
+            self.set_button(button, gamepad.digital_inputs.activated(button));
+        }
+        for axis in crate::AXES {
+            self.set_axis(axis, gamepad.analog_inputs.raw_value(axis));
+        }
+    }
+
+    /// Releases every button and centers every axis.
+    pub fn reset(&mut self) {
+        self.report = Report::default();
+    }
+
+    /// Sends the current inputs to the operating system.
+    pub fn sync(&mut self) -> Result<()> {
+        self.device.send(&self.report)
+    }
+}
@@ -0,0 +1,202 @@
+use super::Report;
+use crate::{Axis, Button};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+
+use crate::Result;
+
+// Event types and codes from linux/input-event-codes.h.
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0;
+const BUS_USB: u16 = 0x03;
+const ABS_CNT: usize = 0x40;
+
+// Requests from linux/uinput.h.
+const UI_DEV_CREATE: u64 = ioc(0, 1, 0);
+const UI_DEV_DESTROY: u64 = ioc(0, 2, 0);
+const UI_SET_EVBIT: u64 = ioc(1, 100, mem::size_of::<libc::c_int>());
+const UI_SET_KEYBIT: u64 = ioc(1, 101, mem::size_of::<libc::c_int>());
+const UI_SET_ABSBIT: u64 = ioc(1, 103, mem::size_of::<libc::c_int>());
+
+/// Key codes of the buttons, matching the evdev backend.
+const BUTTONS: [(Button, u16); 20] = [
+    (Button::A, 0x130),
+    (Button::B, 0x131),
+    (Button::Y, 0x133),
+    (Button::X, 0x134),
+    (Button::LeftShoulder, 0x136),
+    (Button::RightShoulder, 0x137),
+    (Button::Back, 0x13a),
+    (Button::Start, 0x13b),
+    (Button::Guide, 0x13c),
+    (Button::LeftStick, 0x13d),
+    (Button::RightStick, 0x13e),
+    (Button::DPadUp, 0x220),
+    (Button::DPadDown, 0x221),
+    (Button::DPadLeft, 0x222),
+    (Button::DPadRight, 0x223),
+    (Button::Misc1, 0xa7),
+    (Button::Paddle1, 0x2c4),
+    (Button::Paddle3, 0x2c5),
+    (Button::Paddle2, 0x2c6),
+    (Button::Paddle4, 0x2c7),
+];
+
+/// Absolute axis codes of the axes, with their ranges.
+const AXES: [(Axis, u16, i32, i32); 6] = [
+    (Axis::LeftX, 0x00, -32768, 32767),
+    (Axis::LeftY, 0x01, -32768, 32767),
+    (Axis::RightX, 0x03, -32768, 32767),
+    (Axis::RightY, 0x04, -32768, 32767),
+    (Axis::TriggerLeft, 0x02, 0, 255),
+    (Axis::TriggerRight, 0x05, 0, 255),
+];
+
+/// Builds an `_IOC(dir, 'U', nr, size)` ioctl request.
+const fn ioc(dir: u64, nr: u64, size: usize) -> u64 {
+    (dir << 30) | ((size as u64) << 16) | ((b'U' as u64) << 8) | nr
+}
+
+/// `struct uinput_user_dev`, the legacy device setup that every kernel with uinput supports.
+#[repr(C)]
+struct UserDev {
+    name: [u8; 80],
+    id: libc::input_id,
+    ff_effects_max: u32,
+    absmax: [i32; ABS_CNT],
+    absmin: [i32; ABS_CNT],
+    absfuzz: [i32; ABS_CNT],
+    absflat: [i32; ABS_CNT],
+}
+
+/// A uinput device node, destroyed when dropped.
+pub struct Device {
+    file: File,
+    events: Vec<libc::input_event>,
+}
+
+impl Device {
+    pub fn create(name: &str) -> Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .map_err(|e| format!("Failed to open /dev/uinput: {}", e))?;
+        let fd = file.as_raw_fd();
+
+        let set_bit = |request: u64, bit: u16| {
+            // SAFETY: these requests take an integer argument.
+            if unsafe { libc::ioctl(fd, request as _, libc::c_int::from(bit)) } < 0 {
+                Err(format!(
+                    "Failed to set up the virtual gamepad: {}",
+                    std::io::Error::last_os_error()
+                ))
+            } else {
+                Ok(())
+            }
+        };
+        set_bit(UI_SET_EVBIT, EV_KEY)?;
+        set_bit(UI_SET_EVBIT, EV_ABS)?;
+        for (_, code) in BUTTONS {
+            set_bit(UI_SET_KEYBIT, code)?;
+        }
+        for (_, code, _, _) in AXES {
+            set_bit(UI_SET_ABSBIT, code)?;
+        }
+
+        // SAFETY: the struct only holds integers, for which zero is valid.
+        let mut dev: UserDev = unsafe { mem::zeroed() };
+        let len = name.len().min(dev.name.len() - 1);
+        dev.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        // Identify as an Xbox 360 controller, so that applications map it without configuration.
+        dev.id = libc::input_id {
+            bustype: BUS_USB,
+            vendor: 0x045e,
+            product: 0x028e,
+            version: 0x0110,
+        };
+        for (_, code, min, max) in AXES {
+            dev.absmin[code as usize] = min;
+            dev.absmax[code as usize] = max;
+        }
+
+        // SAFETY: `UserDev` is `repr(C)` without padding, so all of its bytes are initialized.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &dev as *const UserDev as *const u8,
+                mem::size_of::<UserDev>(),
+            )
+        };
+        (&file)
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to set up the virtual gamepad: {}", e))?;
+
+        // SAFETY: this request takes no argument.
+        if unsafe { libc::ioctl(fd, UI_DEV_CREATE as _) } < 0 {
+            return Err(format!(
+                "Failed to create the virtual gamepad: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        Ok(Self {
+            file,
+            events: Vec::with_capacity(BUTTONS.len() + AXES.len() + 1),
+        })
+    }
+
+    pub fn send(&mut self, report: &Report) -> Result<()> {
+        const EVENT_SIZE: usize = mem::size_of::<libc::input_event>();
+
+        // The kernel drops events that do not change anything, so the whole state is sent.
+        self.events.clear();
+        for (button, code) in BUTTONS {
+            let value = report.buttons.contains(button).into();
+            self.events.push(event(EV_KEY, code, value));
+        }
+        for (axis, code, min, max) in AXES {
+            let unit = match axis {
+                Axis::TriggerLeft | Axis::TriggerRight => report.axis(axis),
+                _ => (report.axis(axis) + 1.0) / 2.0,
+            };
+            let value = min + ((max - min) as f32 * unit).round() as i32;
+            self.events.push(event(EV_ABS, code, value));
+        }
+        self.events.push(event(EV_SYN, SYN_REPORT, 0));
+
+        // SAFETY: `input_event` is a plain C struct, and the slice covers the whole buffer.
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                self.events.as_ptr() as *const u8,
+                self.events.len() * EVENT_SIZE,
+            )
+        };
+        self.file
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to send virtual gamepad input: {}", e))
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        // SAFETY: this request takes no argument.
+        unsafe { libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY as _) };
+    }
+}
+
+fn event(type_: u16, code: u16, value: i32) -> libc::input_event {
+    libc::input_event {
+        time: libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        type_,
+        code,
+        value,
+    }
+}
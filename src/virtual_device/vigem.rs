@@ -0,0 +1,140 @@
+use super::Report;
+use crate::{Axis, Button};
+use std::ffi::c_void;
+
+use crate::Result;
+
+type Client = *mut c_void;
+type Target = *mut c_void;
+type Error = u32;
+
+const VIGEM_ERROR_NONE: Error = 0x2000_0000;
+
+/// `XUSB_REPORT`, the state of an Xbox 360 controller, like `XINPUT_GAMEPAD`.
+#[repr(C)]
+struct XusbReport {
+    buttons: u16,
+    left_trigger: u8,
+    right_trigger: u8,
+    thumb_lx: i16,
+    thumb_ly: i16,
+    thumb_rx: i16,
+    thumb_ry: i16,
+}
+
+#[link(name = "ViGEmClient")]
+extern "C" {
+    fn vigem_alloc() -> Client;
+    fn vigem_free(client: Client);
+    fn vigem_connect(client: Client) -> Error;
+    fn vigem_disconnect(client: Client);
+    fn vigem_target_x360_alloc() -> Target;
+    fn vigem_target_free(target: Target);
+    fn vigem_target_add(client: Client, target: Target) -> Error;
+    fn vigem_target_remove(client: Client, target: Target) -> Error;
+    fn vigem_target_x360_update(client: Client, target: Target, report: XusbReport) -> Error;
+}
+
+/// Button flags of `XUSB_REPORT`, the same as XInput's.
+const BUTTONS: [(Button, u16); 15] = [
+    (Button::DPadUp, 0x0001),
+    (Button::DPadDown, 0x0002),
+    (Button::DPadLeft, 0x0004),
+    (Button::DPadRight, 0x0008),
+    (Button::Start, 0x0010),
+    (Button::Back, 0x0020),
+    (Button::LeftStick, 0x0040),
+    (Button::RightStick, 0x0080),
+    (Button::LeftShoulder, 0x0100),
+    (Button::RightShoulder, 0x0200),
+    (Button::Guide, 0x0400),
+    (Button::A, 0x1000),
+    (Button::B, 0x2000),
+    (Button::X, 0x4000),
+    (Button::Y, 0x8000),
+];
+
+/// A virtual Xbox 360 controller on the ViGEmBus driver, removed when dropped.
+///
+/// ViGEm names the controller itself.
+pub struct Device {
+    client: Client,
+    target: Target,
+}
+
+impl Device {
+    pub fn create(_: &str) -> Result<Self> {
+        // SAFETY: the client and target are freed on every path that does not keep them.
+        unsafe {
+            let client = vigem_alloc();
+            if client.is_null() {
+                return Err("Failed to allocate a ViGEm client".into());
+            }
+            let error = vigem_connect(client);
+            if error != VIGEM_ERROR_NONE {
+                vigem_free(client);
+                return Err(format!(
+                    "Failed to connect to ViGEmBus (error {:#x}). Is the driver installed?",
+                    error
+                ));
+            }
+
+            let target = vigem_target_x360_alloc();
+            let error = vigem_target_add(client, target);
+            if error != VIGEM_ERROR_NONE {
+                vigem_target_free(target);
+                vigem_disconnect(client);
+                vigem_free(client);
+                return Err(format!(
+                    "Failed to create the virtual gamepad (error {:#x})",
+                    error
+                ));
+            }
+
+            Ok(Self { client, target })
+        }
+    }
+
+    pub fn send(&mut self, report: &Report) -> Result<()> {
+        let buttons = BUTTONS
+            .iter()
+            .filter(|&&(button, _)| report.buttons.contains(button))
+            .fold(0, |buttons, &(_, flag)| buttons | flag);
+        let trigger = |axis| (report.axis(axis) * u8::MAX as f32).round() as u8;
+        // XInput's vertical axes are positive upwards.
+        let thumb = |axis, sign: f32| (sign * report.axis(axis) * i16::MAX as f32).round() as i16;
+
+        let report = XusbReport {
+            buttons,
+            left_trigger: trigger(Axis::TriggerLeft),
+            right_trigger: trigger(Axis::TriggerRight),
+            thumb_lx: thumb(Axis::LeftX, 1.0),
+            thumb_ly: thumb(Axis::LeftY, -1.0),
+            thumb_rx: thumb(Axis::RightX, 1.0),
+            thumb_ry: thumb(Axis::RightY, -1.0),
+        };
+
+        // SAFETY: the client and target stay alive until the device is dropped.
+        let error = unsafe { vigem_target_x360_update(self.client, self.target, report) };
+        if error == VIGEM_ERROR_NONE {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to send virtual gamepad input (error {:#x})",
+                error
+            ))
+        }
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        // SAFETY: the client and target are only freed here.
+        unsafe {
+            vigem_target_remove(self.client, self.target);
+            vigem_target_free(self.target);
+            vigem_disconnect(self.client);
+            vigem_free(self.client);
+        }
+    }
+}